@@ -1,53 +1,114 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::time::{Duration, Instant};
 
-use crate::git::model::{Commit, CommitInspectData, InspectCacheEntry, Refs};
-use crate::graph::GraphRow;
+use ratatui::layout::Rect;
+
+use crate::git::model::{Commit, CommitInspectData, LineStats, Refs, SignatureTrust};
+use crate::graph::{compute_layout, row_height, GraphRow, GraphStyle, LayoutOptions};
+use crate::keymap::{Keymap, PendingKeys};
+use crate::references::ReferenceFinder;
+use crate::stats::ContributionStats;
+use crate::theme::Theme;
+pub use crate::widgets::DetailsTab;
+
+#[derive(Debug, Clone)]
+pub enum InspectCacheEntry {
+    Loading,
+    Ready(CommitInspectData),
+    Error(String),
+}
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Mode {
     Normal,
     Filter,
+    /// Editing the revision range prompt.
+    Revision,
 }
 
+const REVISION_HISTORY_LIMIT: usize = 50;
+pub const DEFAULT_DETAILS_HEIGHT: u16 = 16;
+const HELP_SCROLL_LIMIT: u16 = 60;
+pub const MIN_DETAILS_HEIGHT: u16 = 3;
+pub const DEFAULT_DETAILS_WIDTH_PERCENT: u16 = 45;
+pub const MIN_DETAILS_WIDTH_PERCENT: u16 = 20;
+pub const MAX_DETAILS_WIDTH_PERCENT: u16 = 80;
+const DETAILS_HEIGHT_STEP: i16 = 2;
+const DETAILS_WIDTH_STEP: i16 = 5;
+
+/// How the graph and details panes are arranged.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum DetailsTab {
-    Summary,
-    Files,
-    Diff,
+pub enum LayoutMode {
+    /// Side by side on wide terminals, stacked otherwise.
+    Auto,
+    /// Details below the graph.
+    Stacked,
+    /// Details to the right of the graph.
+    SideBySide,
 }
 
-impl DetailsTab {
-    pub fn title(self) -> &'static str {
+impl LayoutMode {
+    pub fn name(self) -> &'static str {
         match self {
-            DetailsTab::Summary => "Summary",
-            DetailsTab::Files => "Files",
-            DetailsTab::Diff => "Diff",
+            LayoutMode::Auto => "auto",
+            LayoutMode::Stacked => "stacked",
+            LayoutMode::SideBySide => "side-by-side",
         }
     }
 
-    pub fn next(self) -> Self {
-        match self {
-            DetailsTab::Summary => DetailsTab::Files,
-            DetailsTab::Files => DetailsTab::Diff,
-            DetailsTab::Diff => DetailsTab::Summary,
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "auto" => Some(LayoutMode::Auto),
+            "stacked" => Some(LayoutMode::Stacked),
+            "side-by-side" => Some(LayoutMode::SideBySide),
+            _ => None,
         }
     }
 
-    pub fn previous(self) -> Self {
+    pub fn next(self) -> Self {
         match self {
-            DetailsTab::Summary => DetailsTab::Diff,
-            DetailsTab::Files => DetailsTab::Summary,
-            DetailsTab::Diff => DetailsTab::Files,
+            LayoutMode::Auto => LayoutMode::Stacked,
+            LayoutMode::Stacked => LayoutMode::SideBySide,
+            LayoutMode::SideBySide => LayoutMode::Auto,
         }
     }
 }
+/// Screen areas from the last draw, used for mouse hit-testing.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct LayoutAreas {
+    pub sidebar: Rect,
+    pub graph: Rect,
+    pub details: Rect,
+    /// Whether details were drawn to the right of the graph.
+    pub side_by_side: bool,
+}
+
+/// Pane border currently being dragged with the mouse.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DragTarget {
+    DetailsSplit,
+}
+
+/// One row of the ref sidebar.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SidebarEntry {
+    /// Group header; `refnames` are the refs the group toggles together.
+    Section {
+        title: String,
+        refnames: Vec<String>,
+    },
+    Ref {
+        label: String,
+        refname: String,
+        oid: String,
+    },
+}
 
 pub struct App {
     pub commits: Vec<Commit>,
     pub refs: Refs,
     pub graph: Vec<GraphRow>,
-    pub colors_enabled: bool,
+    pub theme: Theme,
 
     /// Indices into `commits` that pass the current filter (or all, when no filter).
     pub filtered: Vec<usize>,
@@ -64,36 +125,115 @@ pub struct App {
     /// Whether the details panel is expanded (vs. collapsed).
     pub details_expanded: bool,
 
+    /// Height of the expanded details panel when stacked.
+    pub details_height: u16,
+    /// Share of the width given to details when side by side.
+    pub details_width_percent: u16,
+    /// Details fill the whole content area, hiding the graph.
+    pub details_fullscreen: bool,
+    pub layout_mode: LayoutMode,
+
     pub active_tab: DetailsTab,
     pub details_scroll: u16,
     pub help_open: bool,
+    pub help_scroll: u16,
+    pub branches_open: bool,
+    pub branches_scroll: u16,
+    /// Whether the author statistics panel is shown.
+    pub stats_open: bool,
+    pub stats_scroll: u16,
+
+    /// Whether the ref sidebar is shown, and whether it has keyboard focus.
+    pub sidebar_open: bool,
+    pub sidebar_focused: bool,
+    pub sidebar_selected: usize,
+
+    /// Refs (by refname) excluded from the revisions fed to `git log`.
+    pub hidden_refs: HashSet<String>,
+    /// Whether the session history starts from every ref (`--all`), which
+    /// is the only history hiding refs can change.
+    pub all_refs: bool,
+
+    /// Revision range entered at runtime; `None` uses the session default.
+    pub revisions: Option<Vec<String>>,
+    pub revision_input: String,
+    /// Previously applied ranges, oldest first.
+    pub revision_history: Vec<String>,
+    /// Position in `revision_history` while browsing it from the prompt.
+    pub revision_history_cursor: Option<usize>,
+
     pub inspect_cache: HashMap<String, InspectCacheEntry>,
+    /// Lines each commit added and removed, loaded when statistics open.
+    pub line_stats: HashMap<String, LineStats>,
+    /// Highlights and links issue and tracker references in messages.
+    pub references: ReferenceFinder,
+    /// Show authors and committers as recorded instead of through `.mailmap`.
+    pub raw_identity: bool,
+    pub keymap: Keymap,
+    /// Count prefix and partial key sequence typed in normal mode.
+    pub pending_keys: PendingKeys,
+
+    pub graph_style: GraphStyle,
+    /// Options the graph was laid out with, kept to redo the layout when
+    /// the first-parent view or an expanded merge changes.
+    pub layout_options: LayoutOptions,
+
+    /// First visible row of the graph and sidebar lists, kept across draws.
+    pub graph_offset: usize,
+    pub sidebar_offset: usize,
+    pub layout: LayoutAreas,
+    pub drag: Option<DragTarget>,
+
     pub status_message: Option<String>,
     pub status_deadline: Option<Instant>,
 }
 
 impl App {
-    pub fn new(
-        commits: Vec<Commit>,
-        refs: Refs,
-        graph: Vec<GraphRow>,
-        colors_enabled: bool,
-    ) -> Self {
+    pub fn new(commits: Vec<Commit>, refs: Refs, graph: Vec<GraphRow>, theme: Theme) -> Self {
         let filtered: Vec<usize> = (0..commits.len()).collect();
         App {
             commits,
             refs,
             graph,
-            colors_enabled,
+            theme,
             filtered,
             selected: 0,
             mode: Mode::Normal,
             filter: String::new(),
             details_expanded: true,
+            details_height: DEFAULT_DETAILS_HEIGHT,
+            details_width_percent: DEFAULT_DETAILS_WIDTH_PERCENT,
+            details_fullscreen: false,
+            layout_mode: LayoutMode::Auto,
             active_tab: DetailsTab::Summary,
             details_scroll: 0,
             help_open: false,
+            help_scroll: 0,
+            branches_open: false,
+            branches_scroll: 0,
+            stats_open: false,
+            stats_scroll: 0,
+            sidebar_open: false,
+            sidebar_focused: false,
+            sidebar_selected: 0,
+            hidden_refs: HashSet::new(),
+            all_refs: true,
+            revisions: None,
+            revision_input: String::new(),
+            revision_history: Vec::new(),
+            revision_history_cursor: None,
             inspect_cache: HashMap::new(),
+            line_stats: HashMap::new(),
+            references: ReferenceFinder::default(),
+            raw_identity: false,
+            keymap: Keymap::default(),
+            pending_keys: PendingKeys::default(),
+            graph_style: GraphStyle::default(),
+            layout_options: LayoutOptions::default(),
+            graph_offset: 0,
+            sidebar_offset: 0,
+            layout: LayoutAreas::default(),
+            drag: None,
             status_message: None,
             status_deadline: None,
         }
@@ -139,6 +279,38 @@ impl App {
         false
     }
 
+    /// Select the row at `index` within `filtered`.
+    pub fn select_index(&mut self, index: usize) -> bool {
+        if index < self.filtered.len() && index != self.selected {
+            self.selected = index;
+            self.details_scroll = 0;
+            return true;
+        }
+        false
+    }
+
+    /// Index within `filtered` of the commit drawn `line` lines below the
+    /// first visible one, accounting for rows taller than one line.
+    pub fn graph_index_at_line(&self, line: usize) -> usize {
+        let mut remaining = line;
+        for (index, &commit_idx) in self.filtered.iter().enumerate().skip(self.graph_offset) {
+            let height = row_height(&self.graph[commit_idx], self.graph_style);
+            if remaining < height {
+                return index;
+            }
+            remaining -= height;
+        }
+        self.filtered.len()
+    }
+
+    pub fn move_by(&mut self, amount: isize) -> bool {
+        if self.filtered.is_empty() {
+            return false;
+        }
+        let target = (self.selected as isize + amount).clamp(0, self.filtered.len() as isize - 1);
+        self.select_index(target as usize)
+    }
+
     pub fn search_next(&mut self) -> bool {
         if self.filter.is_empty() || self.filtered.is_empty() {
             return false;
@@ -167,6 +339,16 @@ impl App {
         self.details_scroll = 0;
     }
 
+    pub fn select_tab(&mut self, tab: DetailsTab) -> bool {
+        if self.active_tab == tab {
+            return false;
+        }
+        self.active_tab = tab;
+        self.details_scroll = 0;
+        self.prepare_selected_inspect_retry();
+        true
+    }
+
     pub fn cycle_tab_forward(&mut self) {
         self.active_tab = self.active_tab.next();
         self.details_scroll = 0;
@@ -187,6 +369,54 @@ impl App {
         }
     }
 
+    pub fn cycle_layout_mode(&mut self) {
+        self.layout_mode = self.layout_mode.next();
+        self.details_fullscreen = false;
+    }
+
+    pub fn toggle_details_fullscreen(&mut self) {
+        self.details_fullscreen = !self.details_fullscreen;
+        if self.details_fullscreen {
+            self.details_expanded = true;
+        }
+    }
+
+    /// Grow (positive) or shrink (negative) the details pane by one step in
+    /// the direction it was last laid out.
+    pub fn resize_details(&mut self, steps: i16) {
+        if self.layout.side_by_side {
+            let percent = self.details_width_percent as i16 + steps * DETAILS_WIDTH_STEP;
+            self.details_width_percent =
+                (percent.max(0) as u16).clamp(MIN_DETAILS_WIDTH_PERCENT, MAX_DETAILS_WIDTH_PERCENT);
+        } else {
+            let height = self.details_height as i16 + steps * DETAILS_HEIGHT_STEP;
+            self.details_height = (height.max(0) as u16).max(MIN_DETAILS_HEIGHT);
+        }
+        self.details_expanded = true;
+    }
+
+    /// Resize the side-by-side details pane so its left border sits on `column`.
+    pub fn drag_details_split_horizontal(&mut self, column: u16, bounds: Rect) {
+        if bounds.width == 0 {
+            return;
+        }
+        let right = bounds.x + bounds.width;
+        let width = right.saturating_sub(column) as u32;
+        let percent = (width * 100 / bounds.width as u32) as u16;
+        self.details_width_percent =
+            percent.clamp(MIN_DETAILS_WIDTH_PERCENT, MAX_DETAILS_WIDTH_PERCENT);
+    }
+
+    /// Resize the details panel so its top border sits on `row`, within `bounds`.
+    pub fn drag_details_split(&mut self, row: u16, bounds: Rect) {
+        let bottom = bounds.y + bounds.height;
+        let max_height = bounds.height.saturating_sub(MIN_DETAILS_HEIGHT);
+        self.details_height = bottom
+            .saturating_sub(row)
+            .clamp(MIN_DETAILS_HEIGHT, max_height.max(MIN_DETAILS_HEIGHT));
+        self.details_expanded = true;
+    }
+
     pub fn clamp_details_scroll(&mut self, max_scroll: u16) {
         if self.details_scroll > max_scroll {
             self.details_scroll = max_scroll;
@@ -196,12 +426,297 @@ impl App {
     // Help overlay
     pub fn toggle_help(&mut self) {
         self.help_open = !self.help_open;
+        self.help_scroll = 0;
+    }
+
+    pub fn scroll_help_lines(&mut self, amount: i16) {
+        if amount >= 0 {
+            self.help_scroll = self
+                .help_scroll
+                .saturating_add(amount as u16)
+                .min(HELP_SCROLL_LIMIT);
+        } else {
+            self.help_scroll = self.help_scroll.saturating_sub((-amount) as u16);
+        }
     }
 
     pub fn close_help(&mut self) {
         self.help_open = false;
     }
 
+    // Branch panel
+    pub fn toggle_branches(&mut self) {
+        self.branches_open = !self.branches_open;
+        self.branches_scroll = 0;
+    }
+
+    pub fn close_branches(&mut self) {
+        self.branches_open = false;
+    }
+
+    pub fn scroll_branches_lines(&mut self, amount: i16) {
+        let max_scroll = self.refs.branches.len().saturating_sub(1) as u16;
+        if amount >= 0 {
+            self.branches_scroll = self
+                .branches_scroll
+                .saturating_add(amount as u16)
+                .min(max_scroll);
+        } else {
+            self.branches_scroll = self.branches_scroll.saturating_sub((-amount) as u16);
+        }
+    }
+
+    // Author statistics
+    pub fn toggle_stats(&mut self) {
+        self.stats_open = !self.stats_open;
+        self.stats_scroll = 0;
+    }
+
+    pub fn close_stats(&mut self) {
+        self.stats_open = false;
+    }
+
+    pub fn scroll_stats_lines(&mut self, amount: i16) {
+        let max_scroll = self.contribution_stats().authors.len() as u16;
+        if amount >= 0 {
+            self.stats_scroll = self
+                .stats_scroll
+                .saturating_add(amount as u16)
+                .min(max_scroll);
+        } else {
+            self.stats_scroll = self.stats_scroll.saturating_sub((-amount) as u16);
+        }
+    }
+
+    /// Per-author statistics of the commits the current filter shows.
+    pub fn contribution_stats(&self) -> ContributionStats {
+        ContributionStats::new(
+            self.filtered.iter().map(|&index| &self.commits[index]),
+            |oid| self.line_stats.get(oid).copied(),
+        )
+    }
+
+    /// Shown commits whose line counts have not been loaded yet.
+    pub fn missing_line_stats(&self) -> Vec<String> {
+        self.filtered
+            .iter()
+            .map(|&index| &self.commits[index].oid)
+            .filter(|oid| !self.line_stats.contains_key(*oid))
+            .cloned()
+            .collect()
+    }
+
+    // Ref sidebar
+    pub fn toggle_sidebar(&mut self) {
+        if !self.sidebar_open {
+            self.sidebar_open = true;
+            self.sidebar_focused = true;
+        } else if !self.sidebar_focused {
+            self.sidebar_focused = true;
+        } else {
+            self.sidebar_open = false;
+            self.sidebar_focused = false;
+        }
+    }
+
+    pub fn unfocus_sidebar(&mut self) {
+        self.sidebar_focused = false;
+    }
+
+    pub fn sidebar_entries(&self) -> Vec<SidebarEntry> {
+        let mut entries = Vec::new();
+        push_section(
+            &mut entries,
+            "Branches".to_string(),
+            &self.refs.branches,
+            "refs/heads/",
+        );
+        for (remote, refs) in self.refs.remotes_by_name() {
+            let refs: Vec<(String, String)> = refs.into_iter().cloned().collect();
+            push_section(
+                &mut entries,
+                format!("Remote: {}", remote),
+                &refs,
+                "refs/remotes/",
+            );
+        }
+        push_section(
+            &mut entries,
+            "Tags".to_string(),
+            &self.refs.tags,
+            "refs/tags/",
+        );
+        push_section(&mut entries, "Stashes".to_string(), &self.refs.stashes, "");
+        entries
+    }
+
+    pub fn sidebar_select(&mut self, index: usize) {
+        if index < self.sidebar_entries().len() {
+            self.sidebar_selected = index;
+        }
+    }
+
+    pub fn sidebar_move(&mut self, amount: isize) {
+        let len = self.sidebar_entries().len();
+        if len == 0 {
+            self.sidebar_selected = 0;
+            return;
+        }
+        let target = self.sidebar_selected as isize + amount;
+        self.sidebar_selected = target.clamp(0, len as isize - 1) as usize;
+    }
+
+    /// Whether hiding refs changes the graph: it does for the `--all`
+    /// history, but not for HEAD alone or a revision range.
+    pub fn can_hide_refs(&self) -> bool {
+        self.all_refs && self.revisions.is_none()
+    }
+
+    /// Whether `refname` is drawn; refs are only shown hidden while hiding
+    /// them changes the graph.
+    pub fn is_ref_visible(&self, refname: &str) -> bool {
+        !self.can_hide_refs() || !self.hidden_refs.contains(refname)
+    }
+
+    /// Toggle visibility of the selected ref, or of every ref in the selected
+    /// section. Returns true when the set of hidden refs changed; otherwise
+    /// says in the status bar why the ref cannot be hidden.
+    pub fn toggle_selected_ref_visibility(&mut self) -> bool {
+        let Some(entry) = self
+            .sidebar_entries()
+            .into_iter()
+            .nth(self.sidebar_selected)
+        else {
+            return false;
+        };
+        if !self.can_hide_refs() {
+            self.set_status(if self.all_refs {
+                "Refs cannot be hidden while a revision range is shown"
+            } else {
+                "Refs can only be hidden in the --all history"
+            });
+            return false;
+        }
+
+        let (mut refnames, label) = match entry {
+            SidebarEntry::Section { refnames, title } => (refnames, title),
+            SidebarEntry::Ref { refname, label, .. } => (vec![refname], label),
+        };
+        refnames.retain(|refname| Refs::can_hide(refname));
+        if refnames.is_empty() {
+            self.set_status(format!("{} is not a starting point of the graph", label));
+            return false;
+        }
+
+        if refnames
+            .iter()
+            .all(|refname| self.hidden_refs.contains(refname))
+        {
+            for refname in &refnames {
+                self.hidden_refs.remove(refname);
+            }
+        } else {
+            self.hidden_refs.extend(refnames);
+        }
+        true
+    }
+
+    /// Select the commit at the tip of the selected sidebar ref.
+    /// Returns `Some(changed)` when the tip is loaded, `None` otherwise.
+    pub fn jump_to_selected_ref(&mut self) -> Option<bool> {
+        let entry = self
+            .sidebar_entries()
+            .into_iter()
+            .nth(self.sidebar_selected)?;
+        let SidebarEntry::Ref { oid, .. } = entry else {
+            return None;
+        };
+        self.select_oid(&oid)
+    }
+
+    /// Select the commit with the given oid if it passes the current filter.
+    pub fn select_oid(&mut self, oid: &str) -> Option<bool> {
+        let position = self
+            .filtered
+            .iter()
+            .position(|&index| self.commits[index].oid == oid)?;
+        if position == self.selected {
+            return Some(false);
+        }
+        self.selected = position;
+        self.details_scroll = 0;
+        Some(true)
+    }
+
+    // Revision range prompt
+    pub fn enter_revision_mode(&mut self) {
+        self.mode = Mode::Revision;
+        self.revision_input = self
+            .revisions
+            .as_ref()
+            .map(|revisions| revisions.join(" "))
+            .unwrap_or_default();
+        self.revision_history_cursor = None;
+    }
+
+    pub fn exit_revision_mode(&mut self) {
+        self.mode = Mode::Normal;
+        self.revision_input.clear();
+        self.revision_history_cursor = None;
+    }
+
+    /// Leave the prompt and return the entered range.
+    pub fn confirm_revision_input(&mut self) -> String {
+        self.mode = Mode::Normal;
+        self.revision_history_cursor = None;
+        std::mem::take(&mut self.revision_input).trim().to_string()
+    }
+
+    pub fn revision_push(&mut self, c: char) {
+        self.revision_input.push(c);
+    }
+
+    pub fn revision_pop(&mut self) {
+        self.revision_input.pop();
+    }
+
+    pub fn revision_history_previous(&mut self) {
+        if self.revision_history.is_empty() {
+            return;
+        }
+        let cursor = match self.revision_history_cursor {
+            Some(cursor) => cursor.saturating_sub(1),
+            None => self.revision_history.len() - 1,
+        };
+        self.revision_history_cursor = Some(cursor);
+        self.revision_input = self.revision_history[cursor].clone();
+    }
+
+    pub fn revision_history_next(&mut self) {
+        let Some(cursor) = self.revision_history_cursor else {
+            return;
+        };
+        if cursor + 1 < self.revision_history.len() {
+            self.revision_history_cursor = Some(cursor + 1);
+            self.revision_input = self.revision_history[cursor + 1].clone();
+        } else {
+            self.revision_history_cursor = None;
+            self.revision_input.clear();
+        }
+    }
+
+    /// Record an applied range, moving repeats to the end.
+    pub fn remember_revision_range(&mut self, range: &str) {
+        if range.is_empty() {
+            return;
+        }
+        self.revision_history.retain(|existing| existing != range);
+        self.revision_history.push(range.to_string());
+        if self.revision_history.len() > REVISION_HISTORY_LIMIT {
+            self.revision_history.remove(0);
+        }
+    }
+
     // Filter
     pub fn enter_filter_mode(&mut self) {
         self.mode = Mode::Filter;
@@ -227,11 +742,13 @@ impl App {
         self.recompute_filter();
     }
 
-    pub fn replace_data(&mut self, commits: Vec<Commit>, refs: Refs, graph: Vec<GraphRow>) {
+    /// Swap in freshly loaded history and lay out its graph.
+    pub fn replace_data(&mut self, commits: Vec<Commit>, refs: Refs) {
         let selected_oid = self.selected_commit().map(|commit| commit.oid.clone());
+        self.layout_options.branch_names = refs.branch_names();
+        self.graph = compute_layout(&commits, &self.layout_options);
         self.commits = commits;
         self.refs = refs;
-        self.graph = graph;
         self.inspect_cache.clear();
         self.active_tab = DetailsTab::Summary;
         self.details_scroll = 0;
@@ -252,6 +769,68 @@ impl App {
         } else if self.selected >= self.filtered.len() {
             self.selected = self.filtered.len() - 1;
         }
+
+        let entries = self.sidebar_entries().len();
+        self.sidebar_selected = self.sidebar_selected.min(entries.saturating_sub(1));
+    }
+
+    /// Switch between the full graph and first-parent history.
+    pub fn toggle_first_parent(&mut self) {
+        self.layout_options.first_parent = !self.layout_options.first_parent;
+        self.relayout();
+        self.set_status(if self.layout_options.first_parent {
+            "First-parent view"
+        } else {
+            "Full graph view"
+        });
+    }
+
+    /// Switch the details between `.mailmap` identities and the ones
+    /// recorded in the commits. Search always matches the mapped identity.
+    pub fn toggle_raw_identity(&mut self) {
+        self.raw_identity = !self.raw_identity;
+        self.set_status(if self.raw_identity {
+            "Showing authors as recorded"
+        } else {
+            "Showing authors through .mailmap"
+        });
+    }
+
+    /// Expand the selected merge in place to show the commits it brought
+    /// in, or collapse it again. Returns true when the graph changed.
+    pub fn toggle_selected_merge(&mut self) -> bool {
+        let Some(commit) = self.selected_commit() else {
+            return false;
+        };
+        if !self.layout_options.first_parent {
+            self.set_status("Merges expand in the first-parent view");
+            return false;
+        }
+        if commit.parents.len() < 2 {
+            self.set_status("Not a merge commit");
+            return false;
+        }
+        let oid = commit.oid.clone();
+        if !self.layout_options.expanded_merges.remove(&oid) {
+            self.layout_options.expanded_merges.insert(oid);
+        }
+        self.relayout();
+        true
+    }
+
+    /// Lay the graph out again, keeping the selection on the same commit or,
+    /// if it is now hidden, on the next one shown.
+    fn relayout(&mut self) {
+        let selected_index = self.filtered.get(self.selected).copied();
+        self.graph = compute_layout(&self.commits, &self.layout_options);
+        self.recompute_filter();
+        if let Some(selected_index) = selected_index {
+            self.selected = self
+                .filtered
+                .iter()
+                .position(|&index| index >= selected_index)
+                .unwrap_or(self.filtered.len().saturating_sub(1));
+        }
     }
 
     pub fn selected_commit(&self) -> Option<&Commit> {
@@ -265,7 +844,11 @@ impl App {
     }
 
     pub fn should_load_selected_inspect(&self) -> bool {
-        if self.help_open || matches!(self.active_tab, DetailsTab::Summary) {
+        if self.help_open
+            || self.branches_open
+            || self.stats_open
+            || matches!(self.active_tab, DetailsTab::Summary)
+        {
             return false;
         }
 
@@ -273,7 +856,7 @@ impl App {
             return false;
         };
 
-        self.inspect_cache.get(oid).is_none()
+        !self.inspect_cache.contains_key(oid)
     }
 
     pub fn insert_loading_for_selected(&mut self) -> Option<String> {
@@ -339,17 +922,26 @@ impl App {
         let query = self.filter.to_lowercase();
         let tokens: Vec<&str> = query.split_whitespace().collect();
 
+        let shown = |index: usize| !self.graph.get(index).is_some_and(|row| row.hidden);
         if tokens.is_empty() {
-            self.filtered = (0..self.commits.len()).collect();
+            self.filtered = (0..self.commits.len())
+                .filter(|&index| shown(index))
+                .collect();
         } else {
             self.filtered = self
                 .commits
                 .iter()
                 .enumerate()
+                .filter(|&(index, _)| shown(index))
                 .filter(|(_, commit)| {
                     let mut haystack = format!(
-                        "{}\n{}\n{}\n{}",
-                        commit.subject, commit.author, commit.author_email, commit.oid
+                        "{}\n{}\n{}\n{}\n{}\n{}",
+                        commit.subject,
+                        commit.author,
+                        commit.author_email,
+                        commit.committer,
+                        commit.committer_email,
+                        commit.oid
                     );
 
                     if !commit.body.is_empty() {
@@ -364,7 +956,15 @@ impl App {
                     }
 
                     let haystack = haystack.to_lowercase();
-                    tokens.iter().all(|token| haystack.contains(token))
+                    tokens.iter().all(|token| {
+                        if let Some(spec) = token.strip_prefix("trailer:") {
+                            trailer_matches(commit, spec)
+                        } else if let Some(trust) = token.strip_prefix("sig:") {
+                            signature_matches(commit, trust)
+                        } else {
+                            haystack.contains(token)
+                        }
+                    })
                 })
                 .map(|(i, _)| i)
                 .collect();
@@ -380,10 +980,56 @@ impl App {
     }
 }
 
+fn push_section(
+    entries: &mut Vec<SidebarEntry>,
+    title: String,
+    refs: &[(String, String)],
+    prefix: &str,
+) {
+    if refs.is_empty() {
+        return;
+    }
+
+    entries.push(SidebarEntry::Section {
+        title,
+        refnames: refs.iter().map(|(refname, _)| refname.clone()).collect(),
+    });
+    for (refname, oid) in refs {
+        entries.push(SidebarEntry::Ref {
+            label: refname.strip_prefix(prefix).unwrap_or(refname).to_string(),
+            refname: refname.clone(),
+            oid: oid.clone(),
+        });
+    }
+}
+
+/// A `sig:` filter: `verified`, `unknown`, `bad` or `unsigned`, or `signed`
+/// for any signature.
+fn signature_matches(commit: &Commit, spec: &str) -> bool {
+    let trust = commit.signature.status.trust();
+    match spec {
+        "signed" => trust != SignatureTrust::Unsigned,
+        name => trust.name() == name,
+    }
+}
+
+/// A lowercased `trailer:` filter: `key` matches commits with that trailer,
+/// `key=text` those where one of its values contains `text`.
+fn trailer_matches(commit: &Commit, spec: &str) -> bool {
+    let (key, text) = match spec.split_once('=') {
+        Some((key, text)) => (key, Some(text)),
+        None => (spec, None),
+    };
+    commit.trailer_values(key).any(|value| match text {
+        Some(text) => value.to_lowercase().contains(text),
+        None => true,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::git::model::Commit;
+    use crate::git::model::{Commit, Signature, SignatureStatus, Trailer};
 
     fn commit(oid: &str, subject: &str) -> Commit {
         Commit {
@@ -394,12 +1040,13 @@ mod tests {
             timestamp: 0,
             subject: subject.to_string(),
             body: String::new(),
+            ..Default::default()
         }
     }
 
     fn app_with_commits(commits: Vec<Commit>) -> App {
         let graph = Vec::new();
-        App::new(commits, Refs::default(), graph, true)
+        App::new(commits, Refs::default(), graph, Theme::default())
     }
 
     #[test]
@@ -433,6 +1080,347 @@ mod tests {
         assert_eq!(app.selected, 2);
     }
 
+    #[test]
+    fn test_filter_by_trailer() {
+        let with_trailers = |oid: &str, trailers: &[(&str, &str)]| Commit {
+            trailers: trailers
+                .iter()
+                .map(|(key, value)| Trailer {
+                    key: key.to_string(),
+                    value: value.to_string(),
+                })
+                .collect(),
+            ..commit(oid, "change")
+        };
+        let mut app = app_with_commits(vec![
+            with_trailers("a", &[("Reviewed-by", "Alice <alice@example.com>")]),
+            with_trailers("b", &[("Reviewed-by", "Bob <bob@example.com>")]),
+            with_trailers("c", &[("Signed-off-by", "Alice <alice@example.com>")]),
+        ]);
+        let matches = |app: &mut App, filter: &str| {
+            app.filter = filter.to_string();
+            app.filter_push(' ');
+            app.filter_pop();
+            app.filtered
+                .iter()
+                .map(|&i| app.commits[i].oid.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(matches(&mut app, "trailer:reviewed-by=alice"), ["a"]);
+        assert_eq!(matches(&mut app, "trailer:Reviewed-By"), ["a", "b"]);
+        assert_eq!(
+            matches(&mut app, "change trailer:signed-off-by=ALICE"),
+            ["c"]
+        );
+        assert!(matches(&mut app, "trailer:change-id").is_empty());
+    }
+
+    #[test]
+    fn test_filter_by_signature() {
+        let signed = |oid: &str, status| Commit {
+            signature: Signature {
+                status,
+                ..Signature::default()
+            },
+            ..commit(oid, "change")
+        };
+        let mut app = app_with_commits(vec![
+            signed("a", SignatureStatus::Good),
+            signed("b", SignatureStatus::CannotCheck),
+            signed("c", SignatureStatus::GoodRevokedKey),
+            signed("d", SignatureStatus::Unsigned),
+        ]);
+        let mut matches = |filter: &str| {
+            app.filter = filter.to_string();
+            app.filter_push(' ');
+            app.filter_pop();
+            app.filtered
+                .iter()
+                .map(|&i| app.commits[i].oid.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(matches("sig:verified"), ["a"]);
+        assert_eq!(matches("sig:unknown"), ["b"]);
+        assert_eq!(matches("sig:bad"), ["c"]);
+        assert_eq!(matches("sig:unsigned"), ["d"]);
+        assert_eq!(matches("sig:signed"), ["a", "b", "c"]);
+    }
+
+    #[test]
+    fn test_filter_matches_mailmap_identity() {
+        let by = |oid: &str, author: &str, raw_author: &str| Commit {
+            author: author.to_string(),
+            raw_author: raw_author.to_string(),
+            ..commit(oid, "change")
+        };
+        let mut app = app_with_commits(vec![
+            by("a", "Ada Lovelace", "ada"),
+            by("b", "Ada Lovelace", "Ada L."),
+        ]);
+        app.toggle_raw_identity();
+        assert!(app.raw_identity);
+        app.filter = "lovelace".to_string();
+        app.filter_push(' ');
+        app.filter_pop();
+        assert_eq!(app.filtered, [0, 1]);
+        app.filter = "ada l.".to_string();
+        app.filter_push(' ');
+        app.filter_pop();
+        assert!(app.filtered.is_empty());
+    }
+
+    #[test]
+    fn test_stats_follow_filter_and_loaded_line_counts() {
+        let mut app = app_with_commits(vec![
+            commit("a", "fix parser"),
+            commit("b", "add parser"),
+            commit("c", "docs"),
+        ]);
+        app.line_stats.insert(
+            "a".to_string(),
+            LineStats {
+                added: 4,
+                removed: 1,
+            },
+        );
+        app.filter = "parser".to_string();
+        app.filter_push(' ');
+        app.filter_pop();
+
+        assert_eq!(app.missing_line_stats(), ["b"]);
+        let stats = app.contribution_stats();
+        assert_eq!((stats.commits, stats.missing_lines), (2, 1));
+        assert_eq!(
+            stats.authors[0].lines,
+            LineStats {
+                added: 4,
+                removed: 1
+            }
+        );
+
+        app.toggle_stats();
+        assert!(app.stats_open && !app.should_load_selected_inspect());
+        app.scroll_stats_lines(5);
+        assert_eq!(app.stats_scroll, 1);
+    }
+
+    fn refs_fixture() -> Refs {
+        Refs {
+            branches: vec![("refs/heads/main".to_string(), "c".to_string())],
+            remotes: vec![
+                ("refs/remotes/origin/main".to_string(), "b".to_string()),
+                ("refs/remotes/origin/old".to_string(), "a".to_string()),
+                ("refs/remotes/fork/topic".to_string(), "a".to_string()),
+            ],
+            ..Refs::default()
+        }
+    }
+
+    #[test]
+    fn test_sidebar_groups_remotes_by_name() {
+        let mut app = app_with_commits(vec![commit("c", "third"), commit("b", "second")]);
+        app.refs = refs_fixture();
+        let titles: Vec<String> = app
+            .sidebar_entries()
+            .into_iter()
+            .filter_map(|entry| match entry {
+                SidebarEntry::Section { title, .. } => Some(title),
+                SidebarEntry::Ref { .. } => None,
+            })
+            .collect();
+        assert_eq!(titles, vec!["Branches", "Remote: origin", "Remote: fork"]);
+    }
+
+    #[test]
+    fn test_sidebar_jump_and_section_visibility_toggle() {
+        let mut app = app_with_commits(vec![commit("c", "third"), commit("b", "second")]);
+        app.refs = refs_fixture();
+
+        // Entry 3 is origin/main, pointing at "b".
+        app.sidebar_move(3);
+        assert_eq!(app.jump_to_selected_ref(), Some(true));
+        assert_eq!(app.selected_commit_oid(), Some("b"));
+
+        // Entry 4 is origin/old, whose tip is not loaded.
+        app.sidebar_move(1);
+        assert_eq!(app.jump_to_selected_ref(), None);
+
+        // Entry 2 is the "Remote: origin" header: hides the whole group.
+        app.sidebar_move(-2);
+        assert!(app.toggle_selected_ref_visibility());
+        assert!(!app.is_ref_visible("refs/remotes/origin/main"));
+        assert!(!app.is_ref_visible("refs/remotes/origin/old"));
+        assert!(app.is_ref_visible("refs/remotes/fork/topic"));
+        assert!(app.toggle_selected_ref_visibility());
+        assert!(app.hidden_refs.is_empty());
+    }
+
+    #[test]
+    fn test_ref_visibility_only_toggles_refs_the_graph_can_hide() {
+        let mut app = app_with_commits(vec![commit("c", "third"), commit("b", "second")]);
+        app.refs = Refs {
+            stashes: vec![
+                ("stash@{0}".to_string(), "b".to_string()),
+                ("stash@{1}".to_string(), "c".to_string()),
+            ],
+            ..refs_fixture()
+        };
+
+        // The last entry is stash@{1}, which `--all` does not start from.
+        app.sidebar_move(isize::MAX / 2);
+        assert!(!app.toggle_selected_ref_visibility());
+        assert!(app.hidden_refs.is_empty());
+        assert!(app.status_message.is_some());
+
+        // The Stashes header only hides the newest stash.
+        app.sidebar_move(-2);
+        assert!(app.toggle_selected_ref_visibility());
+        assert_eq!(app.hidden_refs, HashSet::from(["stash@{0}".to_string()]));
+        assert!(!app.is_ref_visible("stash@{0}"));
+
+        // A revision range or a HEAD-only history ignores hidden refs.
+        app.revisions = Some(vec!["main..feature".to_string()]);
+        assert!(app.is_ref_visible("stash@{0}"));
+        assert!(!app.toggle_selected_ref_visibility());
+        app.revisions = None;
+        app.all_refs = false;
+        assert!(app.is_ref_visible("stash@{0}"));
+        assert!(!app.toggle_selected_ref_visibility());
+        assert_eq!(app.hidden_refs.len(), 1);
+    }
+
+    #[test]
+    fn test_revision_history_browsing() {
+        let mut app = app_with_commits(vec![commit("a", "first")]);
+        app.remember_revision_range("main..feature");
+        app.remember_revision_range("^origin/main HEAD");
+        app.remember_revision_range("main..feature");
+        assert_eq!(
+            app.revision_history,
+            vec!["^origin/main HEAD", "main..feature"]
+        );
+
+        app.enter_revision_mode();
+        app.revision_history_previous();
+        assert_eq!(app.revision_input, "main..feature");
+        app.revision_history_previous();
+        assert_eq!(app.revision_input, "^origin/main HEAD");
+        app.revision_history_next();
+        assert_eq!(app.revision_input, "main..feature");
+        app.revision_history_next();
+        assert!(app.revision_input.is_empty());
+        assert_eq!(app.confirm_revision_input(), "");
+        assert_eq!(app.mode, Mode::Normal);
+    }
+
+    #[test]
+    fn test_move_by_clamps_to_bounds() {
+        let mut app = app_with_commits(vec![commit("a", "1"), commit("b", "2"), commit("c", "3")]);
+        assert!(app.move_by(10));
+        assert_eq!(app.selected, 2);
+        assert!(!app.move_by(1));
+        assert!(app.move_by(-3));
+        assert_eq!(app.selected, 0);
+        assert!(!app.select_index(5));
+    }
+
+    #[test]
+    fn test_graph_index_at_line_counts_wide_rows() {
+        use crate::graph::lanes::GraphCell;
+        use crate::graph::GraphSpacing;
+
+        let row = |cells: Vec<GraphCell>| GraphRow {
+            commit_lane: 0,
+            colors: vec![0; cells.len()],
+            cells,
+            continues_below: true,
+            hidden: false,
+        };
+        let commits = vec![commit("a", "1"), commit("b", "2"), commit("c", "3")];
+        let graph = vec![
+            row(vec![GraphCell::MergeCommit, GraphCell::CornerDownLeft]),
+            row(vec![GraphCell::Commit, GraphCell::Vertical]),
+            row(vec![GraphCell::Commit, GraphCell::CornerUpLeft]),
+        ];
+        let mut app = App::new(commits, Refs::default(), graph, Theme::default());
+        assert_eq!(app.graph_index_at_line(1), 1);
+
+        app.graph_style.spacing = GraphSpacing::Wide;
+        let indices: Vec<usize> = (0..6).map(|line| app.graph_index_at_line(line)).collect();
+        assert_eq!(indices, [0, 0, 1, 2, 2, 3]);
+    }
+
+    #[test]
+    fn test_first_parent_toggle_keeps_selection_nearby() {
+        let with_parents = |oid: &str, parents: &[&str]| Commit {
+            parents: parents.iter().map(|parent| parent.to_string()).collect(),
+            ..commit(oid, oid)
+        };
+        let commits = vec![
+            with_parents("M", &["m1", "t1"]),
+            with_parents("t1", &["base"]),
+            with_parents("m1", &["base"]),
+            with_parents("base", &[]),
+        ];
+        let mut app = app_with_commits(Vec::new());
+        app.replace_data(commits, Refs::default());
+        assert!(app.select_oid("t1").is_some());
+
+        app.toggle_first_parent();
+        assert_eq!(app.filtered, vec![0, 2, 3]);
+        assert_eq!(app.selected_commit_oid(), Some("m1"));
+
+        app.select_index(0);
+        assert!(app.toggle_selected_merge());
+        assert_eq!(app.filtered, vec![0, 1, 2, 3]);
+        assert_eq!(app.selected_commit_oid(), Some("M"));
+
+        assert!(app.toggle_selected_merge());
+        app.toggle_first_parent();
+        assert_eq!(app.filtered.len(), 4);
+        assert!(!app.toggle_selected_merge());
+    }
+
+    #[test]
+    fn test_drag_details_split_clamps_height() {
+        let mut app = app_with_commits(vec![commit("a", "first")]);
+        let bounds = Rect::new(0, 0, 80, 40);
+        app.drag_details_split(30, bounds);
+        assert_eq!(app.details_height, 10);
+        app.drag_details_split(0, bounds);
+        assert_eq!(app.details_height, 37);
+        app.drag_details_split(39, bounds);
+        assert_eq!(app.details_height, MIN_DETAILS_HEIGHT);
+    }
+
+    #[test]
+    fn test_resize_details_follows_orientation() {
+        let mut app = app_with_commits(vec![commit("a", "first")]);
+        app.resize_details(1);
+        assert_eq!(app.details_height, DEFAULT_DETAILS_HEIGHT + 2);
+        app.resize_details(-20);
+        assert_eq!(app.details_height, MIN_DETAILS_HEIGHT);
+
+        app.layout.side_by_side = true;
+        app.resize_details(1);
+        assert_eq!(app.details_width_percent, DEFAULT_DETAILS_WIDTH_PERCENT + 5);
+        app.resize_details(100);
+        assert_eq!(app.details_width_percent, 80);
+        app.drag_details_split_horizontal(50, Rect::new(0, 0, 100, 30));
+        assert_eq!(app.details_width_percent, 50);
+    }
+
+    #[test]
+    fn test_layout_mode_names_round_trip() {
+        let mut mode = LayoutMode::Auto;
+        for _ in 0..3 {
+            assert_eq!(LayoutMode::from_name(mode.name()), Some(mode));
+            mode = mode.next();
+        }
+        assert_eq!(mode, LayoutMode::Auto);
+        assert_eq!(LayoutMode::from_name("diagonal"), None);
+    }
+
     #[test]
     fn test_details_scroll_clamping() {
         let mut app = app_with_commits(vec![commit("a", "first")]);
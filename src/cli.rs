@@ -1,4 +1,4 @@
-use clap::Parser;
+use clap::{Args, Parser, Subcommand};
 
 #[derive(Parser, Debug)]
 #[command(
@@ -9,23 +9,80 @@ use clap::Parser;
                   Navigate with j/k or arrow keys. Press q to quit."
 )]
 pub struct Cli {
-    /// Show all branches (not just HEAD)
-    #[arg(long, default_value_t = true, action = clap::ArgAction::Set)]
-    pub all: bool,
+    /// Show all branches (not just HEAD) [default: true]
+    #[arg(long, global = true, value_name = "BOOL", action = clap::ArgAction::Set)]
+    pub all: Option<bool>,
 
-    /// Maximum number of commits to display
-    #[arg(long, short = 'n', default_value_t = 200)]
-    pub max: usize,
+    /// Maximum number of commits to display [default: 200]
+    #[arg(long, global = true, short = 'n')]
+    pub max: Option<usize>,
 
     /// Exclude commits reachable from this revision boundary (e.g. HEAD~500)
-    #[arg(long = "exclude-reachable-from", alias = "since", value_name = "REV")]
+    #[arg(
+        long = "exclude-reachable-from",
+        global = true,
+        alias = "since",
+        value_name = "REV"
+    )]
     pub exclude_reachable_from: Option<String>,
 
+    /// Revision range to show instead of --all (e.g. "main..feature")
+    #[arg(long, global = true, value_name = "REVS", allow_hyphen_values = true)]
+    pub range: Option<String>,
+
     /// Path to the git repository (default: current directory)
-    #[arg(long)]
+    #[arg(long, global = true)]
     pub repo: Option<String>,
 
+    /// Remote whose web UI commits are opened in (default: the current branch's upstream remote, then origin)
+    #[arg(long, global = true, value_name = "NAME")]
+    pub remote: Option<String>,
+
+    /// Colour theme: auto, default, dark, light, high-contrast, monochrome or a custom theme
+    #[arg(long, global = true, value_name = "NAME")]
+    pub theme: Option<String>,
+
+    /// Graph characters: rounded, square or ascii
+    #[arg(long, global = true, value_name = "GLYPHS", value_parser = ["rounded", "square", "ascii"])]
+    pub graph_glyphs: Option<String>,
+
+    /// Graph spacing: compact, normal or wide
+    #[arg(long, global = true, value_name = "SPACING", value_parser = ["compact", "normal", "wide"])]
+    pub graph_spacing: Option<String>,
+
+    /// Graph columns drawn before further lanes collapse into an overflow marker [default: 16]
+    #[arg(long, global = true, value_name = "N", value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+    pub max_lanes: Option<usize>,
+
     /// Disable coloured styling and use monochrome rendering
-    #[arg(long)]
+    #[arg(long, global = true)]
     pub no_color: bool,
+
+    /// Print commits, refs and graph layout as JSON and exit; `lines` writes JSON Lines
+    #[arg(long, value_name = "STYLE", num_args = 0..=1, default_missing_value = "document", value_parser = ["document", "lines"])]
+    pub json: Option<String>,
+
+    #[command(subcommand)]
+    pub command: Option<Commands>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Commands {
+    /// Print the commit graph to stdout instead of starting the TUI
+    Export(ExportArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct ExportArgs {
+    /// Output format
+    #[arg(long, value_name = "FORMAT", default_value = "text", value_parser = ["text", "ansi", "html", "svg"])]
+    pub format: String,
+
+    /// Page colours for HTML and SVG output
+    #[arg(long, value_name = "BACKGROUND", default_value = "dark", value_parser = ["dark", "light"])]
+    pub background: String,
+
+    /// Show only first-parent history, with merges collapsed
+    #[arg(long)]
+    pub first_parent: bool,
 }
@@ -0,0 +1,861 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use serde::Deserialize;
+use toml::Spanned;
+
+use crate::app::LayoutMode;
+use crate::forge::{ForgeHost, ForgeHosts, ForgeKind};
+use crate::graph::{GraphGlyphs, GraphSpacing};
+use crate::keymap::{parse_sequence, Action, KeySequence, Keymap};
+use crate::references::{PatternError, ReferencePattern};
+use crate::theme::{parse_color, parse_style, ColorMode, Theme, BUILTIN_THEMES};
+
+/// Name of the per-repository config file, read from the worktree root.
+pub const REPO_CONFIG_FILE: &str = ".gitviz.toml";
+
+/// Settings merged from the user and per-repository config files.
+/// Every field is optional so CLI flags and built-in defaults can fill gaps.
+#[derive(Debug, Default, Clone)]
+pub struct Config {
+    pub max: Option<usize>,
+    pub all: Option<bool>,
+    pub range: Option<String>,
+    pub exclude_reachable_from: Option<String>,
+    pub color: Option<bool>,
+    pub color_mode: Option<ColorMode>,
+    pub theme: Option<ThemeChoice>,
+    pub themes: BTreeMap<String, Theme>,
+    pub layout: Option<LayoutMode>,
+    pub graph_glyphs: Option<GraphGlyphs>,
+    pub graph_spacing: Option<GraphSpacing>,
+    pub max_lanes: Option<usize>,
+    /// Remote whose forge `open_commit` links to.
+    pub forge_remote: Option<String>,
+    pub forge_hosts: ForgeHosts,
+    /// `[[references]]` patterns, the repository's before the user's.
+    pub reference_patterns: Vec<ReferencePattern>,
+    pub keys: Vec<(Action, Vec<KeySequence>)>,
+}
+
+/// A `ui.theme` setting and where it was made, so an unknown name can be
+/// reported once every config file has contributed its `[themes]`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ThemeChoice {
+    pub name: String,
+    pub path: PathBuf,
+    pub line: usize,
+}
+
+/// A config problem, reported as `path:line: message`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigError {
+    pub path: PathBuf,
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "{}:{}: {}", self.path.display(), line, self.message),
+            None => write!(f, "{}: {}", self.path.display(), self.message),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// Where a config file comes from. `.gitviz.toml` is committed with the
+/// repository, so anyone who can push to it chooses what it says.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ConfigSource {
+    User,
+    Repo,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ConfigFile {
+    #[serde(default)]
+    defaults: DefaultsSection,
+    #[serde(default)]
+    ui: UiSection,
+    #[serde(default)]
+    graph: GraphSection,
+    #[serde(default)]
+    forge: ForgeSection,
+    #[serde(default)]
+    references: Vec<ReferenceSpec>,
+    #[serde(default)]
+    keys: BTreeMap<String, Spanned<KeySpec>>,
+    #[serde(default)]
+    themes: BTreeMap<String, ThemeTable>,
+}
+
+/// A `[themes.<name>]` table or a theme file: `extends`, `lanes` and styles.
+type ThemeTable = BTreeMap<String, Spanned<toml::Value>>;
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct DefaultsSection {
+    max: Option<usize>,
+    all: Option<bool>,
+    range: Option<String>,
+    exclude_reachable_from: Option<Spanned<String>>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct UiSection {
+    color: Option<bool>,
+    color_mode: Option<Spanned<String>>,
+    theme: Option<Spanned<String>>,
+    layout: Option<Spanned<String>>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct GraphSection {
+    glyphs: Option<Spanned<String>>,
+    spacing: Option<Spanned<String>>,
+    max_lanes: Option<Spanned<usize>>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ForgeSection {
+    remote: Option<String>,
+    #[serde(default)]
+    hosts: BTreeMap<String, Spanned<HostSpec>>,
+}
+
+/// A `[forge.hosts]` entry: a forge type, or a table that also sets the web URL.
+#[derive(Debug, Deserialize)]
+#[serde(
+    untagged,
+    expecting = "a forge type such as \"gitlab\" or a table with `type` and `web_url`"
+)]
+enum HostSpec {
+    Kind(String),
+    Table(HostTable),
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct HostTable {
+    #[serde(rename = "type")]
+    kind: String,
+    web_url: Option<String>,
+}
+
+/// A `[[references]]` entry: a regular expression and the URL it opens.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ReferenceSpec {
+    pattern: Spanned<String>,
+    url: Spanned<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged, expecting = "a key such as \"q\" or a list of keys")]
+enum KeySpec {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl KeySpec {
+    fn specs(&self) -> Vec<&str> {
+        match self {
+            KeySpec::One(spec) => vec![spec.as_str()],
+            KeySpec::Many(specs) => specs.iter().map(String::as_str).collect(),
+        }
+    }
+}
+
+impl Config {
+    /// Load the user config, then overlay `.gitviz.toml` from the worktree root.
+    pub fn load(worktree: Option<&Path>) -> Result<Self, ConfigError> {
+        let mut config = Config::default();
+        if let Some(path) = user_config_path() {
+            if let Some(user) = Config::load_file(&path, ConfigSource::User)? {
+                config = user;
+            }
+        }
+        if let Some(worktree) = worktree {
+            let path = worktree.join(REPO_CONFIG_FILE);
+            if let Some(repo) = Config::load_file(&path, ConfigSource::Repo)? {
+                config.merge(repo);
+            }
+        }
+        Ok(config)
+    }
+
+    fn load_file(path: &Path, source: ConfigSource) -> Result<Option<Self>, ConfigError> {
+        match fs::read_to_string(path) {
+            Ok(contents) => Config::parse(path, &contents, source).map(Some),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(ConfigError {
+                path: path.to_path_buf(),
+                line: None,
+                message: err.to_string(),
+            }),
+        }
+    }
+
+    /// Parse a config file; `.gitviz.toml` may not set the keys that only the
+    /// user config is trusted with.
+    fn parse(path: &Path, contents: &str, source: ConfigSource) -> Result<Self, ConfigError> {
+        let error = |span: Option<std::ops::Range<usize>>, message: String| ConfigError {
+            path: path.to_path_buf(),
+            line: span.map(|span| line_of(contents, span.start)),
+            message,
+        };
+
+        let file: ConfigFile =
+            toml::from_str(contents).map_err(|err| error(err.span(), err.message().to_string()))?;
+
+        let theme = file.ui.theme.map(|theme| ThemeChoice {
+            line: line_of(contents, theme.span().start),
+            name: theme.into_inner(),
+            path: path.to_path_buf(),
+        });
+
+        let color_mode = match file.ui.color_mode {
+            Some(mode) if mode.get_ref() == "auto" => None,
+            Some(mode) => Some(ColorMode::from_name(mode.get_ref()).ok_or_else(|| {
+                error(
+                    Some(mode.span()),
+                    format!(
+                        "unknown color_mode '{}' (expected auto, 16, 256 or truecolor)",
+                        mode.get_ref()
+                    ),
+                )
+            })?),
+            None => None,
+        };
+
+        let exclude_reachable_from = match file.defaults.exclude_reachable_from {
+            Some(boundary) if boundary.get_ref().starts_with('-') => {
+                return Err(error(
+                    Some(boundary.span()),
+                    format!(
+                        "exclude_reachable_from must be a revision, not an option: '{}'",
+                        boundary.get_ref()
+                    ),
+                ));
+            }
+            boundary => boundary.map(Spanned::into_inner),
+        };
+
+        let mut themes = BTreeMap::new();
+        for (name, table) in &file.themes {
+            themes.insert(name.clone(), parse_theme(table, &error)?);
+        }
+
+        let layout = match file.ui.layout {
+            Some(layout) => Some(LayoutMode::from_name(layout.get_ref()).ok_or_else(|| {
+                error(
+                    Some(layout.span()),
+                    format!(
+                        "unknown layout '{}' (expected auto, stacked or side-by-side)",
+                        layout.get_ref()
+                    ),
+                )
+            })?),
+            None => None,
+        };
+
+        let graph_glyphs = match file.graph.glyphs {
+            Some(glyphs) => Some(GraphGlyphs::from_name(glyphs.get_ref()).ok_or_else(|| {
+                error(
+                    Some(glyphs.span()),
+                    format!(
+                        "unknown graph glyphs '{}' (expected rounded, square or ascii)",
+                        glyphs.get_ref()
+                    ),
+                )
+            })?),
+            None => None,
+        };
+
+        let graph_spacing = match file.graph.spacing {
+            Some(spacing) => Some(GraphSpacing::from_name(spacing.get_ref()).ok_or_else(|| {
+                error(
+                    Some(spacing.span()),
+                    format!(
+                        "unknown graph spacing '{}' (expected compact, normal or wide)",
+                        spacing.get_ref()
+                    ),
+                )
+            })?),
+            None => None,
+        };
+
+        let max_lanes = match file.graph.max_lanes {
+            Some(max_lanes) if *max_lanes.get_ref() == 0 => {
+                return Err(error(
+                    Some(max_lanes.span()),
+                    "max_lanes must be at least 1".to_string(),
+                ));
+            }
+            max_lanes => max_lanes.map(Spanned::into_inner),
+        };
+
+        let mut forge_hosts = ForgeHosts::new();
+        for (host, spec) in file.forge.hosts {
+            let span = spec.span();
+            let (kind, web_url) = match spec.into_inner() {
+                HostSpec::Kind(kind) => (kind, None),
+                HostSpec::Table(table) => (table.kind, table.web_url),
+            };
+            if web_url.is_some() && source == ConfigSource::Repo {
+                return Err(error(
+                    Some(span),
+                    format!(
+                        "web_url for host '{}' can only be set in the user config",
+                        host
+                    ),
+                ));
+            }
+            let kind = ForgeKind::from_name(&kind).ok_or_else(|| {
+                error(
+                    Some(span),
+                    format!(
+                        "unknown forge type '{}' for host '{}' (expected github, gitlab, bitbucket, gitea, forgejo, azure-devops or sourcehut)",
+                        kind, host
+                    ),
+                )
+            })?;
+            forge_hosts.insert(host.to_ascii_lowercase(), ForgeHost { kind, web_url });
+        }
+
+        let mut reference_patterns = Vec::new();
+        for spec in &file.references {
+            let pattern = ReferencePattern::new(spec.pattern.get_ref(), spec.url.get_ref())
+                .map_err(|err| match err {
+                    PatternError::Regex(reason) => error(
+                        Some(spec.pattern.span()),
+                        format!(
+                            "invalid reference pattern '{}': {}",
+                            spec.pattern.get_ref(),
+                            reason
+                        ),
+                    ),
+                    PatternError::Url => error(
+                        Some(spec.url.span()),
+                        format!(
+                            "invalid reference URL '{}': must start with http:// or https://",
+                            spec.url.get_ref()
+                        ),
+                    ),
+                })?;
+            reference_patterns.push(pattern);
+        }
+
+        let mut keys = Vec::new();
+        for (name, spec) in &file.keys {
+            let action = Action::from_name(name).ok_or_else(|| {
+                error(
+                    Some(spec.span()),
+                    format!("unknown action '{}' in [keys]", name),
+                )
+            })?;
+            let chords = spec
+                .get_ref()
+                .specs()
+                .into_iter()
+                .map(parse_sequence)
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|message| {
+                    error(Some(spec.span()), format!("{} for '{}'", message, name))
+                })?;
+            keys.push((action, chords));
+        }
+
+        Ok(Config {
+            max: file.defaults.max,
+            all: file.defaults.all,
+            range: file.defaults.range,
+            exclude_reachable_from,
+            color: file.ui.color,
+            color_mode,
+            theme,
+            themes,
+            layout,
+            graph_glyphs,
+            graph_spacing,
+            max_lanes,
+            forge_remote: file.forge.remote,
+            forge_hosts,
+            reference_patterns,
+            keys,
+        })
+    }
+
+    /// Overlay `other` on top of `self`; values set in `other` win.
+    pub fn merge(&mut self, other: Config) {
+        self.max = other.max.or(self.max);
+        self.all = other.all.or(self.all);
+        self.range = other.range.or(self.range.take());
+        self.exclude_reachable_from = other
+            .exclude_reachable_from
+            .or(self.exclude_reachable_from.take());
+        self.color = other.color.or(self.color);
+        self.color_mode = other.color_mode.or(self.color_mode);
+        self.theme = other.theme.or(self.theme.take());
+        self.themes.extend(other.themes);
+        self.layout = other.layout.or(self.layout);
+        self.graph_glyphs = other.graph_glyphs.or(self.graph_glyphs);
+        self.graph_spacing = other.graph_spacing.or(self.graph_spacing);
+        self.max_lanes = other.max_lanes.or(self.max_lanes);
+        self.forge_remote = other.forge_remote.or(self.forge_remote.take());
+        self.forge_hosts.extend(other.forge_hosts);
+        let user_patterns =
+            std::mem::replace(&mut self.reference_patterns, other.reference_patterns);
+        self.reference_patterns.extend(user_patterns);
+        self.keys.extend(other.keys);
+    }
+
+    /// The theme chosen by `ui.theme`, or the default palette.
+    pub fn theme(&self) -> Result<Theme, ConfigError> {
+        let Some(choice) = &self.theme else {
+            return Ok(Theme::default());
+        };
+        self.theme_named(&choice.name)?.ok_or_else(|| {
+            let mut known: Vec<&str> = BUILTIN_THEMES.to_vec();
+            known.extend(self.themes.keys().map(String::as_str));
+            ConfigError {
+                path: choice.path.clone(),
+                line: Some(choice.line),
+                message: format!(
+                    "unknown theme '{}' (expected one of: {})",
+                    choice.name,
+                    known.join(", ")
+                ),
+            }
+        })
+    }
+
+    /// Look a theme up in `[themes]`, then `themes/<name>.toml` next to the
+    /// user config, then the built-in presets.
+    pub fn theme_named(&self, name: &str) -> Result<Option<Theme>, ConfigError> {
+        if let Some(theme) = self.themes.get(name) {
+            return Ok(Some(theme.clone()));
+        }
+        if let Some(theme) = load_theme_file(name)? {
+            return Ok(Some(theme));
+        }
+        Ok(Theme::builtin(name))
+    }
+
+    pub fn keymap(&self) -> Keymap {
+        let mut keymap = Keymap::default();
+        for (action, chords) in &self.keys {
+            keymap.bind(*action, chords);
+        }
+        keymap
+    }
+}
+
+/// `$XDG_CONFIG_HOME/gitviz/config.toml`, or `~/.config/gitviz/config.toml`.
+pub fn user_config_path() -> Option<PathBuf> {
+    let config_home = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|value| !value.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(config_home.join("gitviz").join("config.toml"))
+}
+
+fn load_theme_file(name: &str) -> Result<Option<Theme>, ConfigError> {
+    let is_file_name = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    let Some(dir) = user_config_path().and_then(|path| path.parent().map(Path::to_path_buf)) else {
+        return Ok(None);
+    };
+    if !is_file_name {
+        return Ok(None);
+    }
+
+    let path = dir.join("themes").join(format!("{}.toml", name));
+    let contents = match fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => {
+            return Err(ConfigError {
+                path,
+                line: None,
+                message: err.to_string(),
+            })
+        }
+    };
+    parse_theme_file(&path, &contents).map(Some)
+}
+
+fn parse_theme_file(path: &Path, contents: &str) -> Result<Theme, ConfigError> {
+    let error = |span: Option<std::ops::Range<usize>>, message: String| ConfigError {
+        path: path.to_path_buf(),
+        line: span.map(|span| line_of(contents, span.start)),
+        message,
+    };
+    let table: ThemeTable =
+        toml::from_str(contents).map_err(|err| error(err.span(), err.message().to_string()))?;
+    parse_theme(&table, &error)
+}
+
+/// Build a theme from its table, starting from the `extends` preset.
+fn parse_theme(
+    table: &ThemeTable,
+    error: &impl Fn(Option<std::ops::Range<usize>>, String) -> ConfigError,
+) -> Result<Theme, ConfigError> {
+    let mut theme = match table.get("extends") {
+        Some(base) => {
+            let name = base.get_ref().as_str().unwrap_or_default();
+            Theme::builtin(name).ok_or_else(|| {
+                error(
+                    Some(base.span()),
+                    format!(
+                        "unknown base theme '{}' (expected one of: {})",
+                        name,
+                        BUILTIN_THEMES.join(", ")
+                    ),
+                )
+            })?
+        }
+        None => Theme::default(),
+    };
+
+    for (key, value) in table {
+        let span = Some(value.span());
+        match key.as_str() {
+            "extends" => {}
+            "lanes" => {
+                let colors = value.get_ref().as_array().ok_or_else(|| {
+                    error(span.clone(), "'lanes' must be a list of colours".into())
+                })?;
+                theme.lanes = colors
+                    .iter()
+                    .map(|color| {
+                        color
+                            .as_str()
+                            .ok_or_else(|| "'lanes' must be a list of colours".to_string())
+                            .and_then(parse_color)
+                    })
+                    .collect::<Result<_, _>>()
+                    .map_err(|message| error(span.clone(), message))?;
+            }
+            key => {
+                let style = theme
+                    .style_mut(key)
+                    .ok_or_else(|| error(span.clone(), format!("unknown theme key '{}'", key)))?;
+                let spec = value.get_ref().as_str().ok_or_else(|| {
+                    error(
+                        span.clone(),
+                        format!("'{}' must be a style such as \"bold green\"", key),
+                    )
+                })?;
+                *style = parse_style(spec)
+                    .map_err(|message| error(span.clone(), format!("{} for '{}'", message, key)))?;
+            }
+        }
+    }
+    Ok(theme)
+}
+
+fn line_of(contents: &str, offset: usize) -> usize {
+    let offset = offset.min(contents.len());
+    contents.as_bytes()[..offset]
+        .iter()
+        .filter(|&&byte| byte == b'\n')
+        .count()
+        + 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keymap::{KeyChord, PendingKeys};
+    use crate::references::ReferenceFinder;
+    use crossterm::event::KeyCode;
+    use ratatui::style::{Color, Modifier, Style};
+
+    fn parse(contents: &str) -> Result<Config, ConfigError> {
+        Config::parse(Path::new("config.toml"), contents, ConfigSource::User)
+    }
+
+    fn parse_repo(contents: &str) -> Result<Config, ConfigError> {
+        Config::parse(Path::new(REPO_CONFIG_FILE), contents, ConfigSource::Repo)
+    }
+
+    #[test]
+    fn test_parse_full_config() {
+        let config = parse(
+            r#"
+[defaults]
+max = 500
+all = false
+range = "main..feature"
+
+[ui]
+color = true
+theme = "monochrome"
+layout = "side-by-side"
+
+[graph]
+glyphs = "ascii"
+spacing = "wide"
+max_lanes = 8
+
+[keys]
+copy_hash = "c"
+move_down = ["j", "Ctrl-n"]
+move_to_top = "g g"
+"#,
+        )
+        .expect("config should parse");
+
+        assert_eq!(config.max, Some(500));
+        assert_eq!(config.all, Some(false));
+        assert_eq!(config.range.as_deref(), Some("main..feature"));
+        assert_eq!(
+            config.theme.as_ref().map(|theme| theme.name.as_str()),
+            Some("monochrome")
+        );
+        assert_eq!(config.theme().unwrap(), Theme::monochrome());
+        assert_eq!(config.layout, Some(LayoutMode::SideBySide));
+        assert_eq!(config.graph_glyphs, Some(GraphGlyphs::Ascii));
+        assert_eq!(config.graph_spacing, Some(GraphSpacing::Wide));
+        assert_eq!(config.max_lanes, Some(8));
+        assert_eq!(
+            config.keys,
+            vec![
+                (
+                    Action::CopyHash,
+                    vec![vec![KeyChord::plain(KeyCode::Char('c'))]]
+                ),
+                (
+                    Action::MoveDown,
+                    vec![
+                        vec![KeyChord::plain(KeyCode::Char('j'))],
+                        vec![KeyChord::ctrl('n')]
+                    ]
+                ),
+                (
+                    Action::MoveToTop,
+                    vec![vec![
+                        KeyChord::plain(KeyCode::Char('g')),
+                        KeyChord::plain(KeyCode::Char('g'))
+                    ]]
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_errors_report_line_numbers() {
+        let err = parse("[defaults]\nmax = 10\nmaxx = 20\n").unwrap_err();
+        assert_eq!(err.line, Some(3));
+        assert!(err.to_string().starts_with("config.toml:3: "));
+
+        let err = parse("[ui]\n\nlayout = \"diagonal\"\n").unwrap_err();
+        assert_eq!(err.line, Some(3));
+        assert!(err.message.contains("unknown layout 'diagonal'"));
+
+        let err = parse("[keys]\nquit = \"q\"\nlaunch = \"x\"\n").unwrap_err();
+        assert_eq!(err.line, Some(3));
+        assert!(err.message.contains("unknown action 'launch'"));
+
+        let err = parse("[keys]\nquit = \"Hyper-q\"\n").unwrap_err();
+        assert_eq!(err.line, Some(2));
+        assert!(err.message.contains("unknown key 'Hyper-q'"));
+
+        let err = parse("[keys]\nquit = \"q\"\nmove_down = \"Pgdown\"\n").unwrap_err();
+        assert_eq!(err.line, Some(3));
+        assert!(err.message.contains("unknown key 'Pgdown' for 'move_down'"));
+
+        let err = parse("[defaults]\nmax = \"lots\"\n").unwrap_err();
+        assert_eq!(err.line, Some(2));
+
+        let err = parse("[ui]\ncolor_mode = \"88\"\n").unwrap_err();
+        assert_eq!(err.line, Some(2));
+
+        let err = parse("[graph]\nglyphs = \"ascii\"\nspacing = \"roomy\"\n").unwrap_err();
+        assert_eq!(err.line, Some(3));
+        assert!(err.message.contains("unknown graph spacing 'roomy'"));
+
+        let err = parse("[graph]\nmax_lanes = 0\n").unwrap_err();
+        assert_eq!(err.line, Some(2));
+
+        let err = parse("[defaults]\nexclude_reachable_from = \"--output=/tmp/x\"\n").unwrap_err();
+        assert_eq!(err.line, Some(2));
+        assert!(err.message.contains("must be a revision, not an option"));
+
+        let err = parse("\n[ui]\ntheme = \"solarized\"\n")
+            .unwrap()
+            .theme()
+            .unwrap_err();
+        assert_eq!(err.line, Some(3));
+        assert!(err.message.contains("unknown theme 'solarized'"));
+
+        let err = parse("[themes.mine]\ntag = \"bold\"\nsparkle = \"red\"\n").unwrap_err();
+        assert_eq!(err.line, Some(3));
+        assert!(err.message.contains("unknown theme key 'sparkle'"));
+
+        let err = parse("[themes.mine]\nbranch = \"bold chartreuse\"\n").unwrap_err();
+        assert_eq!(err.line, Some(2));
+        assert!(err
+            .message
+            .contains("unknown colour 'chartreuse' for 'branch'"));
+    }
+
+    #[test]
+    fn test_custom_theme() {
+        let config = parse(
+            r##"
+[ui]
+theme = "mine"
+color_mode = "256"
+
+[themes.mine]
+extends = "light"
+lanes = ["red", "#00ff00", "208"]
+tag = "bold yellow on black"
+"##,
+        )
+        .expect("config should parse");
+
+        assert_eq!(config.color_mode, Some(ColorMode::Ansi256));
+        let theme = config.theme().unwrap();
+        assert_eq!(
+            theme.lanes,
+            vec![Color::Red, Color::Rgb(0, 255, 0), Color::Indexed(208)]
+        );
+        assert_eq!(
+            theme.tag,
+            Style::default()
+                .fg(Color::Yellow)
+                .bg(Color::Black)
+                .add_modifier(Modifier::BOLD)
+        );
+        assert_eq!(theme.branch, Theme::light().branch);
+    }
+
+    #[test]
+    fn test_forge_hosts() {
+        let config = parse(
+            r#"
+[forge]
+remote = "upstream"
+
+[forge.hosts]
+"Git.Example.com" = "gitlab"
+"code.internal" = { type = "forgejo", web_url = "https://code.internal:3000" }
+"#,
+        )
+        .expect("config should parse");
+
+        assert_eq!(config.forge_remote.as_deref(), Some("upstream"));
+        assert_eq!(
+            config.forge_hosts.get("git.example.com"),
+            Some(&ForgeHost {
+                kind: ForgeKind::GitLab,
+                web_url: None
+            })
+        );
+        assert_eq!(
+            config.forge_hosts.get("code.internal"),
+            Some(&ForgeHost {
+                kind: ForgeKind::Gitea,
+                web_url: Some("https://code.internal:3000".to_string())
+            })
+        );
+
+        let err = parse("[forge.hosts]\n\"git.example.com\" = \"fossil\"\n").unwrap_err();
+        assert_eq!(err.line, Some(2));
+        assert!(err
+            .message
+            .contains("unknown forge type 'fossil' for host 'git.example.com'"));
+
+        let repo = parse_repo("[forge.hosts]\n\"git.example.com\" = \"gitlab\"\n").unwrap();
+        assert_eq!(repo.forge_hosts.len(), 1);
+        let err = parse_repo(
+            "[forge.hosts]\nevil = { type = \"gitlab\", web_url = \"https://x.test\" }\n",
+        )
+        .unwrap_err();
+        assert_eq!(err.line, Some(2));
+        assert!(err.message.contains("can only be set in the user config"));
+    }
+
+    #[test]
+    fn test_reference_patterns() {
+        let mut user = parse(
+            r#"
+[[references]]
+pattern = '\bOPS-\d+\b'
+url = "https://tracker.example.com/browse/$0"
+"#,
+        )
+        .expect("config should parse");
+        let repo = parse_repo(
+            "[[references]]\npattern = 'T(\\d+)'\nurl = \"https://t.example.com/${1}\"\n",
+        )
+        .unwrap();
+        user.merge(repo);
+        let finder = ReferenceFinder::new(None, user.reference_patterns.clone());
+        assert_eq!(
+            finder.links("OPS-4 and T7"),
+            vec![
+                (
+                    "OPS-4".to_string(),
+                    "https://tracker.example.com/browse/OPS-4".to_string()
+                ),
+                ("T7".to_string(), "https://t.example.com/7".to_string()),
+            ]
+        );
+
+        let err = parse("\n[[references]]\npattern = '(ab'\nurl = 'https://x'\n").unwrap_err();
+        assert_eq!(err.line, Some(3));
+        assert_eq!(
+            err.message,
+            "invalid reference pattern '(ab': unclosed group"
+        );
+
+        let err = parse("[[references]]\npattern = 'T\\d+'\nurl = 'file:///etc/$0'\n").unwrap_err();
+        assert_eq!(err.line, Some(3));
+        assert_eq!(
+            err.message,
+            "invalid reference URL 'file:///etc/$0': must start with http:// or https://"
+        );
+    }
+
+    #[test]
+    fn test_repo_config_can_use_user_theme() {
+        let mut user = parse("[themes.mine]\nextends = \"dark\"\n").unwrap();
+        let repo = parse_repo("[ui]\ntheme = \"mine\"\n").unwrap();
+        user.merge(repo);
+        assert_eq!(user.theme().unwrap(), Theme::dark());
+    }
+
+    #[test]
+    fn test_merge_prefers_repo_values() {
+        let mut user = parse("[defaults]\nmax = 100\nall = true\n[keys]\nquit = \"x\"\n").unwrap();
+        let repo = parse_repo("[defaults]\nmax = 50\n[keys]\nquit = \"Q\"\n").unwrap();
+        user.merge(repo);
+        assert_eq!(user.max, Some(50));
+        assert_eq!(user.all, Some(true));
+
+        let keymap = user.keymap();
+        let mut pending = PendingKeys::default();
+        let press = |c| crossterm::event::KeyEvent::from(KeyCode::Char(c));
+        assert_eq!(
+            keymap.feed(&mut pending, press('Q')),
+            vec![(Action::Quit, None)]
+        );
+        assert!(keymap.feed(&mut pending, press('x')).is_empty());
+    }
+}
@@ -0,0 +1,428 @@
+use ratatui::style::{Color, Modifier, Style};
+
+use crate::git::model::{Commit, Refs};
+use crate::graph::{graph_lines, GraphRow, GraphStyle};
+use crate::theme::{color_to_rgb, Theme};
+use crate::util::{format_relative, short_hash};
+
+/// Output formats of `gitviz export`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Text,
+    Ansi,
+    Html,
+    Svg,
+}
+
+impl ExportFormat {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "text" => Some(ExportFormat::Text),
+            "ansi" => Some(ExportFormat::Ansi),
+            "html" => Some(ExportFormat::Html),
+            "svg" => Some(ExportFormat::Svg),
+            _ => None,
+        }
+    }
+}
+
+/// Page colours for HTML and SVG output, where there is no terminal
+/// background to draw on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PageBackground {
+    #[default]
+    Dark,
+    Light,
+}
+
+impl PageBackground {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "dark" => Some(PageBackground::Dark),
+            "light" => Some(PageBackground::Light),
+            _ => None,
+        }
+    }
+
+    /// Background and default text colour.
+    fn colors(self) -> (&'static str, &'static str) {
+        match self {
+            PageBackground::Dark => ("#1e1e1e", "#d4d4d4"),
+            PageBackground::Light => ("#ffffff", "#24292f"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ExportOptions {
+    pub format: ExportFormat,
+    pub theme: Theme,
+    pub graph_style: GraphStyle,
+    pub background: PageBackground,
+}
+
+/// One line of output as styled pieces of text.
+type StyledLine = Vec<(String, Style)>;
+
+const SVG_FONT_SIZE: f32 = 14.0;
+const SVG_CHAR_WIDTH: f32 = 8.4;
+const SVG_LINE_HEIGHT: f32 = 17.0;
+const SVG_PADDING: f32 = 8.0;
+
+/// Render the graph the TUI shows as a document in the chosen format.
+/// Rows hidden by a first-parent view are left out.
+pub fn export(
+    commits: &[Commit],
+    refs: &Refs,
+    graph: &[GraphRow],
+    options: &ExportOptions,
+) -> String {
+    let lines = styled_lines(commits, refs, graph, options);
+    match options.format {
+        ExportFormat::Text => to_text(&lines),
+        ExportFormat::Ansi => to_ansi(&lines),
+        ExportFormat::Html => to_html(&lines, options.background),
+        ExportFormat::Svg => to_svg(&lines, options.background),
+    }
+}
+
+fn styled_lines(
+    commits: &[Commit],
+    refs: &Refs,
+    graph: &[GraphRow],
+    options: &ExportOptions,
+) -> Vec<StyledLine> {
+    let theme = &options.theme;
+    let mut lines = Vec::new();
+    for (commit, row) in commits.iter().zip(graph).filter(|(_, row)| !row.hidden) {
+        let mut graph_text = graph_lines(row, options.graph_style)
+            .into_iter()
+            .map(|segments| {
+                segments
+                    .into_iter()
+                    .map(|(text, color)| (text, theme.line_style(color)))
+                    .collect::<StyledLine>()
+            });
+
+        let mut line = graph_text.next().unwrap_or_default();
+        line.push((" ".to_string(), Style::default()));
+        line.push((short_hash(&commit.oid).to_string(), theme.accent));
+        line.push((" ".to_string(), Style::default()));
+        for (kind, label) in refs.labeled_refs(&commit.oid) {
+            line.push((format!("[{}]", printable(&label)), theme.ref_style(kind)));
+            line.push((" ".to_string(), Style::default()));
+        }
+        line.push((printable(&commit.subject), Style::default()));
+        line.push((
+            format!(
+                "  {}, {}",
+                printable(&commit.author),
+                format_relative(commit.timestamp)
+            ),
+            theme.muted,
+        ));
+        lines.push(line);
+        lines.extend(graph_text);
+    }
+    lines
+}
+
+/// Drop control characters from repository text, so a subject cannot
+/// smuggle escape sequences into `ansi` or `text` output.
+fn printable(text: &str) -> String {
+    text.chars().filter(|c| !c.is_control()).collect()
+}
+
+fn to_text(lines: &[StyledLine]) -> String {
+    let mut out = String::new();
+    for line in lines {
+        for (text, _) in line {
+            out.push_str(text);
+        }
+        out.push('\n');
+    }
+    out
+}
+
+fn to_ansi(lines: &[StyledLine]) -> String {
+    let mut out = String::new();
+    for line in lines {
+        for (text, style) in line {
+            let codes = sgr_codes(*style);
+            if codes.is_empty() {
+                out.push_str(text);
+            } else {
+                out.push_str(&format!("\x1b[{}m{}\x1b[0m", codes.join(";"), text));
+            }
+        }
+        out.push('\n');
+    }
+    out
+}
+
+/// SGR parameters selecting a style, empty for the terminal default.
+fn sgr_codes(style: Style) -> Vec<String> {
+    let mut codes: Vec<String> = [
+        (Modifier::BOLD, "1"),
+        (Modifier::DIM, "2"),
+        (Modifier::ITALIC, "3"),
+        (Modifier::UNDERLINED, "4"),
+        (Modifier::REVERSED, "7"),
+    ]
+    .iter()
+    .filter(|(modifier, _)| style.add_modifier.contains(*modifier))
+    .map(|(_, code)| code.to_string())
+    .collect();
+    codes.extend(style.fg.and_then(|color| sgr_color(color, false)));
+    codes.extend(style.bg.and_then(|color| sgr_color(color, true)));
+    codes
+}
+
+fn sgr_color(color: Color, background: bool) -> Option<String> {
+    let offset = if background { 10 } else { 0 };
+    let base = match color {
+        Color::Reset => return None,
+        Color::Black => 30,
+        Color::Red => 31,
+        Color::Green => 32,
+        Color::Yellow => 33,
+        Color::Blue => 34,
+        Color::Magenta => 35,
+        Color::Cyan => 36,
+        Color::Gray => 37,
+        Color::DarkGray => 90,
+        Color::LightRed => 91,
+        Color::LightGreen => 92,
+        Color::LightYellow => 93,
+        Color::LightBlue => 94,
+        Color::LightMagenta => 95,
+        Color::LightCyan => 96,
+        Color::White => 97,
+        Color::Indexed(index) => return Some(format!("{};5;{}", 38 + offset, index)),
+        Color::Rgb(r, g, b) => return Some(format!("{};2;{};{};{}", 38 + offset, r, g, b)),
+    };
+    Some((base + offset).to_string())
+}
+
+fn to_html(lines: &[StyledLine], background: PageBackground) -> String {
+    let (page, text) = background.colors();
+    let mut out = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>gitviz</title>\n\
+         <style>\nbody {{ background: {page}; color: {text}; margin: 0; }}\n\
+         pre {{ font-family: ui-monospace, Menlo, Consolas, monospace; font-size: 14px; \
+         line-height: 1.2; padding: 8px; margin: 0; }}\n</style>\n</head>\n<body>\n<pre>"
+    );
+    for line in lines {
+        for (piece, style) in line {
+            let css = css_style(*style);
+            if css.is_empty() {
+                out.push_str(&escape(piece));
+            } else {
+                out.push_str(&format!("<span style=\"{}\">{}</span>", css, escape(piece)));
+            }
+        }
+        out.push('\n');
+    }
+    out.push_str("</pre>\n</body>\n</html>\n");
+    out
+}
+
+fn css_style(style: Style) -> String {
+    let mut rules = Vec::new();
+    if let Some((r, g, b)) = style.fg.and_then(color_to_rgb) {
+        rules.push(format!("color:#{:02x}{:02x}{:02x}", r, g, b));
+    }
+    if let Some((r, g, b)) = style.bg.and_then(color_to_rgb) {
+        rules.push(format!("background:#{:02x}{:02x}{:02x}", r, g, b));
+    }
+    if style.add_modifier.contains(Modifier::BOLD) {
+        rules.push("font-weight:bold".to_string());
+    }
+    if style.add_modifier.contains(Modifier::DIM) {
+        rules.push("opacity:0.6".to_string());
+    }
+    if style.add_modifier.contains(Modifier::ITALIC) {
+        rules.push("font-style:italic".to_string());
+    }
+    if style.add_modifier.contains(Modifier::UNDERLINED) {
+        rules.push("text-decoration:underline".to_string());
+    }
+    rules.join(";")
+}
+
+/// Text laid out on a character grid, so graph glyphs line up between rows.
+fn to_svg(lines: &[StyledLine], background: PageBackground) -> String {
+    let (page, text) = background.colors();
+    let columns = lines
+        .iter()
+        .map(|line| {
+            line.iter()
+                .map(|(piece, _)| piece.chars().count())
+                .sum::<usize>()
+        })
+        .max()
+        .unwrap_or(0);
+    let width = columns as f32 * SVG_CHAR_WIDTH + 2.0 * SVG_PADDING;
+    let height = lines.len() as f32 * SVG_LINE_HEIGHT + 2.0 * SVG_PADDING;
+
+    let mut out = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width:.1}\" height=\"{height:.1}\" \
+         viewBox=\"0 0 {width:.1} {height:.1}\" font-family=\"ui-monospace, Menlo, Consolas, monospace\" \
+         font-size=\"{SVG_FONT_SIZE}\">\n<rect width=\"100%\" height=\"100%\" fill=\"{page}\"/>\n"
+    );
+    for (index, line) in lines.iter().enumerate() {
+        let y = SVG_PADDING + (index as f32 + 0.8) * SVG_LINE_HEIGHT;
+        out.push_str(&format!(
+            "<text y=\"{:.1}\" fill=\"{}\" xml:space=\"preserve\">",
+            y, text
+        ));
+        let mut column = 0;
+        for (piece, style) in line {
+            let x = SVG_PADDING + column as f32 * SVG_CHAR_WIDTH;
+            column += piece.chars().count();
+            if piece.trim().is_empty() {
+                continue;
+            }
+            out.push_str(&format!(
+                "<tspan x=\"{:.1}\"{}>{}</tspan>",
+                x,
+                svg_attributes(*style),
+                escape(piece)
+            ));
+        }
+        out.push_str("</text>\n");
+    }
+    out.push_str("</svg>\n");
+    out
+}
+
+fn svg_attributes(style: Style) -> String {
+    let mut attributes = String::new();
+    if let Some((r, g, b)) = style.fg.and_then(color_to_rgb) {
+        attributes.push_str(&format!(" fill=\"#{:02x}{:02x}{:02x}\"", r, g, b));
+    }
+    if style.add_modifier.contains(Modifier::BOLD) {
+        attributes.push_str(" font-weight=\"bold\"");
+    }
+    if style.add_modifier.contains(Modifier::ITALIC) {
+        attributes.push_str(" font-style=\"italic\"");
+    }
+    attributes
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{compute_layout, LayoutOptions};
+
+    fn history() -> (Vec<Commit>, Refs, Vec<GraphRow>) {
+        let commit = |oid: &str, parents: &[&str], subject: &str| Commit {
+            oid: oid.to_string(),
+            parents: parents.iter().map(|parent| parent.to_string()).collect(),
+            author: "Ada".to_string(),
+            author_email: "ada@example.com".to_string(),
+            timestamp: 0,
+            subject: subject.to_string(),
+            body: String::new(),
+            ..Default::default()
+        };
+        let commits = vec![
+            commit(
+                "aaaaaaa1",
+                &["bbbbbbb2", "ccccccc3"],
+                "Merge <topic> & more",
+            ),
+            commit("ccccccc3", &["bbbbbbb2"], "Topic"),
+            commit("bbbbbbb2", &[], "Root"),
+        ];
+        let mut refs = Refs::default();
+        refs.branches
+            .push(("refs/heads/main".to_string(), "aaaaaaa1".to_string()));
+        let options = LayoutOptions {
+            branch_names: refs.branch_names(),
+            ..LayoutOptions::default()
+        };
+        let graph = compute_layout(&commits, &options);
+        (commits, refs, graph)
+    }
+
+    fn export_as(format: ExportFormat) -> String {
+        let (commits, refs, graph) = history();
+        let options = ExportOptions {
+            format,
+            theme: Theme::default(),
+            graph_style: GraphStyle::default(),
+            background: PageBackground::Dark,
+        };
+        export(&commits, &refs, &graph, &options)
+    }
+
+    #[test]
+    fn test_text_export_matches_the_graph() {
+        let text = export_as(ExportFormat::Text);
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("◎─╮ aaaaaaa [main] Merge <topic> & more  Ada, "));
+        assert!(lines[1].starts_with("├─● ccccccc Topic"));
+        assert!(lines[2].starts_with("● bbbbbbb Root"));
+        assert!(!text.contains('\x1b'));
+    }
+
+    #[test]
+    fn test_ansi_export_colours_pieces() {
+        let ansi = export_as(ExportFormat::Ansi);
+        assert!(ansi.starts_with("\x1b[34m◎\x1b[0m"));
+        assert!(ansi.contains("\x1b[1;32m[main]\x1b[0m"));
+    }
+
+    #[test]
+    fn test_exports_drop_control_characters() {
+        let (mut commits, refs, graph) = history();
+        commits[1].subject = "Topic\x1b]0;pwned\x07\r".to_string();
+        commits[1].author = "Ada\x1b[2J".to_string();
+        for format in [ExportFormat::Text, ExportFormat::Ansi] {
+            let options = ExportOptions {
+                format,
+                theme: Theme::default(),
+                graph_style: GraphStyle::default(),
+                background: PageBackground::Dark,
+            };
+            let output = export(&commits, &refs, &graph, &options);
+            assert!(output.contains("Topic]0;pwned"));
+            assert!(output.contains("Ada[2J"));
+            assert!(!output.contains('\x07') && !output.contains('\r'));
+            assert!(!output.contains("\x1b]") && !output.contains("\x1b[2J"));
+        }
+    }
+
+    #[test]
+    fn test_html_and_svg_exports_escape_text() {
+        let html = export_as(ExportFormat::Html);
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("Merge &lt;topic&gt; &amp; more"));
+        assert!(html.contains("<span style=\"color:#00cd00;font-weight:bold\">[main]</span>"));
+
+        let svg = export_as(ExportFormat::Svg);
+        assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+        assert!(svg.trim_end().ends_with("</svg>"));
+        assert_eq!(svg.matches("<text ").count(), 3);
+        assert!(svg.contains(">Merge &lt;topic&gt; &amp; more</tspan>"));
+    }
+
+    #[test]
+    fn test_sgr_codes() {
+        let style = Style::default()
+            .fg(Color::Rgb(1, 2, 3))
+            .bg(Color::Indexed(208))
+            .add_modifier(Modifier::BOLD);
+        assert_eq!(sgr_codes(style), ["1", "38;2;1;2;3", "48;5;208"]);
+        assert!(sgr_codes(Style::default()).is_empty());
+    }
+}
@@ -0,0 +1,437 @@
+use std::collections::HashMap;
+
+use crate::git::remote::{RemoteUrl, Transport};
+
+/// A family of code hosting software, which decides how web URLs are laid out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ForgeKind {
+    /// GitHub and GitHub Enterprise.
+    GitHub,
+    /// GitLab, including nested groups.
+    GitLab,
+    /// Bitbucket Cloud.
+    Bitbucket,
+    /// Gitea and Forgejo, e.g. Codeberg.
+    Gitea,
+    /// Azure DevOps Services.
+    AzureDevOps,
+    /// sourcehut.
+    SourceHut,
+}
+
+impl ForgeKind {
+    /// Parse a forge type as written in config files.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "github" => Some(ForgeKind::GitHub),
+            "gitlab" => Some(ForgeKind::GitLab),
+            "bitbucket" => Some(ForgeKind::Bitbucket),
+            "gitea" | "forgejo" => Some(ForgeKind::Gitea),
+            "azure-devops" => Some(ForgeKind::AzureDevOps),
+            "sourcehut" => Some(ForgeKind::SourceHut),
+            _ => None,
+        }
+    }
+
+    /// Name shown to the user.
+    pub fn title(self) -> &'static str {
+        match self {
+            ForgeKind::GitHub => "GitHub",
+            ForgeKind::GitLab => "GitLab",
+            ForgeKind::Bitbucket => "Bitbucket",
+            ForgeKind::Gitea => "Gitea",
+            ForgeKind::AzureDevOps => "Azure DevOps",
+            ForgeKind::SourceHut => "sourcehut",
+        }
+    }
+
+    /// Forge serving a host, for public instances and for hosts named after
+    /// their software such as `gitlab.example.com`.
+    pub fn for_host(host: &str) -> Option<Self> {
+        match host {
+            "github.com" => Some(ForgeKind::GitHub),
+            "gitlab.com" => Some(ForgeKind::GitLab),
+            "bitbucket.org" => Some(ForgeKind::Bitbucket),
+            "codeberg.org" | "gitea.com" => Some(ForgeKind::Gitea),
+            "dev.azure.com" | "ssh.dev.azure.com" => Some(ForgeKind::AzureDevOps),
+            "git.sr.ht" => Some(ForgeKind::SourceHut),
+            _ if host.ends_with(".visualstudio.com") => Some(ForgeKind::AzureDevOps),
+            _ => {
+                let first = host.split('.').next().unwrap_or(host);
+                match first {
+                    "github" => Some(ForgeKind::GitHub),
+                    "gitlab" => Some(ForgeKind::GitLab),
+                    "gitea" | "forgejo" => Some(ForgeKind::Gitea),
+                    _ => None,
+                }
+            }
+        }
+    }
+}
+
+/// How a self-hosted instance is reached on the web, configured per host.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ForgeHost {
+    /// Software the host runs.
+    pub kind: ForgeKind,
+    /// Web address to use instead of `https://<host>`, for instances whose
+    /// git and web hosts differ or that live under a path or port.
+    pub web_url: Option<String>,
+}
+
+/// Custom forge settings keyed by the host name in remote URLs.
+pub type ForgeHosts = HashMap<String, ForgeHost>;
+
+/// A repository on a forge, able to build links into its web UI.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Forge {
+    kind: ForgeKind,
+    site_url: String,
+    repo_url: String,
+}
+
+impl Forge {
+    /// The forge serving `remote`, using `hosts` before the built-in list of
+    /// known hosts.
+    pub fn from_remote(remote: &RemoteUrl, hosts: &ForgeHosts) -> Option<Self> {
+        let (kind, web_url) = match hosts.get(&remote.host) {
+            Some(host) => (host.kind, host.web_url.clone()),
+            None => (ForgeKind::for_host(&remote.host)?, None),
+        };
+
+        let (site_url, repo_url) = match kind {
+            ForgeKind::AzureDevOps => azure_repo_url(remote, web_url.as_deref())?,
+            _ => {
+                let base = web_url.unwrap_or_else(|| default_web_url(remote));
+                let site_url = base.trim_end_matches('/').to_string();
+                let repo_url = format!("{}/{}", site_url, remote.path);
+                (site_url, repo_url)
+            }
+        };
+        Some(Forge {
+            kind,
+            site_url,
+            repo_url,
+        })
+    }
+
+    /// Another repository on the same site, given as `owner/repo` (or a
+    /// longer group path on GitLab). Azure DevOps and sourcehut paths don't
+    /// follow that form, so they have no siblings.
+    pub fn sibling(&self, path: &str) -> Option<Forge> {
+        match self.kind {
+            ForgeKind::AzureDevOps | ForgeKind::SourceHut => None,
+            _ => Some(Forge {
+                kind: self.kind,
+                site_url: self.site_url.clone(),
+                repo_url: format!("{}/{}", self.site_url, path.trim_matches('/')),
+            }),
+        }
+    }
+
+    /// Software behind the repository.
+    pub fn kind(&self) -> ForgeKind {
+        self.kind
+    }
+
+    /// Web page of the repository.
+    pub fn repo_url(&self) -> &str {
+        &self.repo_url
+    }
+
+    /// Page of one commit.
+    pub fn commit_url(&self, oid: &str) -> String {
+        match self.kind {
+            ForgeKind::GitLab => format!("{}/-/commit/{}", self.repo_url, oid),
+            ForgeKind::Bitbucket => format!("{}/commits/{}", self.repo_url, oid),
+            ForgeKind::GitHub
+            | ForgeKind::Gitea
+            | ForgeKind::AzureDevOps
+            | ForgeKind::SourceHut => {
+                format!("{}/commit/{}", self.repo_url, oid)
+            }
+        }
+    }
+
+    /// Page of a branch, given by its short name.
+    pub fn branch_url(&self, branch: &str) -> String {
+        let branch = encode_path(branch);
+        match self.kind {
+            ForgeKind::GitHub => format!("{}/tree/{}", self.repo_url, branch),
+            ForgeKind::GitLab => format!("{}/-/tree/{}", self.repo_url, branch),
+            ForgeKind::Bitbucket => format!("{}/branch/{}", self.repo_url, branch),
+            ForgeKind::Gitea => format!("{}/src/branch/{}", self.repo_url, branch),
+            ForgeKind::AzureDevOps => format!("{}?version=GB{}", self.repo_url, branch),
+            ForgeKind::SourceHut => format!("{}/log/{}", self.repo_url, branch),
+        }
+    }
+
+    /// Changes on `head` since it left `base`; both are branch names or
+    /// commits. sourcehut has no compare page.
+    pub fn compare_url(&self, base: &str, head: &str) -> Option<String> {
+        let (base, head) = (encode_path(base), encode_path(head));
+        let url = match self.kind {
+            ForgeKind::GitHub | ForgeKind::Gitea => {
+                format!("{}/compare/{}...{}", self.repo_url, base, head)
+            }
+            ForgeKind::GitLab => format!("{}/-/compare/{}...{}", self.repo_url, base, head),
+            ForgeKind::Bitbucket => {
+                format!("{}/branches/compare/{}%0D{}", self.repo_url, head, base)
+            }
+            ForgeKind::AzureDevOps => format!(
+                "{}/branchCompare?baseVersion=GB{}&targetVersion=GB{}",
+                self.repo_url, base, head
+            ),
+            ForgeKind::SourceHut => return None,
+        };
+        Some(url)
+    }
+
+    /// Page of an issue, pull request or work item by number. GitHub and
+    /// Gitea redirect issue links to pull requests that share the number;
+    /// sourcehut trackers live apart from the repository, so it has none.
+    pub fn issue_url(&self, number: u64) -> Option<String> {
+        let url = match self.kind {
+            ForgeKind::GitHub | ForgeKind::Gitea | ForgeKind::Bitbucket => {
+                format!("{}/issues/{}", self.repo_url, number)
+            }
+            ForgeKind::GitLab => format!("{}/-/issues/{}", self.repo_url, number),
+            ForgeKind::AzureDevOps => {
+                let project = self.repo_url.split("/_git/").next()?;
+                format!("{}/_workitems/edit/{}", project, number)
+            }
+            ForgeKind::SourceHut => return None,
+        };
+        Some(url)
+    }
+
+    /// Page of a file as of a commit.
+    pub fn file_url(&self, oid: &str, path: &str) -> String {
+        let path = encode_path(path);
+        match self.kind {
+            ForgeKind::GitHub => format!("{}/blob/{}/{}", self.repo_url, oid, path),
+            ForgeKind::GitLab => format!("{}/-/blob/{}/{}", self.repo_url, oid, path),
+            ForgeKind::Bitbucket => format!("{}/src/{}/{}", self.repo_url, oid, path),
+            ForgeKind::Gitea => format!("{}/src/commit/{}/{}", self.repo_url, oid, path),
+            ForgeKind::AzureDevOps => {
+                format!("{}?path=/{}&version=GC{}", self.repo_url, path, oid)
+            }
+            ForgeKind::SourceHut => format!("{}/tree/{}/item/{}", self.repo_url, oid, path),
+        }
+    }
+}
+
+/// HTTP remotes are served by the web UI itself, port included; every other
+/// transport gets the HTTPS site of the same host.
+fn default_web_url(remote: &RemoteUrl) -> String {
+    let scheme = match remote.transport {
+        Transport::Http => "http",
+        _ => "https",
+    };
+    match remote.port {
+        Some(port) if matches!(remote.transport, Transport::Http | Transport::Https) => {
+            format!("{}://{}:{}", scheme, remote.host, port)
+        }
+        _ => format!("{}://{}", scheme, remote.host),
+    }
+}
+
+/// Azure DevOps remotes come as `dev.azure.com/<org>/<project>/_git/<repo>`,
+/// `ssh.dev.azure.com:v3/<org>/<project>/<repo>` or the older
+/// `<org>.visualstudio.com/<project>/_git/<repo>`; the web UI is always under
+/// `dev.azure.com` unless a web URL is configured.
+fn azure_repo_url(remote: &RemoteUrl, web_url: Option<&str>) -> Option<(String, String)> {
+    let segments: Vec<&str> = remote.path.split('/').collect();
+    let legacy_org = remote.host.strip_suffix(".visualstudio.com");
+    let (org, project, repo) = match (legacy_org, segments.as_slice()) {
+        (Some(org), [project, "_git", repo] | ["DefaultCollection", project, "_git", repo]) => {
+            (org, *project, *repo)
+        }
+        (_, ["v3", org, project, repo] | [org, project, "_git", repo]) => (*org, *project, *repo),
+        _ => return None,
+    };
+    let base = match web_url {
+        Some(web_url) => web_url.trim_end_matches('/').to_string(),
+        None => format!("https://dev.azure.com/{}", org),
+    };
+    let repo_url = format!("{}/{}/_git/{}", base, project, repo);
+    Some((base, repo_url))
+}
+
+/// Escape the characters of a branch or file path that cannot appear in a
+/// URL path; `/` is kept.
+pub(crate) fn encode_path(text: &str) -> String {
+    let mut encoded = String::with_capacity(text.len());
+    for byte in text.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::remote::parse_remote_url;
+
+    fn forge(url: &str) -> Option<Forge> {
+        forge_with(url, &ForgeHosts::new())
+    }
+
+    fn forge_with(url: &str, hosts: &ForgeHosts) -> Option<Forge> {
+        Forge::from_remote(&parse_remote_url(url)?, hosts)
+    }
+
+    #[test]
+    fn test_github_urls() {
+        let forge = forge("git@github.com:owner/repo.git").unwrap();
+        assert_eq!(forge.kind(), ForgeKind::GitHub);
+        assert_eq!(
+            forge.commit_url("abc"),
+            "https://github.com/owner/repo/commit/abc"
+        );
+        assert_eq!(
+            forge.branch_url("feat/x"),
+            "https://github.com/owner/repo/tree/feat/x"
+        );
+        assert_eq!(
+            forge.compare_url("main", "feat").as_deref(),
+            Some("https://github.com/owner/repo/compare/main...feat")
+        );
+        assert_eq!(
+            forge.file_url("abc", "src/a b.rs"),
+            "https://github.com/owner/repo/blob/abc/src/a%20b.rs"
+        );
+    }
+
+    #[test]
+    fn test_gitlab_nested_groups() {
+        let forge = forge("https://gitlab.com/group/sub/project.git").unwrap();
+        assert_eq!(forge.repo_url(), "https://gitlab.com/group/sub/project");
+        assert_eq!(
+            forge.commit_url("abc"),
+            "https://gitlab.com/group/sub/project/-/commit/abc"
+        );
+        assert_eq!(
+            forge.file_url("abc", "README.md"),
+            "https://gitlab.com/group/sub/project/-/blob/abc/README.md"
+        );
+    }
+
+    #[test]
+    fn test_other_public_forges() {
+        let bitbucket = forge("git@bitbucket.org:team/repo.git").unwrap();
+        assert_eq!(
+            bitbucket.commit_url("abc"),
+            "https://bitbucket.org/team/repo/commits/abc"
+        );
+        assert_eq!(
+            bitbucket.compare_url("main", "feat").as_deref(),
+            Some("https://bitbucket.org/team/repo/branches/compare/feat%0Dmain")
+        );
+
+        let codeberg = forge("https://codeberg.org/user/repo.git").unwrap();
+        assert_eq!(codeberg.kind(), ForgeKind::Gitea);
+        assert_eq!(
+            codeberg.branch_url("main"),
+            "https://codeberg.org/user/repo/src/branch/main"
+        );
+
+        let sourcehut = forge("git@git.sr.ht:~user/repo").unwrap();
+        assert_eq!(
+            sourcehut.commit_url("abc"),
+            "https://git.sr.ht/~user/repo/commit/abc"
+        );
+        assert_eq!(sourcehut.compare_url("main", "feat"), None);
+    }
+
+    #[test]
+    fn test_azure_devops_remote_forms() {
+        let expected = "https://dev.azure.com/org/project/_git/repo";
+        for url in [
+            "https://org@dev.azure.com/org/project/_git/repo",
+            "git@ssh.dev.azure.com:v3/org/project/repo",
+            "https://org.visualstudio.com/project/_git/repo",
+            "https://org.visualstudio.com/DefaultCollection/project/_git/repo",
+        ] {
+            let forge = forge(url).unwrap_or_else(|| panic!("{} should resolve", url));
+            assert_eq!(forge.repo_url(), expected, "{}", url);
+        }
+        let forge = forge("git@ssh.dev.azure.com:v3/org/project/repo").unwrap();
+        assert_eq!(forge.commit_url("abc"), format!("{}/commit/abc", expected));
+        assert_eq!(
+            forge.branch_url("main"),
+            format!("{}?version=GBmain", expected)
+        );
+    }
+
+    #[test]
+    fn test_issue_urls() {
+        let issue = |url: &str| forge(url).unwrap().issue_url(12);
+        assert_eq!(
+            issue("git@github.com:owner/repo.git").as_deref(),
+            Some("https://github.com/owner/repo/issues/12")
+        );
+        assert_eq!(
+            issue("git@gitlab.com:group/sub/project.git").as_deref(),
+            Some("https://gitlab.com/group/sub/project/-/issues/12")
+        );
+        assert_eq!(
+            issue("git@ssh.dev.azure.com:v3/org/project/repo").as_deref(),
+            Some("https://dev.azure.com/org/project/_workitems/edit/12")
+        );
+        assert_eq!(issue("git@git.sr.ht:~user/repo"), None);
+
+        let github = forge("http://github.example.com:8080/owner/repo.git").unwrap();
+        assert_eq!(
+            github
+                .sibling("other/lib")
+                .and_then(|forge| forge.issue_url(3))
+                .as_deref(),
+            Some("http://github.example.com:8080/other/lib/issues/3")
+        );
+        assert!(forge("git@git.sr.ht:~user/repo")
+            .unwrap()
+            .sibling("a/b")
+            .is_none());
+    }
+
+    #[test]
+    fn test_self_hosted_instances() {
+        assert_eq!(
+            forge("git@gitlab.example.com:team/app.git").map(|forge| forge.kind()),
+            Some(ForgeKind::GitLab)
+        );
+        assert!(forge("git@git.example.com:team/app.git").is_none());
+        assert_eq!(
+            forge("http://gitea.local:3000/team/app.git")
+                .unwrap()
+                .repo_url(),
+            "http://gitea.local:3000/team/app"
+        );
+        assert_eq!(
+            forge("ssh://git@gitlab.example.com:2222/team/app.git")
+                .unwrap()
+                .repo_url(),
+            "https://gitlab.example.com/team/app"
+        );
+
+        let mut hosts = ForgeHosts::new();
+        hosts.insert(
+            "git.example.com".to_string(),
+            ForgeHost {
+                kind: ForgeKind::Gitea,
+                web_url: Some("https://code.example.com:3000/".to_string()),
+            },
+        );
+        let forge = forge_with("git@git.example.com:team/app.git", &hosts).unwrap();
+        assert_eq!(
+            forge.commit_url("abc"),
+            "https://code.example.com:3000/team/app/commit/abc"
+        );
+    }
+}
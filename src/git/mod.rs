@@ -1,42 +1,59 @@
-pub mod commands;
+mod commands;
+/// Commits, refs and changed files.
 pub mod model;
+/// Parsers for the output of the `git` commands this crate runs.
 pub mod parser;
+/// Remote URLs.
+pub mod remote;
+#[cfg(test)]
+pub(crate) mod testing;
 
+use crate::forge::{Forge, ForgeHosts};
 use anyhow::{bail, Context, Result};
-use model::{Commit, CommitInspectData, Refs};
-use std::path::Path;
+use model::{Commit, CommitInspectData, LineStats, Refs};
+use std::path::{Path, PathBuf};
 
 /// Load commits from the repository using a single `git log` call.
+///
+/// `revisions` are passed to `git log` as-is (e.g. `--all`, ref names);
+/// an empty slice means `HEAD`.
 pub fn load_commits(
     repo: &Path,
     max: usize,
-    all: bool,
+    revisions: &[String],
     exclude_reachable_from: Option<&str>,
 ) -> Result<Vec<Commit>> {
-    // Format: hash \x1f parents \x1f author \x1f email \x1f timestamp \x1f subject \x1f body \x1e
+    // Format: hash \x1f parents \x1f author and email, mailmapped then raw \x1f
+    // timestamp \x1f ISO date \x1f committer and email, mailmapped then raw \x1f
+    // timestamp \x1f ISO date \x1f signature status \x1f signer \x1f key \x1f subject
+    // \x1f body \x1e; the ISO dates carry the timezone offsets.
     // %x1f = ASCII unit separator (0x1f), %x1e = ASCII record separator (0x1e)
-    let format_str = "--format=%H%x1f%P%x1f%an%x1f%ae%x1f%at%x1f%s%x1f%b%x1e";
+    let format_str = concat!(
+        "--format=%H%x1f%P%x1f%aN%x1f%aE%x1f%an%x1f%ae%x1f%at%x1f%ai%x1f",
+        "%cN%x1f%cE%x1f%cn%x1f%ce%x1f%ct%x1f%ci%x1f%G?%x1f%GS%x1f%GK%x1f%s%x1f%b%x1e"
+    );
     let max_str = max.to_string();
     let max_count = format!("--max-count={}", max_str);
 
     let mut args: Vec<&str> = vec!["log", "--topo-order", format_str, &max_count];
 
-    if all {
-        args.push("--all");
+    if revisions.is_empty() {
+        args.push("HEAD");
+    } else {
+        args.extend(revisions.iter().map(String::as_str));
     }
 
     // Exclude commits reachable from the given revision boundary.
-    let not_arg: String;
-    if let Some(s) = exclude_reachable_from {
-        not_arg = s.to_string();
+    if let Some(boundary) = exclude_reachable_from {
+        if boundary.starts_with('-') {
+            bail!("revision boundary '{}' looks like an option", boundary);
+        }
         args.push("--not");
-        args.push(&not_arg);
+        args.push(boundary);
     }
 
-    // If not --all, specify HEAD explicitly
-    if !all {
-        args.push("HEAD");
-    }
+    // Keep revisions that look like paths from being treated as pathspecs.
+    args.push("--");
 
     let output = commands::run_git(repo, &args)
         .with_context(|| format!("Failed to load commits from {}", repo.display()))?;
@@ -44,6 +61,57 @@ pub fn load_commits(
     parse_git_log_output(&output)
 }
 
+/// Revisions for a default `git log`: every ref with `--all`, otherwise HEAD.
+pub fn default_revisions(all: bool) -> Vec<String> {
+    if all {
+        vec!["--all".to_string()]
+    } else {
+        vec!["HEAD".to_string()]
+    }
+}
+
+/// Revision-selection options accepted from the runtime range prompt.
+/// Anything else starting with `-` is rejected so the prompt cannot pass
+/// arbitrary `git log` options (e.g. `--output`).
+const REVISION_OPTIONS: [&str; 7] = [
+    "--all",
+    "--branches",
+    "--tags",
+    "--remotes",
+    "--glob",
+    "--exclude",
+    "--not",
+];
+
+/// Split a revision range typed by the user (`main..feature`,
+/// `--branches=release/*`, `^origin/main`) into `git log` arguments.
+pub fn parse_revision_args(input: &str) -> Result<Vec<String>> {
+    let mut args = Vec::new();
+    for arg in input.split_whitespace() {
+        if arg.starts_with('-') {
+            let name = arg.split('=').next().unwrap_or(arg);
+            if !REVISION_OPTIONS.contains(&name) {
+                bail!("unsupported option '{}'", arg);
+            }
+        }
+        args.push(arg.to_string());
+    }
+    Ok(args)
+}
+
+/// Check that every revision resolves, using `git rev-parse`.
+pub fn validate_revisions(repo: &Path, revisions: &[String]) -> Result<()> {
+    let mut args: Vec<&str> = vec!["rev-parse"];
+    args.extend(revisions.iter().map(String::as_str));
+    args.push("--");
+    commands::run_git(repo, &args).map_err(|err| {
+        let message = err.to_string();
+        let reason = message.rsplit("fatal: ").next().unwrap_or(&message).trim();
+        anyhow::anyhow!("invalid revision range: {}", reason)
+    })?;
+    Ok(())
+}
+
 fn parse_git_log_output(output: &str) -> Result<Vec<Commit>> {
     let report = parser::parse_commits(output);
     if report.commits.is_empty() && output.trim().is_empty() {
@@ -69,19 +137,23 @@ fn parse_git_log_output(output: &str) -> Result<Vec<Commit>> {
 
 /// Load refs (HEAD, local branches, remote branches, tags) from the repository.
 pub fn load_refs(repo: &Path) -> Result<Refs> {
-    let mut refs = Refs::default();
-
     // HEAD oid
-    refs.head_oid = commands::run_git(repo, &["rev-parse", "HEAD"])
+    let head_oid = commands::run_git(repo, &["rev-parse", "HEAD"])
         .unwrap_or_default()
         .trim()
         .to_string();
 
     // Symbolic HEAD (branch name) — exits non-zero when detached
-    refs.head_name = commands::try_run_git(repo, &["symbolic-ref", "-q", "HEAD"])
+    let head_name = commands::try_run_git(repo, &["symbolic-ref", "-q", "HEAD"])
         .map(|s| s.trim().to_string())
         .filter(|s| !s.is_empty());
 
+    let mut refs = Refs {
+        head_oid,
+        head_name,
+        ..Refs::default()
+    };
+
     let ref_out = commands::try_run_git(repo, &["show-ref"]).unwrap_or_default();
     for (refname, oid) in parser::parse_show_ref(&ref_out) {
         if refname.starts_with("refs/heads/") {
@@ -93,9 +165,34 @@ pub fn load_refs(repo: &Path) -> Result<Refs> {
         }
     }
 
+    let stash_out =
+        commands::try_run_git(repo, &["stash", "list", "--format=%gd%x1f%H"]).unwrap_or_default();
+    refs.stashes = parser::parse_stash_list(&stash_out);
+
+    let track_out = commands::try_run_git(
+        repo,
+        &[
+            "for-each-ref",
+            "--format=%(refname)%1f%(upstream)%1f%(upstream:track,nobracket)",
+            "refs/heads",
+        ],
+    )
+    .unwrap_or_default();
+    refs.tracking = parser::parse_upstream_tracking(&track_out)
+        .into_iter()
+        .collect();
+
     Ok(refs)
 }
 
+/// Top-level directory of the worktree, if the repository has one.
+pub fn worktree_root(repo: &Path) -> Option<PathBuf> {
+    commands::try_run_git(repo, &["rev-parse", "--show-toplevel"])
+        .map(|path| path.trim().to_string())
+        .filter(|path| !path.is_empty())
+        .map(PathBuf::from)
+}
+
 /// Verify the path is inside a git repository.
 pub fn check_repo(repo: &Path) -> Result<()> {
     commands::run_git(repo, &["rev-parse", "--git-dir"])
@@ -103,6 +200,7 @@ pub fn check_repo(repo: &Path) -> Result<()> {
     Ok(())
 }
 
+/// Load the changed files and patch of one commit with `git show`.
 pub fn load_commit_inspect_data(repo: &Path, oid: &str) -> Result<CommitInspectData> {
     let files_output = commands::run_git(
         repo,
@@ -143,12 +241,72 @@ pub fn load_commit_inspect_data(repo: &Path, oid: &str) -> Result<CommitInspectD
     )
 }
 
-pub fn github_commit_url(repo: &Path, oid: &str) -> Option<String> {
-    let remote_url = commands::try_run_git(repo, &["config", "--get", "remote.origin.url"])?;
-    let (owner, repo_name) = parse_github_remote_url(remote_url.trim())?;
-    Some(format!(
-        "https://github.com/{owner}/{repo_name}/commit/{oid}"
-    ))
+/// Commits passed to one `git log` when loading line counts, to stay well
+/// below command-line length limits.
+pub const LINE_STATS_BATCH: usize = 500;
+
+/// Lines added and removed by each of `oids`, with `git log --numstat`.
+/// Merges count no lines, as in `git log --numstat`.
+pub fn load_line_stats(repo: &Path, oids: &[String]) -> Result<Vec<(String, LineStats)>> {
+    let mut stats = Vec::with_capacity(oids.len());
+    for batch in oids.chunks(LINE_STATS_BATCH) {
+        let mut args = vec!["log", "--no-walk=unsorted", "--numstat", "--format=%x1e%H"];
+        args.extend(batch.iter().map(String::as_str));
+        let output = commands::run_git(repo, &args).context("Failed to load line counts")?;
+        stats.extend(parser::parse_numstat(&output));
+    }
+    Ok(stats)
+}
+
+/// Remote to link to when none is configured: the upstream remote of the
+/// checked-out branch, then `origin`, then the first remote.
+pub fn default_remote(repo: &Path) -> Option<String> {
+    let upstream = commands::try_run_git(repo, &["symbolic-ref", "-q", "--short", "HEAD"])
+        .and_then(|branch| {
+            let key = format!("branch.{}.remote", branch.trim());
+            commands::try_run_git(repo, &["config", "--get", &key])
+        })
+        .map(|remote| remote.trim().to_string())
+        // `.` means the upstream is a local branch.
+        .filter(|remote| !remote.is_empty() && remote != ".");
+    if upstream.is_some() {
+        return upstream;
+    }
+
+    let remotes = commands::try_run_git(repo, &["remote"]).unwrap_or_default();
+    let mut remotes = remotes
+        .lines()
+        .map(str::trim)
+        .filter(|name| !name.is_empty());
+    let first = remotes.next()?;
+    if first == "origin" || remotes.any(|name| name == "origin") {
+        Some("origin".to_string())
+    } else {
+        Some(first.to_string())
+    }
+}
+
+/// Forge hosting `remote`, or [`default_remote`] when `None`. `hosts` maps
+/// self-hosted instances to the software they run.
+pub fn remote_forge(repo: &Path, remote: Option<&str>, hosts: &ForgeHosts) -> Option<Forge> {
+    let remote = match remote {
+        Some(remote) => remote.to_string(),
+        None => default_remote(repo)?,
+    };
+    let key = format!("remote.{}.url", remote);
+    let url = commands::try_run_git(repo, &["config", "--get", &key])?;
+    let url = remote::rewrite_url(url.trim(), &url_rewrites(repo));
+    Forge::from_remote(&remote::parse_remote_url(&url)?, hosts)
+}
+
+/// The `url.<base>.insteadOf` settings in effect for the repository.
+pub fn url_rewrites(repo: &Path) -> Vec<remote::UrlRewrite> {
+    let output = commands::try_run_git(
+        repo,
+        &["config", "-z", "--get-regexp", r"^url\..*\.insteadof$"],
+    )
+    .unwrap_or_default();
+    parser::parse_url_rewrites(&output)
 }
 
 fn truncate_diff_preview(diff_output: &str, max_lines: usize) -> (String, bool) {
@@ -163,117 +321,25 @@ fn truncate_diff_preview(diff_output: &str, max_lines: usize) -> (String, bool)
         if !text.is_empty() {
             text.push('\n');
         }
-        text.push_str("... diff truncated; open in GitHub or use git show for the full patch");
+        text.push_str(
+            "... diff truncated; open the commit in the browser or use git show for the full patch",
+        );
     }
 
     (text, truncated)
 }
 
-fn parse_github_remote_url(remote: &str) -> Option<(String, String)> {
-    let remote = remote.trim();
-
-    let stripped = if let Some(rest) = remote.strip_prefix("git@github.com:") {
-        rest
-    } else if let Some(rest) = remote.strip_prefix("ssh://git@github.com/") {
-        rest
-    } else if let Some(rest) = remote.strip_prefix("https://github.com/") {
-        rest
-    } else {
-        return None;
-    };
-
-    let stripped = stripped.strip_suffix(".git").unwrap_or(stripped);
-    let mut parts = stripped.split('/');
-    let owner = parts.next()?.trim();
-    let repo_name = parts.next()?.trim();
-    if owner.is_empty() || repo_name.is_empty() || parts.next().is_some() {
-        return None;
-    }
-
-    Some((owner.to_string(), repo_name.to_string()))
-}
-
 #[cfg(test)]
 mod tests {
     use super::{
-        github_commit_url, load_commit_inspect_data, load_commits, load_refs,
-        parse_git_log_output, parse_github_remote_url, truncate_diff_preview,
+        default_remote, default_revisions, load_commit_inspect_data, load_commits, load_line_stats,
+        load_refs, parse_git_log_output, parse_revision_args, remote_forge, truncate_diff_preview,
+        validate_revisions,
     };
-    use crate::git::model::ChangeKind;
+    use crate::forge::{ForgeHosts, ForgeKind};
+    use crate::git::model::{ChangeKind, LineStats, SignatureStatus};
+    use crate::git::testing::{run_git, TempRepo};
     use std::fs;
-    use std::path::{Path, PathBuf};
-    use std::process::Command;
-    use std::time::{SystemTime, UNIX_EPOCH};
-
-    struct TempRepo {
-        path: PathBuf,
-    }
-
-    impl TempRepo {
-        fn new() -> Self {
-            let mut path = std::env::temp_dir();
-            let unique = SystemTime::now()
-                .duration_since(UNIX_EPOCH)
-                .expect("system time before unix epoch")
-                .as_nanos();
-            path.push(format!("gitviz-test-{}-{}", std::process::id(), unique));
-            fs::create_dir_all(&path).expect("failed to create temp repo dir");
-
-            run_git(&path, &["init", "-b", "main"]);
-            run_git(&path, &["config", "user.name", "Gitviz Test"]);
-            run_git(&path, &["config", "user.email", "gitviz@example.com"]);
-
-            Self { path }
-        }
-
-        fn path(&self) -> &Path {
-            &self.path
-        }
-
-        fn commit_file(&self, name: &str, contents: &str, message: &str) -> String {
-            fs::write(self.path.join(name), contents).expect("failed to write test file");
-            run_git(self.path(), &["add", name]);
-            run_git(self.path(), &["commit", "-m", message]);
-            run_git(self.path(), &["rev-parse", "HEAD"]).trim().to_string()
-        }
-
-        fn commit_file_with_body(
-            &self,
-            name: &str,
-            contents: &str,
-            subject: &str,
-            body: &str,
-        ) -> String {
-            fs::write(self.path.join(name), contents).expect("failed to write test file");
-            run_git(self.path(), &["add", name]);
-            run_git(self.path(), &["commit", "-m", subject, "-m", body]);
-            run_git(self.path(), &["rev-parse", "HEAD"]).trim().to_string()
-        }
-    }
-
-    impl Drop for TempRepo {
-        fn drop(&mut self) {
-            let _ = fs::remove_dir_all(&self.path);
-        }
-    }
-
-    fn run_git(repo: &Path, args: &[&str]) -> String {
-        let output = Command::new("git")
-            .arg("-C")
-            .arg(repo)
-            .args(args)
-            .output()
-            .expect("failed to run git command");
-
-        assert!(
-            output.status.success(),
-            "git {:?} failed: {}",
-            args,
-            String::from_utf8_lossy(&output.stderr)
-        );
-
-        String::from_utf8(output.stdout).expect("git output was not valid UTF-8")
-    }
 
     #[test]
     fn test_parse_git_log_output_empty_is_ok() {
@@ -283,7 +349,7 @@ mod tests {
 
     #[test]
     fn test_parse_git_log_output_valid_is_ok() {
-        let output = "aaa\x1f\x1fAuth1\x1fa@b.com\x1f1000\x1fFirst\x1fBody\x1e";
+        let output = "aaa\x1f\x1fAuth1\x1fa@b.com\x1fAuth1\x1fa@b.com\x1f1000\x1f2023-11-14 22:13:20 +0000\x1fCommitter\x1fc@example.com\x1fCommitter\x1fc@example.com\x1f1000\x1f2023-11-14 22:13:20 +0000\x1fN\x1f\x1f\x1fFirst\x1fBody\x1e";
         let commits = parse_git_log_output(output).expect("valid output should parse");
         assert_eq!(commits.len(), 1);
         assert_eq!(commits[0].oid, "aaa");
@@ -292,8 +358,8 @@ mod tests {
 
     #[test]
     fn test_parse_git_log_output_rejects_malformed_records() {
-        let valid = "aaa\x1f\x1fAuth1\x1fa@b.com\x1f1000\x1fFirst\x1fBody";
-        let invalid = "bbb\x1faaa\x1fAuth2\x1fb@c.com\x1fnot-a-number\x1fSecond\x1fBody";
+        let valid = "aaa\x1f\x1fAuth1\x1fa@b.com\x1fAuth1\x1fa@b.com\x1f1000\x1f2023-11-14 22:13:20 +0000\x1fCommitter\x1fc@example.com\x1fCommitter\x1fc@example.com\x1f1000\x1f2023-11-14 22:13:20 +0000\x1fN\x1f\x1f\x1fFirst\x1fBody";
+        let invalid = "bbb\x1faaa\x1fAuth2\x1fb@c.com\x1fAuth2\x1fb@c.com\x1fnot-a-number\x1f2023-11-14 22:13:20 +0000\x1fCommitter\x1fc@example.com\x1fCommitter\x1fc@example.com\x1fnot-a-number\x1f2023-11-14 22:13:20 +0000\x1fN\x1f\x1f\x1fSecond\x1fBody";
         let output = format!("{}\x1e{}\x1e", valid, invalid);
         let err = parse_git_log_output(&output).expect_err("must fail when any row is malformed");
         let msg = err.to_string();
@@ -316,17 +382,162 @@ mod tests {
         assert!(refs.labels_for(&head).iter().any(|label| label == "origin/main"));
     }
 
+    #[test]
+    fn test_branch_names_prefer_local_and_trunk() {
+        let repo = TempRepo::new();
+        let base = repo.commit_file("README.md", "hello\n", "initial commit");
+        run_git(repo.path(), &["branch", "aaa"]);
+        run_git(repo.path(), &["checkout", "-q", "-b", "topic"]);
+        let topic = repo.commit_file("topic.txt", "topic\n", "topic work");
+        run_git(
+            repo.path(),
+            &["update-ref", "refs/remotes/origin/topic", &topic],
+        );
+        run_git(
+            repo.path(),
+            &["update-ref", "refs/remotes/origin/zzz", &topic],
+        );
+
+        let names = load_refs(repo.path())
+            .expect("refs should load")
+            .branch_names();
+        assert_eq!(names.get(&base).map(String::as_str), Some("main"));
+        assert_eq!(names.get(&topic).map(String::as_str), Some("topic"));
+    }
+
+    #[test]
+    fn test_load_refs_reads_upstream_ahead_behind() {
+        let repo = TempRepo::new();
+        let base = repo.commit_file("app.txt", "one\n", "base");
+        repo.commit_file("app.txt", "two\n", "local work");
+        let head = repo.commit_file("app.txt", "three\n", "more local work");
+        run_git(
+            repo.path(),
+            &["remote", "add", "origin", "https://example.com/repo.git"],
+        );
+        run_git(
+            repo.path(),
+            &["update-ref", "refs/remotes/origin/main", &base],
+        );
+        run_git(repo.path(), &["config", "branch.main.remote", "origin"]);
+        run_git(
+            repo.path(),
+            &["config", "branch.main.merge", "refs/heads/main"],
+        );
+
+        let refs = load_refs(repo.path()).expect("refs should load");
+        let tracking = refs
+            .tracking
+            .get("refs/heads/main")
+            .expect("main should track origin/main");
+        assert_eq!(tracking.upstream, "refs/remotes/origin/main");
+        assert_eq!((tracking.ahead, tracking.behind), (2, 0));
+        assert!(refs
+            .labels_for(&head)
+            .iter()
+            .any(|label| label == "HEAD -> main"));
+    }
+
     #[test]
     fn test_load_commits_excludes_revision_boundary() {
         let repo = TempRepo::new();
         let first = repo.commit_file("app.txt", "one\n", "first");
         let second = repo.commit_file("app.txt", "two\n", "second");
 
-        let commits =
-            load_commits(repo.path(), 50, true, Some(&first)).expect("commits should load");
+        let commits = load_commits(repo.path(), 50, &default_revisions(true), Some(&first))
+            .expect("commits should load");
 
         assert_eq!(commits.len(), 1);
         assert_eq!(commits[0].oid, second);
+
+        let output = repo.path().join("pwned");
+        let option = format!("--output={}", output.display());
+        assert!(load_commits(repo.path(), 50, &default_revisions(true), Some(&option)).is_err());
+        assert!(!output.exists());
+    }
+
+    #[test]
+    fn test_load_commits_limited_to_visible_refs() {
+        let repo = TempRepo::new();
+        repo.commit_file("app.txt", "one\n", "base");
+        run_git(repo.path(), &["checkout", "-b", "stale"]);
+        let stale = repo.commit_file("stale.txt", "old\n", "stale work");
+        run_git(repo.path(), &["checkout", "main"]);
+        repo.commit_file("app.txt", "two\n", "main work");
+
+        let refs = load_refs(repo.path()).expect("refs should load");
+        let all = load_commits(repo.path(), 50, &default_revisions(true), None)
+            .expect("commits should load");
+        assert!(all.iter().any(|commit| commit.oid == stale));
+
+        let hidden = ["refs/heads/stale".to_string(), "stash@{0}".to_string()]
+            .into_iter()
+            .collect();
+        let revisions = refs.visible_revisions(&hidden);
+        assert_eq!(
+            revisions,
+            [
+                "HEAD",
+                "--exclude=refs/heads/stale",
+                "--exclude=refs/stash",
+                "--all"
+            ]
+        );
+        let visible = load_commits(repo.path(), 50, &revisions, None).expect("commits should load");
+        assert_eq!(visible.len(), 2);
+        assert!(visible.iter().all(|commit| commit.oid != stale));
+    }
+
+    #[test]
+    fn test_parse_revision_args_allows_revision_options_only() {
+        let args = parse_revision_args("  main..feature --branches=release/* ^origin/main ")
+            .expect("range should parse");
+        assert_eq!(
+            args,
+            vec!["main..feature", "--branches=release/*", "^origin/main"]
+        );
+        assert!(parse_revision_args("--all --not v1.0").is_ok());
+        let err = parse_revision_args("HEAD --output=/tmp/x").unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("unsupported option '--output=/tmp/x'"));
+    }
+
+    #[test]
+    fn test_validate_revisions_and_load_range() {
+        let repo = TempRepo::new();
+        repo.commit_file("app.txt", "one\n", "base");
+        run_git(repo.path(), &["checkout", "-b", "feature"]);
+        let feature = repo.commit_file("feature.txt", "new\n", "feature work");
+        run_git(repo.path(), &["checkout", "main"]);
+
+        let range = parse_revision_args("main..feature").expect("range should parse");
+        validate_revisions(repo.path(), &range).expect("range should resolve");
+        let commits = load_commits(repo.path(), 50, &range, None).expect("commits should load");
+        assert_eq!(commits.len(), 1);
+        assert_eq!(commits[0].oid, feature);
+
+        let missing = parse_revision_args("main..nope").expect("range should parse");
+        let err = validate_revisions(repo.path(), &missing).unwrap_err();
+        assert!(err.to_string().starts_with("invalid revision range"));
+    }
+
+    #[test]
+    fn test_load_refs_includes_stashes() {
+        let repo = TempRepo::new();
+        repo.commit_file("app.txt", "one\n", "base");
+        fs::write(repo.path().join("app.txt"), "dirty\n").expect("failed to write test file");
+        run_git(repo.path(), &["stash", "push", "-m", "wip"]);
+        let stash = run_git(repo.path(), &["rev-parse", "stash@{0}"])
+            .trim()
+            .to_string();
+
+        let refs = load_refs(repo.path()).expect("refs should load");
+        assert_eq!(refs.stashes, vec![("stash@{0}".to_string(), stash.clone())]);
+        assert!(refs
+            .labels_for(&stash)
+            .iter()
+            .any(|label| label == "stash@{0}"));
     }
 
     #[test]
@@ -341,19 +552,6 @@ mod tests {
         assert!(!data.file_list_truncated);
     }
 
-    #[test]
-    fn test_parse_github_remote_url_variants() {
-        let ssh = parse_github_remote_url("git@github.com:owner/repo.git");
-        let ssh_scheme = parse_github_remote_url("ssh://git@github.com/owner/repo.git");
-        let https = parse_github_remote_url("https://github.com/owner/repo.git");
-        let https_plain = parse_github_remote_url("https://github.com/owner/repo");
-        assert_eq!(ssh, Some(("owner".to_string(), "repo".to_string())));
-        assert_eq!(ssh_scheme, Some(("owner".to_string(), "repo".to_string())));
-        assert_eq!(https, Some(("owner".to_string(), "repo".to_string())));
-        assert_eq!(https_plain, Some(("owner".to_string(), "repo".to_string())));
-        assert!(parse_github_remote_url("git@gitlab.com:owner/repo.git").is_none());
-    }
-
     #[test]
     fn test_truncate_diff_preview_limits_lines() {
         let diff = (0..450)
@@ -363,18 +561,213 @@ mod tests {
         let (text, truncated) = truncate_diff_preview(&diff, 400);
         assert!(truncated);
         assert!(text.contains("line-399"));
-        assert!(text.contains("... diff truncated; open in GitHub or use git show for the full patch"));
+        assert!(text.contains(
+            "... diff truncated; open the commit in the browser or use git show for the full patch"
+        ));
     }
 
     #[test]
-    fn test_github_commit_url_uses_origin_remote() {
+    fn test_load_commits_reads_signatures() {
+        let repo = TempRepo::new();
+        let base = repo.commit_file("a.txt", "a", "unsigned");
+        if repo.enable_ssh_signing().is_none() {
+            eprintln!("ssh-keygen not installed; skipping signature test");
+            return;
+        }
+        run_git(
+            repo.path(),
+            &["commit", "--allow-empty", "-S", "-m", "signed"],
+        );
+        let stranger = repo
+            .generate_ssh_key("stranger")
+            .expect("ssh-keygen ran once already");
+        let stranger_key = format!("user.signingkey={}", stranger.display());
+        run_git(
+            repo.path(),
+            &[
+                "-c",
+                &stranger_key,
+                "commit",
+                "--allow-empty",
+                "-S",
+                "-m",
+                "by stranger",
+            ],
+        );
+        // Rewrite the message of a signed commit so its signature no longer matches.
+        run_git(
+            repo.path(),
+            &["commit", "--allow-empty", "-S", "-m", "tampered"],
+        );
+        let object =
+            run_git(repo.path(), &["cat-file", "commit", "HEAD"]).replace("tampered", "altered");
+        let hash_object = ["hash-object", "-t", "commit", "-w", "--stdin"];
+        let forged = git_with_stdin(repo.path(), &hash_object, &object);
+        run_git(
+            repo.path(),
+            &["update-ref", "refs/heads/main", forged.trim()],
+        );
+
+        let commits =
+            load_commits(repo.path(), 10, &default_revisions(false), None).expect("load commits");
+        let statuses: Vec<(&str, SignatureStatus, &str)> = commits
+            .iter()
+            .map(|commit| {
+                let signature = &commit.signature;
+                (
+                    commit.subject.as_str(),
+                    signature.status,
+                    signature.signer.as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            statuses,
+            [
+                ("altered", SignatureStatus::Bad, ""),
+                ("by stranger", SignatureStatus::GoodUnknownValidity, ""),
+                ("signed", SignatureStatus::Good, "gitviz@example.com"),
+                ("unsigned", SignatureStatus::Unsigned, ""),
+            ]
+        );
+        assert!(commits[2].signature.key.starts_with("SHA256:"));
+        assert_eq!(commits[3].oid, base);
+    }
+
+    #[test]
+    fn test_load_commits_applies_mailmap() {
+        let repo = TempRepo::new();
+        run_git(repo.path(), &["config", "user.name", "ada"]);
+        run_git(repo.path(), &["config", "user.email", "ada@laptop.local"]);
+        repo.commit_file(
+            ".mailmap",
+            "Ada Lovelace <ada@example.com> ada <ada@laptop.local>\n",
+            "add mailmap",
+        );
+
+        let commits =
+            load_commits(repo.path(), 10, &default_revisions(false), None).expect("load commits");
+        let commit = &commits[0];
+        assert_eq!(
+            commit.author_identity(false),
+            ("Ada Lovelace", "ada@example.com")
+        );
+        assert_eq!(commit.author_identity(true), ("ada", "ada@laptop.local"));
+        assert_eq!(
+            commit.committer_identity(false),
+            ("Ada Lovelace", "ada@example.com")
+        );
+        assert_eq!(commit.committer_identity(true), ("ada", "ada@laptop.local"));
+    }
+
+    #[test]
+    fn test_load_line_stats() {
+        let repo = TempRepo::new();
+        let first = repo.commit_file("a.txt", "one\ntwo\n", "first");
+        let second = repo.commit_file("a.txt", "one\n2\n3\n", "second");
+
+        let stats = load_line_stats(repo.path(), &[second.clone(), first.clone()])
+            .expect("line counts should load");
+        assert_eq!(
+            stats,
+            [
+                (
+                    second,
+                    LineStats {
+                        added: 2,
+                        removed: 1
+                    }
+                ),
+                (
+                    first,
+                    LineStats {
+                        added: 2,
+                        removed: 0
+                    }
+                ),
+            ]
+        );
+    }
+
+    fn git_with_stdin(repo: &std::path::Path, args: &[&str], input: &str) -> String {
+        use std::io::Write;
+        use std::process::{Command, Stdio};
+        let mut child = Command::new("git")
+            .arg("-C")
+            .arg(repo)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .expect("spawn git");
+        child
+            .stdin
+            .take()
+            .expect("stdin")
+            .write_all(input.as_bytes())
+            .expect("write stdin");
+        let output = child.wait_with_output().expect("wait for git");
+        assert!(output.status.success(), "git {:?} failed", args);
+        String::from_utf8(output.stdout).expect("UTF-8 output")
+    }
+
+    #[test]
+    fn test_remote_forge_follows_branch_upstream() {
         let repo = TempRepo::new();
         let oid = repo.commit_file("README.md", "hello\n", "initial commit");
+        let hosts = ForgeHosts::new();
+        assert_eq!(default_remote(repo.path()), None);
+
+        run_git(
+            repo.path(),
+            &[
+                "remote",
+                "add",
+                "mirror",
+                "https://gitlab.com/group/sub/repo.git",
+            ],
+        );
+        assert_eq!(default_remote(repo.path()).as_deref(), Some("mirror"));
         run_git(
             repo.path(),
-            &["remote", "add", "origin", "https://github.com/owner/repo.git"],
+            &[
+                "remote",
+                "add",
+                "origin",
+                "https://github.com/owner/repo.git",
+            ],
+        );
+        assert_eq!(default_remote(repo.path()).as_deref(), Some("origin"));
+        let forge = remote_forge(repo.path(), None, &hosts).expect("origin should resolve");
+        assert_eq!(
+            forge.commit_url(&oid),
+            format!("https://github.com/owner/repo/commit/{oid}")
         );
-        let url = github_commit_url(repo.path(), &oid).expect("github url should resolve");
-        assert_eq!(url, format!("https://github.com/owner/repo/commit/{oid}"));
+
+        run_git(repo.path(), &["config", "branch.main.remote", "mirror"]);
+        let forge = remote_forge(repo.path(), None, &hosts).expect("upstream should resolve");
+        assert_eq!(forge.kind(), ForgeKind::GitLab);
+        let forge =
+            remote_forge(repo.path(), Some("origin"), &hosts).expect("origin should resolve");
+        assert_eq!(forge.kind(), ForgeKind::GitHub);
+        assert!(remote_forge(repo.path(), Some("missing"), &hosts).is_none());
+    }
+
+    #[test]
+    fn test_remote_forge_applies_instead_of() {
+        let repo = TempRepo::new();
+        repo.commit_file("README.md", "hello\n", "initial commit");
+        run_git(
+            repo.path(),
+            &["config", "url.git@gitlab.com:.insteadOf", "work:"],
+        );
+        run_git(
+            repo.path(),
+            &["remote", "add", "origin", "work:group/sub/repo"],
+        );
+
+        let forge = remote_forge(repo.path(), None, &ForgeHosts::new())
+            .expect("rewritten remote should resolve");
+        assert_eq!(forge.repo_url(), "https://gitlab.com/group/sub/repo");
     }
 }
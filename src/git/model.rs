@@ -1,60 +1,391 @@
-#[derive(Debug, Clone)]
+use std::collections::{HashMap, HashSet};
+
+/// One commit as read from `git log`.
+#[derive(Debug, Clone, Default)]
 pub struct Commit {
+    /// Full object id.
     pub oid: String,
+    /// Parent object ids, first parent first.
     pub parents: Vec<String>,
+    /// Author name, mapped through `.mailmap`.
     pub author: String,
+    /// Author email, mapped through `.mailmap`.
     pub author_email: String,
+    /// Author name as recorded in the commit.
+    pub raw_author: String,
+    /// Author email as recorded in the commit.
+    pub raw_author_email: String,
+    /// Author date as Unix seconds.
     pub timestamp: i64,
+    /// Offset of the author's timezone from UTC, in minutes east.
+    pub author_tz_offset: i32,
+    /// Committer name, mapped through `.mailmap`; differs from the author
+    /// after a rebase, cherry-pick or applied patch.
+    pub committer: String,
+    /// Committer email, mapped through `.mailmap`.
+    pub committer_email: String,
+    /// Committer name as recorded in the commit.
+    pub raw_committer: String,
+    /// Committer email as recorded in the commit.
+    pub raw_committer_email: String,
+    /// Committer date as Unix seconds.
+    pub commit_timestamp: i64,
+    /// Offset of the committer's timezone from UTC, in minutes east.
+    pub committer_tz_offset: i32,
+    /// GPG, SSH or X.509 signature, as verified by `git log`.
+    pub signature: Signature,
+    /// First line of the message.
     pub subject: String,
+    /// Message after the subject, without the blank line between them.
     pub body: String,
+    /// Trailers such as `Signed-off-by` from the end of the body, in order.
+    pub trailers: Vec<Trailer>,
 }
 
+impl Commit {
+    /// Author name and email, as recorded when `raw` is set and mapped
+    /// through `.mailmap` otherwise.
+    pub fn author_identity(&self, raw: bool) -> (&str, &str) {
+        if raw {
+            (&self.raw_author, &self.raw_author_email)
+        } else {
+            (&self.author, &self.author_email)
+        }
+    }
+
+    /// Committer name and email, as recorded when `raw` is set and mapped
+    /// through `.mailmap` otherwise.
+    pub fn committer_identity(&self, raw: bool) -> (&str, &str) {
+        if raw {
+            (&self.raw_committer, &self.raw_committer_email)
+        } else {
+            (&self.committer, &self.committer_email)
+        }
+    }
+
+    /// The body without its trailer block.
+    pub fn body_without_trailers(&self) -> &str {
+        if self.trailers.is_empty() {
+            &self.body
+        } else {
+            super::parser::split_trailers(&self.body).0
+        }
+    }
+
+    /// Values of the trailers named `key`, compared case-insensitively.
+    pub fn trailer_values<'a>(&'a self, key: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+        self.trailers
+            .iter()
+            .filter(move |trailer| trailer.key.eq_ignore_ascii_case(key))
+            .map(|trailer| trailer.value.as_str())
+    }
+}
+
+/// Result of checking a commit signature, from `git log --format=%G?`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum SignatureStatus {
+    /// Not signed (`N`).
+    #[default]
+    Unsigned,
+    /// Good signature from a trusted key (`G`).
+    Good,
+    /// Good signature from a key of unknown validity, or an SSH key with no
+    /// entry in `gpg.ssh.allowedSignersFile` (`U`).
+    GoodUnknownValidity,
+    /// Good signature that has expired (`X`).
+    GoodExpired,
+    /// Good signature made by a key that has since expired (`Y`).
+    GoodExpiredKey,
+    /// Good signature made by a revoked key (`R`).
+    GoodRevokedKey,
+    /// The key is not available, so the signature could not be checked (`E`).
+    CannotCheck,
+    /// The signature does not match the commit (`B`).
+    Bad,
+}
+
+/// How much a [`SignatureStatus`] can be relied on, for badges and filters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignatureTrust {
+    /// No signature.
+    Unsigned,
+    /// A good signature from a trusted key.
+    Verified,
+    /// A signature that is not known to be bad but cannot be trusted: the
+    /// key is unknown, missing or expired.
+    Unknown,
+    /// A bad signature, or one made by a revoked key.
+    Bad,
+}
+
+impl SignatureTrust {
+    /// Name used in filters and machine-readable output.
+    pub fn name(self) -> &'static str {
+        match self {
+            SignatureTrust::Unsigned => "unsigned",
+            SignatureTrust::Verified => "verified",
+            SignatureTrust::Unknown => "unknown",
+            SignatureTrust::Bad => "bad",
+        }
+    }
+}
+
+impl SignatureStatus {
+    /// Parse a `%G?` letter; anything unexpected counts as uncheckable.
+    pub fn from_code(code: &str) -> Self {
+        match code {
+            "N" | "" => SignatureStatus::Unsigned,
+            "G" => SignatureStatus::Good,
+            "U" => SignatureStatus::GoodUnknownValidity,
+            "X" => SignatureStatus::GoodExpired,
+            "Y" => SignatureStatus::GoodExpiredKey,
+            "R" => SignatureStatus::GoodRevokedKey,
+            "B" => SignatureStatus::Bad,
+            _ => SignatureStatus::CannotCheck,
+        }
+    }
+
+    /// Stable name for machine-readable output.
+    pub fn name(self) -> &'static str {
+        match self {
+            SignatureStatus::Unsigned => "unsigned",
+            SignatureStatus::Good => "good",
+            SignatureStatus::GoodUnknownValidity => "unknown_validity",
+            SignatureStatus::GoodExpired => "expired",
+            SignatureStatus::GoodExpiredKey => "expired_key",
+            SignatureStatus::GoodRevokedKey => "revoked_key",
+            SignatureStatus::CannotCheck => "cannot_check",
+            SignatureStatus::Bad => "bad",
+        }
+    }
+
+    /// Coarse classification of the status.
+    pub fn trust(self) -> SignatureTrust {
+        match self {
+            SignatureStatus::Unsigned => SignatureTrust::Unsigned,
+            SignatureStatus::Good => SignatureTrust::Verified,
+            SignatureStatus::GoodUnknownValidity
+            | SignatureStatus::GoodExpired
+            | SignatureStatus::GoodExpiredKey
+            | SignatureStatus::CannotCheck => SignatureTrust::Unknown,
+            SignatureStatus::GoodRevokedKey | SignatureStatus::Bad => SignatureTrust::Bad,
+        }
+    }
+
+    /// What the status means, for the details pane.
+    pub fn description(self) -> &'static str {
+        match self {
+            SignatureStatus::Unsigned => "unsigned",
+            SignatureStatus::Good => "good signature",
+            SignatureStatus::GoodUnknownValidity => "good signature from an untrusted key",
+            SignatureStatus::GoodExpired => "good signature, expired",
+            SignatureStatus::GoodExpiredKey => "good signature from an expired key",
+            SignatureStatus::GoodRevokedKey => "good signature from a revoked key",
+            SignatureStatus::CannotCheck => "cannot be checked: key not available",
+            SignatureStatus::Bad => "BAD signature",
+        }
+    }
+}
+
+/// A commit's signature and who made it.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Signature {
+    /// Verification result.
+    pub status: SignatureStatus,
+    /// Signer (`%GS`): the key's user id, or the SSH principal from the
+    /// allowed signers file. Empty when unknown.
+    pub signer: String,
+    /// Key that made the signature (`%GK`): a GPG key id or an SSH key
+    /// fingerprint. Empty when unsigned.
+    pub key: String,
+}
+
+/// A `Key: value` line from the trailer block that ends a commit message,
+/// as written by `git commit --trailer` or `--signoff`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Trailer {
+    /// Key as written, e.g. `Reviewed-by`.
+    pub key: String,
+    /// Value with folded continuation lines joined by single spaces.
+    pub value: String,
+}
+
+/// Status letter of a changed file in `git show --name-status`.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ChangeKind {
+    /// `A`
     Added,
+    /// `M`
     Modified,
+    /// `D`
     Deleted,
+    /// `R`, with a similarity score.
     Renamed,
+    /// `C`, with a similarity score.
     Copied,
+    /// `T`: the file changed type, e.g. from a file to a symlink.
     TypeChanged,
+    /// `U`
     Unmerged,
+    /// Any other status, kept verbatim.
     Unknown(String),
 }
 
+/// A file touched by a commit.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ChangedFile {
+    /// Path after the change.
     pub path: String,
+    /// How the file changed.
     pub change_kind: ChangeKind,
+    /// Path before a rename or copy.
     pub old_path: Option<String>,
 }
 
+/// Lines added and removed by a commit, as `git log --numstat` counts them.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct LineStats {
+    /// Lines added.
+    pub added: u64,
+    /// Lines removed.
+    pub removed: u64,
+}
+
+impl LineStats {
+    /// Lines added plus lines removed.
+    pub fn changed(&self) -> u64 {
+        self.added + self.removed
+    }
+}
+
+impl std::ops::AddAssign for LineStats {
+    fn add_assign(&mut self, other: LineStats) {
+        self.added += other.added;
+        self.removed += other.removed;
+    }
+}
+
+/// Changed files and patch of one commit, loaded on demand.
 #[derive(Debug, Clone)]
 pub struct CommitInspectData {
+    /// Files touched by the commit, at most 1000.
     pub changed_files: Vec<ChangedFile>,
+    /// More files changed than `changed_files` holds.
     pub file_list_truncated: bool,
+    /// `git show` output: header, message and patch.
     pub diff_text: String,
+    /// `diff_text` was cut short.
     pub diff_truncated: bool,
 }
 
-#[derive(Debug, Clone)]
-pub enum InspectCacheEntry {
-    Loading,
-    Ready(CommitInspectData),
-    Error(String),
+/// Upstream configuration of a local branch and how far it has diverged.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct BranchTracking {
+    /// Full refname of the upstream, e.g. `refs/remotes/origin/main`.
+    pub upstream: String,
+    /// Commits on the branch that are not on the upstream.
+    pub ahead: usize,
+    /// Commits on the upstream that are not on the branch.
+    pub behind: usize,
+    /// The configured upstream no longer exists.
+    pub gone: bool,
 }
 
+impl BranchTracking {
+    /// Commits ahead plus commits behind.
+    pub fn divergence(&self) -> usize {
+        self.ahead + self.behind
+    }
+
+    /// Compact marker such as `↑2 ↓5`, empty when in sync.
+    pub fn marker(&self) -> String {
+        if self.gone {
+            return "gone".to_string();
+        }
+
+        let mut parts = Vec::new();
+        if self.ahead > 0 {
+            parts.push(format!("↑{}", self.ahead));
+        }
+        if self.behind > 0 {
+            parts.push(format!("↓{}", self.behind));
+        }
+        parts.join(" ")
+    }
+}
+
+/// Branch names treated as the trunk of the history.
+pub const TRUNK_BRANCHES: [&str; 2] = ["main", "master"];
+
+/// What kind of ref a decoration label came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RefKind {
+    /// `HEAD`, attached or detached.
+    Head,
+    /// A local branch.
+    Branch,
+    /// A remote-tracking branch.
+    Remote,
+    /// A tag.
+    Tag,
+    /// A stash entry.
+    Stash,
+}
+
+impl RefKind {
+    /// Kind of a full refname such as `refs/remotes/origin/main`.
+    pub fn of_refname(refname: &str) -> Self {
+        if refname.starts_with("refs/remotes/") {
+            RefKind::Remote
+        } else if refname.starts_with("refs/tags/") {
+            RefKind::Tag
+        } else if refname.starts_with("stash") || refname.starts_with("refs/stash") {
+            RefKind::Stash
+        } else {
+            RefKind::Branch
+        }
+    }
+}
+
+/// The repository's refs. Lists hold `(refname, oid)` pairs.
 #[derive(Debug, Default, Clone)]
 pub struct Refs {
+    /// Commit HEAD points at, empty in a repository without commits.
     pub head_oid: String,
-    pub head_name: Option<String>, // "refs/heads/main" or None for detached
-    pub branches: Vec<(String, String)>, // (refname, oid)
-    pub remotes: Vec<(String, String)>,  // (refname, oid)
-    pub tags: Vec<(String, String)>,     // (refname, oid)
+    /// Branch HEAD points at, e.g. `refs/heads/main`; `None` when detached.
+    pub head_name: Option<String>,
+    /// Local branches.
+    pub branches: Vec<(String, String)>,
+    /// Remote-tracking branches.
+    pub remotes: Vec<(String, String)>,
+    /// Tags, with the oid of the tag object for annotated tags.
+    pub tags: Vec<(String, String)>,
+    /// Stash entries, named `stash@{0}` and so on.
+    pub stashes: Vec<(String, String)>,
+    /// Upstream state keyed by local branch refname.
+    pub tracking: HashMap<String, BranchTracking>,
 }
 
 impl Refs {
     /// Returns a short label for a given OID, listing HEAD, branch names, tags.
     pub fn labels_for(&self, oid: &str) -> Vec<String> {
+        self.labeled_refs(oid)
+            .into_iter()
+            .map(|(_, label)| label)
+            .collect()
+    }
+
+    /// Like [`Refs::labels_for`], with the kind of ref behind each label.
+    pub fn labeled_refs(&self, oid: &str) -> Vec<(RefKind, String)> {
+        self.tracked_labels(oid)
+            .into_iter()
+            .map(|(kind, label, _)| (kind, label))
+            .collect()
+    }
+
+    /// Like [`Refs::labeled_refs`], with the upstream tracking of each local
+    /// branch for drawing its ahead/behind [`BranchTracking::marker`].
+    pub fn tracked_labels(&self, oid: &str) -> Vec<(RefKind, String, Option<&BranchTracking>)> {
         let mut labels = Vec::new();
 
         // HEAD indicator
@@ -64,9 +395,14 @@ impl Refs {
                 let short = name
                     .strip_prefix("refs/heads/")
                     .unwrap_or(name.as_str());
-                push_unique(&mut labels, format!("HEAD -> {}", short));
+                push_unique(
+                    &mut labels,
+                    RefKind::Head,
+                    format!("HEAD -> {}", short),
+                    self.tracking.get(name),
+                );
             } else {
-                push_unique(&mut labels, "HEAD".to_string());
+                push_unique(&mut labels, RefKind::Head, "HEAD".to_string(), None);
             }
         }
 
@@ -78,7 +414,12 @@ impl Refs {
                     .unwrap_or(refname.as_str());
                 // Skip if already covered by HEAD label
                 if self.head_name.as_deref() != Some(refname.as_str()) || self.head_oid != oid {
-                    push_unique(&mut labels, short.to_string());
+                    push_unique(
+                        &mut labels,
+                        RefKind::Branch,
+                        short.to_string(),
+                        self.tracking.get(refname),
+                    );
                 }
             }
         }
@@ -89,7 +430,7 @@ impl Refs {
                 let short = refname
                     .strip_prefix("refs/remotes/")
                     .unwrap_or(refname.as_str());
-                push_unique(&mut labels, short.to_string());
+                push_unique(&mut labels, RefKind::Remote, short.to_string(), None);
             }
         }
 
@@ -99,16 +440,138 @@ impl Refs {
                 let short = refname
                     .strip_prefix("refs/tags/")
                     .unwrap_or(refname.as_str());
-                push_unique(&mut labels, format!("tag: {}", short));
+                push_unique(&mut labels, RefKind::Tag, format!("tag: {}", short), None);
+            }
+        }
+
+        // Stash labels
+        for (name, ref_oid) in &self.stashes {
+            if ref_oid == oid {
+                push_unique(&mut labels, RefKind::Stash, name.clone(), None);
             }
         }
 
         labels
     }
+
+    /// Branch name at each branch tip, for colouring graph lines. Remote
+    /// branches are reported without their remote (`origin/x` as `x`); local
+    /// branches win over remote ones, the checked-out branch over other local
+    /// ones, and the trunk over everything.
+    pub fn branch_names(&self) -> HashMap<String, String> {
+        let remotes = self.remotes.iter().filter_map(|(refname, oid)| {
+            let short = refname.strip_prefix("refs/remotes/")?;
+            let (_, name) = short.split_once('/')?;
+            (name != "HEAD").then_some((name, oid))
+        });
+        let locals = self
+            .branches
+            .iter()
+            .filter_map(|(refname, oid)| Some((refname.strip_prefix("refs/heads/")?, oid)));
+        let head = self
+            .head_name
+            .as_deref()
+            .and_then(|name| name.strip_prefix("refs/heads/"))
+            .map(|name| (name, &self.head_oid));
+
+        let candidates: Vec<(&str, &String)> = remotes.chain(locals).chain(head).collect();
+        let trunk = candidates
+            .iter()
+            .filter(|(name, _)| TRUNK_BRANCHES.contains(name))
+            .copied()
+            .collect::<Vec<_>>();
+
+        let mut names = HashMap::new();
+        for (name, oid) in candidates.into_iter().chain(trunk) {
+            names.insert(oid.clone(), name.to_string());
+        }
+        names
+    }
+
+    /// Whether hiding `refname` changes `git log --all`: every ref under
+    /// `refs/` is a starting point, but of the stashes only the newest is,
+    /// through `refs/stash`.
+    pub fn can_hide(refname: &str) -> bool {
+        refname.starts_with("refs/") || refname == "stash@{0}"
+    }
+
+    /// Revisions for `git log --all` without the hidden refs, as `--exclude`
+    /// options before `--all` so the command line stays short however many
+    /// refs there are. HEAD is always included so the checked-out commit
+    /// stays visible.
+    pub fn visible_revisions(&self, hidden: &HashSet<String>) -> Vec<String> {
+        let mut hidden: Vec<&String> = hidden.iter().collect();
+        hidden.sort();
+        let mut revisions = vec!["HEAD".to_string()];
+        for refname in hidden.into_iter().filter(|refname| Refs::can_hide(refname)) {
+            let refname = match refname.as_str() {
+                "stash@{0}" => "refs/stash",
+                name => name,
+            };
+            revisions.push(format!("--exclude={}", escape_glob(refname)));
+        }
+        revisions.push("--all".to_string());
+        revisions
+    }
+
+    /// Remote-tracking refs grouped by remote name, in first-seen order.
+    pub fn remotes_by_name(&self) -> Vec<(&str, Vec<&(String, String)>)> {
+        let mut groups: Vec<(&str, Vec<&(String, String)>)> = Vec::new();
+        for entry in &self.remotes {
+            let short = entry
+                .0
+                .strip_prefix("refs/remotes/")
+                .unwrap_or(entry.0.as_str());
+            let remote = short.split('/').next().unwrap_or(short);
+            match groups.iter_mut().find(|(name, _)| *name == remote) {
+                Some((_, refs)) => refs.push(entry),
+                None => groups.push((remote, vec![entry])),
+            }
+        }
+        groups
+    }
+
+    /// Local branches as `(short name, tracking)`, most diverged first.
+    pub fn branches_by_divergence(&self) -> Vec<(&str, Option<&BranchTracking>)> {
+        let mut branches: Vec<(&str, Option<&BranchTracking>)> = self
+            .branches
+            .iter()
+            .map(|(refname, _)| {
+                let short = refname
+                    .strip_prefix("refs/heads/")
+                    .unwrap_or(refname.as_str());
+                (short, self.tracking.get(refname))
+            })
+            .collect();
+
+        branches.sort_by(|(a_name, a), (b_name, b)| {
+            let a_div = a.map(BranchTracking::divergence).unwrap_or(0);
+            let b_div = b.map(BranchTracking::divergence).unwrap_or(0);
+            b_div.cmp(&a_div).then_with(|| a_name.cmp(b_name))
+        });
+        branches
+    }
+}
+
+/// Escape the characters `--exclude` treats as glob syntax.
+fn escape_glob(refname: &str) -> String {
+    let mut escaped = String::with_capacity(refname.len());
+    for c in refname.chars() {
+        if matches!(c, '*' | '?' | '[' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
 }
 
-fn push_unique(labels: &mut Vec<String>, label: String) {
-    if !labels.iter().any(|existing| existing == &label) {
-        labels.push(label);
+fn push_unique<'a>(
+    labels: &mut Vec<(RefKind, String, Option<&'a BranchTracking>)>,
+    kind: RefKind,
+    label: String,
+    tracking: Option<&'a BranchTracking>,
+) {
+    if !labels.iter().any(|(_, existing, _)| existing == &label) {
+        labels.push((kind, label, tracking));
     }
 }
@@ -1,14 +1,25 @@
-use super::model::{ChangeKind, ChangedFile, Commit};
+use super::model::{
+    BranchTracking, ChangeKind, ChangedFile, Commit, LineStats, Signature, SignatureStatus, Trailer,
+};
+use super::remote::UrlRewrite;
 
+/// Result of [`parse_commits`]: the commits that parsed, and a count of the
+/// records that did not.
 #[derive(Debug, Default)]
 pub struct ParseCommitsReport {
+    /// Commits from well-formed records, in output order.
     pub commits: Vec<Commit>,
+    /// Non-empty records seen.
     pub total_records: usize,
+    /// Records skipped because they were malformed.
     pub rejected_records: usize,
+    /// Why the first rejected record was skipped.
     pub first_error: Option<String>,
 }
 
-/// Parse commits from `git log --format=%H%x1f%P%x1f%an%x1f%ae%x1f%at%x1f%s%x1f%b%x1e` output.
+/// Parse commits from
+/// `git log --format=%H%x1f%P%x1f%aN%x1f%aE%x1f%an%x1f%ae%x1f%at%x1f%ai%x1f%cN%x1f%cE%x1f%cn%x1f%ce%x1f%ct%x1f%ci%x1f%G?%x1f%GS%x1f%GK%x1f%s%x1f%b%x1e`
+/// output.
 /// Records are delimited by ASCII record separator (0x1e).
 /// Fields within each record are delimited by ASCII unit separator (0x1f).
 pub fn parse_commits(output: &str) -> ParseCommitsReport {
@@ -36,11 +47,11 @@ pub fn parse_commits(output: &str) -> ParseCommitsReport {
 }
 
 fn parse_commit_record(record: &str) -> Result<Commit, String> {
-    // splitn(7, ...) so that body (field 7) is kept intact even if it
+    // splitn(19, ...) so that body (field 19) is kept intact even if it
     // somehow contained the separator (unlikely but safe).
-    let parts: Vec<&str> = record.splitn(7, '\x1f').collect();
-    if parts.len() < 7 {
-        return Err(format!("missing fields: expected 7, got {}", parts.len()));
+    let parts: Vec<&str> = record.splitn(19, '\x1f').collect();
+    if parts.len() < 19 {
+        return Err(format!("missing fields: expected 19, got {}", parts.len()));
     }
 
     let oid = parts[0].trim().to_string();
@@ -54,24 +65,111 @@ fn parse_commit_record(record: &str) -> Result<Commit, String> {
         .filter(|s| !s.is_empty())
         .collect();
 
-    let author = parts[2].trim().to_string();
-    let author_email = parts[3].trim().to_string();
-    let timestamp_raw = parts[4].trim();
-    let timestamp = timestamp_raw
-        .parse::<i64>()
-        .map_err(|_| format!("invalid timestamp '{}'", timestamp_raw))?;
+    let field = |index: usize| parts[index].trim().to_string();
+    let timestamp = parse_timestamp(parts[6])?;
+    let author_tz_offset = parse_tz_offset(parts[7])?;
+    let commit_timestamp = parse_timestamp(parts[12])?;
+    let committer_tz_offset = parse_tz_offset(parts[13])?;
+    let signature = Signature {
+        status: SignatureStatus::from_code(parts[14].trim()),
+        signer: field(15),
+        key: field(16),
+    };
     // subject may have trailing \n from git's tformat
-    let subject = parts[5].trim_end_matches('\n').to_string();
-    let body = parts[6].trim_end_matches('\n').to_string();
+    let subject = parts[17].trim_end_matches('\n').to_string();
+    let body = parts[18].trim_end_matches('\n').to_string();
+    let (_, trailers) = split_trailers(&body);
 
     Ok(Commit {
         oid,
         parents,
-        author,
-        author_email,
+        author: field(2),
+        author_email: field(3),
+        raw_author: field(4),
+        raw_author_email: field(5),
         timestamp,
+        author_tz_offset,
+        committer: field(8),
+        committer_email: field(9),
+        raw_committer: field(10),
+        raw_committer_email: field(11),
+        commit_timestamp,
+        committer_tz_offset,
+        signature,
         subject,
         body,
+        trailers,
+    })
+}
+
+fn parse_timestamp(raw: &str) -> Result<i64, String> {
+    let raw = raw.trim();
+    raw.parse::<i64>()
+        .map_err(|_| format!("invalid timestamp '{}'", raw))
+}
+
+/// Minutes east of UTC from the `+hhmm` that ends a `%ai` / `%ci` date.
+fn parse_tz_offset(date: &str) -> Result<i32, String> {
+    let invalid = || format!("invalid timezone in date '{}'", date.trim());
+    let zone = date.split_whitespace().last().ok_or_else(invalid)?;
+    let (sign, digits) = match (zone.strip_prefix('+'), zone.strip_prefix('-')) {
+        (Some(digits), _) => (1, digits),
+        (_, Some(digits)) => (-1, digits),
+        _ => return Err(invalid()),
+    };
+    if digits.len() != 4 || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(invalid());
+    }
+    let hours: i32 = digits[..2].parse().map_err(|_| invalid())?;
+    let minutes: i32 = digits[2..].parse().map_err(|_| invalid())?;
+    Ok(sign * (hours * 60 + minutes))
+}
+
+/// Split a commit body into the text before its trailer block and the
+/// trailers. As with `git interpret-trailers`, the block is the last
+/// paragraph when every line in it is a `Key: value` trailer or an indented
+/// continuation of one; otherwise there are no trailers and the whole body
+/// is returned.
+pub fn split_trailers(body: &str) -> (&str, Vec<Trailer>) {
+    let text = body.trim_end();
+    let mut start = 0;
+    let mut offset = 0;
+    for line in text.split_inclusive('\n') {
+        offset += line.len();
+        if line.trim().is_empty() {
+            start = offset;
+        }
+    }
+
+    let mut trailers: Vec<Trailer> = Vec::new();
+    for line in text[start..].lines() {
+        if line.starts_with([' ', '\t']) {
+            let Some(last) = trailers.last_mut() else {
+                return (body, Vec::new());
+            };
+            last.value.push(' ');
+            last.value.push_str(line.trim());
+        } else if let Some(trailer) = parse_trailer_line(line) {
+            trailers.push(trailer);
+        } else {
+            return (body, Vec::new());
+        }
+    }
+
+    if trailers.is_empty() {
+        return (body, trailers);
+    }
+    (text[..start].trim_end(), trailers)
+}
+
+fn parse_trailer_line(line: &str) -> Option<Trailer> {
+    let (key, value) = line.split_once(':')?;
+    let valid_key = !key.is_empty()
+        && !key.starts_with('-')
+        && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '-');
+    valid_key.then(|| Trailer {
+        key: key.to_string(),
+        value: value.trim().to_string(),
     })
 }
 
@@ -94,6 +192,85 @@ pub fn parse_show_ref(output: &str) -> Vec<(String, String)> {
         .collect()
 }
 
+/// Parse `git stash list --format=%gd%x1f%H` output.
+/// Returns `Vec<("stash@{n}", oid)>`.
+pub fn parse_stash_list(output: &str) -> Vec<(String, String)> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (name, oid) = line.split_once('\x1f')?;
+            let (name, oid) = (name.trim(), oid.trim());
+            if name.is_empty() || oid.is_empty() {
+                None
+            } else {
+                Some((name.to_string(), oid.to_string()))
+            }
+        })
+        .collect()
+}
+
+/// Parse `git for-each-ref --format=%(refname)%1f%(upstream)%1f%(upstream:track,nobracket)`
+/// output. Branches without a configured upstream are skipped.
+/// Returns `Vec<(branch refname, tracking)>`.
+pub fn parse_upstream_tracking(output: &str) -> Vec<(String, BranchTracking)> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.splitn(3, '\x1f');
+            let refname = parts.next()?.trim();
+            let upstream = parts.next()?.trim();
+            let track = parts.next().unwrap_or("").trim();
+            if refname.is_empty() || upstream.is_empty() {
+                return None;
+            }
+
+            let mut tracking = BranchTracking {
+                upstream: upstream.to_string(),
+                ..BranchTracking::default()
+            };
+            for item in track.split(',') {
+                let item = item.trim();
+                if item == "gone" {
+                    tracking.gone = true;
+                } else if let Some(count) = item.strip_prefix("ahead ") {
+                    tracking.ahead = count.trim().parse().unwrap_or(0);
+                } else if let Some(count) = item.strip_prefix("behind ") {
+                    tracking.behind = count.trim().parse().unwrap_or(0);
+                }
+            }
+
+            Some((refname.to_string(), tracking))
+        })
+        .collect()
+}
+
+/// Parse `git log --no-walk=unsorted --numstat --format=%x1e%H` output into
+/// the lines each commit added and removed. Binary files count no lines.
+pub fn parse_numstat(output: &str) -> Vec<(String, LineStats)> {
+    output
+        .split('\x1e')
+        .filter_map(|record| {
+            let mut lines = record.lines();
+            let oid = lines.next()?.trim();
+            if oid.is_empty() {
+                return None;
+            }
+
+            let mut stats = LineStats::default();
+            for line in lines {
+                let mut parts = line.split('\t');
+                let (Some(added), Some(removed)) = (parts.next(), parts.next()) else {
+                    continue;
+                };
+                // Binary files show `-` for both counts.
+                stats.added += added.parse::<u64>().unwrap_or(0);
+                stats.removed += removed.parse::<u64>().unwrap_or(0);
+            }
+            Some((oid.to_string(), stats))
+        })
+        .collect()
+}
+
 /// Parse `git show --name-status` output into changed-file records.
 pub fn parse_changed_files(output: &str) -> Vec<ChangedFile> {
     output
@@ -142,6 +319,26 @@ pub fn parse_changed_files(output: &str) -> Vec<ChangedFile> {
         .collect()
 }
 
+/// Parse `git config -z --get-regexp '^url\..*\.insteadof$'` output: a
+/// `url.<base>.insteadof` key, a newline and the prefix, per NUL-terminated
+/// entry.
+pub fn parse_url_rewrites(output: &str) -> Vec<UrlRewrite> {
+    output
+        .split('\0')
+        .filter_map(|entry| {
+            let (key, value) = entry.split_once('\n')?;
+            let base = key.strip_prefix("url.")?.strip_suffix(".insteadof")?;
+            if base.is_empty() || value.is_empty() {
+                return None;
+            }
+            Some(UrlRewrite {
+                base: base.to_string(),
+                instead_of: value.to_string(),
+            })
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -174,6 +371,55 @@ mod tests {
         assert!(result.is_empty());
     }
 
+    #[test]
+    fn test_parse_stash_list() {
+        let input = "stash@{0}\x1faaa111\nstash@{1}\x1fbbb222\n\n";
+        let result = parse_stash_list(input);
+        assert_eq!(
+            result,
+            vec![
+                ("stash@{0}".to_string(), "aaa111".to_string()),
+                ("stash@{1}".to_string(), "bbb222".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_upstream_tracking() {
+        let input = "refs/heads/main\x1frefs/remotes/origin/main\x1fahead 2, behind 5\n\
+                     refs/heads/local\x1f\x1f\n\
+                     refs/heads/old\x1frefs/remotes/origin/old\x1fgone\n\
+                     refs/heads/synced\x1frefs/remotes/origin/synced\x1f\n";
+        let result = parse_upstream_tracking(input);
+        assert_eq!(result.len(), 3);
+        assert_eq!(result[0].0, "refs/heads/main");
+        assert_eq!(result[0].1.upstream, "refs/remotes/origin/main");
+        assert_eq!((result[0].1.ahead, result[0].1.behind), (2, 5));
+        assert!(result[1].1.gone);
+        assert_eq!(result[2].1.divergence(), 0);
+        assert!(result[2].1.marker().is_empty());
+    }
+
+    #[test]
+    fn test_parse_url_rewrites() {
+        let input = "url.git@github.com:.insteadof\ngh:\0\
+                     url.https://mirror.example.com/a.b/.insteadof\nhttps://example.com/\0\
+                     url.empty.insteadof\n\0";
+        assert_eq!(
+            parse_url_rewrites(input),
+            vec![
+                UrlRewrite {
+                    base: "git@github.com:".to_string(),
+                    instead_of: "gh:".to_string(),
+                },
+                UrlRewrite {
+                    base: "https://mirror.example.com/a.b/".to_string(),
+                    instead_of: "https://example.com/".to_string(),
+                },
+            ]
+        );
+    }
+
     #[test]
     fn test_parse_changed_files_basic() {
         let input = "A\tnew.txt\nM\tmodified.txt\nD\tgone.txt\n";
@@ -196,13 +442,67 @@ mod tests {
         assert_eq!(result[1].old_path.as_deref(), Some("src.txt"));
     }
 
+    #[test]
+    fn test_parse_numstat() {
+        let input = "\x1eaaa\n\n3\t1\tsrc/lib.rs\n-\t-\tlogo.png\n10\t0\tREADME.md\n\x1ebbb\n\x1eccc\n\n0\t7\told.txt\n";
+        let stats = parse_numstat(input);
+        let counts: Vec<(&str, u64, u64)> = stats
+            .iter()
+            .map(|(oid, stats)| (oid.as_str(), stats.added, stats.removed))
+            .collect();
+        assert_eq!(counts, [("aaa", 13, 1), ("bbb", 0, 0), ("ccc", 0, 7)]);
+    }
+
+    #[test]
+    fn test_split_trailers() {
+        let body = "Explain the change.\n\nSigned-off-by: Ada <ada@example.com>\nChange-Id: I12\n  34\nreviewed-by: bob\n";
+        let (text, trailers) = split_trailers(body);
+        assert_eq!(text, "Explain the change.");
+        let pairs: Vec<(&str, &str)> = trailers
+            .iter()
+            .map(|trailer| (trailer.key.as_str(), trailer.value.as_str()))
+            .collect();
+        assert_eq!(
+            pairs,
+            [
+                ("Signed-off-by", "Ada <ada@example.com>"),
+                ("Change-Id", "I12 34"),
+                ("reviewed-by", "bob"),
+            ]
+        );
+
+        let (text, trailers) = split_trailers("Co-authored-by: Eve <eve@example.com>");
+        assert_eq!((text, trailers.len()), ("", 1));
+
+        // A closing paragraph with ordinary prose is not a trailer block.
+        for body in [
+            "Intro.\n\nSigned-off-by: Ada\nand some prose",
+            "Note this: it works",
+            "",
+        ] {
+            assert_eq!(split_trailers(body), (body, Vec::new()), "{:?}", body);
+        }
+    }
+
     #[test]
     fn test_parse_commit_record_with_parents() {
         let record = "abc123def456abc123def456abc1\x1f\
                       par1par1par1par1par1par1par1p par2par2par2par2par2par2par2p\x1f\
                       John Doe\x1f\
                       john@example.com\x1f\
+                      Johnny\x1f\
+                      johnny@old.example.com\x1f\
                       1700000000\x1f\
+                      2023-11-15 03:43:20 +0530\x1f\
+                      Jane Roe\x1f\
+                      jane@example.com\x1f\
+                      Jane Roe\x1f\
+                      jane@example.com\x1f\
+                      1700086400\x1f\
+                      2023-11-15 14:13:20 -0800\x1f\
+                      G\x1f\
+                      Jane Roe <jane@example.com>\x1f\
+                      ABCDEF0123456789\x1f\
                       Fix something important\x1f\
                       Detailed body";
         let commit = parse_commit_record(record).expect("should parse");
@@ -210,14 +510,29 @@ mod tests {
         assert_eq!(commit.parents.len(), 2);
         assert_eq!(commit.author, "John Doe");
         assert_eq!(commit.author_email, "john@example.com");
+        assert_eq!(commit.raw_author, "Johnny");
+        assert_eq!(commit.raw_author_email, "johnny@old.example.com");
         assert_eq!(commit.timestamp, 1700000000);
+        assert_eq!(commit.author_tz_offset, 330);
+        assert_eq!(commit.committer, "Jane Roe");
+        assert_eq!(commit.committer_email, "jane@example.com");
+        assert_eq!(commit.commit_timestamp, 1700086400);
+        assert_eq!(commit.committer_tz_offset, -480);
+        assert_eq!(
+            commit.signature,
+            Signature {
+                status: SignatureStatus::Good,
+                signer: "Jane Roe <jane@example.com>".to_string(),
+                key: "ABCDEF0123456789".to_string(),
+            }
+        );
         assert_eq!(commit.subject, "Fix something important");
         assert_eq!(commit.body, "Detailed body");
     }
 
     #[test]
     fn test_parse_commit_record_no_parents() {
-        let record = "abc123def456abc123def456abc1\x1f\x1fJane Doe\x1fjane@example.com\x1f1700000001\x1fInitial commit\x1f";
+        let record = "abc123def456abc123def456abc1\x1f\x1fJane Doe\x1fjane@example.com\x1fJane Doe\x1fjane@example.com\x1f1700000001\x1f2023-11-14 22:13:20 +0000\x1fCommitter\x1fc@example.com\x1fCommitter\x1fc@example.com\x1f1700000001\x1f2023-11-14 22:13:20 +0000\x1fN\x1f\x1f\x1fInitial commit\x1f";
         let commit = parse_commit_record(record).expect("should parse");
         assert_eq!(commit.oid, "abc123def456abc123def456abc1");
         assert!(commit.parents.is_empty());
@@ -227,8 +542,8 @@ mod tests {
 
     #[test]
     fn test_parse_commits_multiple() {
-        let record1 = "aaa\x1f\x1fAuth1\x1fa@b.com\x1f1000\x1fFirst\x1fBody one";
-        let record2 = "bbb\x1faaa\x1fAuth2\x1fb@c.com\x1f999\x1fSecond\x1fBody two";
+        let record1 = "aaa\x1f\x1fAuth1\x1fa@b.com\x1fAuth1\x1fa@b.com\x1f1000\x1f2023-11-14 22:13:20 +0000\x1fCommitter\x1fc@example.com\x1fCommitter\x1fc@example.com\x1f1000\x1f2023-11-14 22:13:20 +0000\x1fN\x1f\x1f\x1fFirst\x1fBody one";
+        let record2 = "bbb\x1faaa\x1fAuth2\x1fb@c.com\x1fAuth2\x1fb@c.com\x1f999\x1f2023-11-14 22:13:20 +0000\x1fCommitter\x1fc@example.com\x1fCommitter\x1fc@example.com\x1f999\x1f2023-11-14 22:13:20 +0000\x1fN\x1f\x1f\x1fSecond\x1fBody two";
         let input = format!("{}\x1e{}\x1e", record1, record2);
         let report = parse_commits(&input);
         assert_eq!(report.total_records, 2);
@@ -243,7 +558,7 @@ mod tests {
 
     #[test]
     fn test_parse_commits_invalid_timestamp_is_rejected() {
-        let bad = "bbb\x1faaa\x1fAuth2\x1fb@c.com\x1fnot-a-number\x1fSecond\x1fBody";
+        let bad = "bbb\x1faaa\x1fAuth2\x1fb@c.com\x1fAuth2\x1fb@c.com\x1fnot-a-number\x1f2023-11-14 22:13:20 +0000\x1fCommitter\x1fc@example.com\x1fCommitter\x1fc@example.com\x1fnot-a-number\x1f2023-11-14 22:13:20 +0000\x1fN\x1f\x1f\x1fSecond\x1fBody";
         let report = parse_commits(&format!("{}\x1e", bad));
         assert_eq!(report.total_records, 1);
         assert_eq!(report.rejected_records, 1);
@@ -257,10 +572,22 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_parse_commits_invalid_timezone_is_rejected() {
+        let bad = "aaa\x1f\x1fAuth1\x1fa@b.com\x1fAuth1\x1fa@b.com\x1f1000\x1f1970-01-01 00:16:40\x1fC\x1fc@example.com\x1fC\x1fc@example.com\x1f1000\x1f1970-01-01 00:16:40 +0000\x1fN\x1f\x1f\x1fFirst\x1fBody";
+        let report = parse_commits(&format!("{}\x1e", bad));
+        assert_eq!(report.rejected_records, 1);
+        assert!(report
+            .first_error
+            .as_deref()
+            .unwrap_or("")
+            .contains("invalid timezone in date '1970-01-01 00:16:40'"));
+    }
+
     #[test]
     fn test_parse_commits_mixed_valid_and_invalid() {
-        let good = "aaa\x1f\x1fAuth1\x1fa@b.com\x1f1000\x1fFirst\x1fBody";
-        let bad = "bbb\x1faaa\x1fAuth2\x1fb@c.com\x1fnot-a-number\x1fSecond\x1fBody";
+        let good = "aaa\x1f\x1fAuth1\x1fa@b.com\x1fAuth1\x1fa@b.com\x1f1000\x1f2023-11-14 22:13:20 +0000\x1fCommitter\x1fc@example.com\x1fCommitter\x1fc@example.com\x1f1000\x1f2023-11-14 22:13:20 +0000\x1fN\x1f\x1f\x1fFirst\x1fBody";
+        let bad = "bbb\x1faaa\x1fAuth2\x1fb@c.com\x1fAuth2\x1fb@c.com\x1fnot-a-number\x1f2023-11-14 22:13:20 +0000\x1fCommitter\x1fc@example.com\x1fCommitter\x1fc@example.com\x1fnot-a-number\x1f2023-11-14 22:13:20 +0000\x1fN\x1f\x1f\x1fSecond\x1fBody";
         let report = parse_commits(&format!("{}\x1e{}\x1e", good, bad));
         assert_eq!(report.total_records, 2);
         assert_eq!(report.rejected_records, 1);
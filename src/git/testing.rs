@@ -0,0 +1,116 @@
+//! Throwaway repositories for tests that need real `git` history.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub struct TempRepo {
+    path: PathBuf,
+}
+
+impl TempRepo {
+    pub fn new() -> Self {
+        let mut path = std::env::temp_dir();
+        let unique = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("system time before unix epoch")
+            .as_nanos();
+        path.push(format!("gitviz-test-{}-{}", std::process::id(), unique));
+        fs::create_dir_all(&path).expect("failed to create temp repo dir");
+
+        run_git(&path, &["init", "-b", "main"]);
+        run_git(&path, &["config", "user.name", "Gitviz Test"]);
+        run_git(&path, &["config", "user.email", "gitviz@example.com"]);
+
+        Self { path }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn commit_file(&self, name: &str, contents: &str, message: &str) -> String {
+        fs::write(self.path.join(name), contents).expect("failed to write test file");
+        run_git(self.path(), &["add", name]);
+        run_git(self.path(), &["commit", "-m", message]);
+        run_git(self.path(), &["rev-parse", "HEAD"])
+            .trim()
+            .to_string()
+    }
+
+    pub fn commit_file_with_body(
+        &self,
+        name: &str,
+        contents: &str,
+        subject: &str,
+        body: &str,
+    ) -> String {
+        fs::write(self.path.join(name), contents).expect("failed to write test file");
+        run_git(self.path(), &["add", name]);
+        run_git(self.path(), &["commit", "-m", subject, "-m", body]);
+        run_git(self.path(), &["rev-parse", "HEAD"])
+            .trim()
+            .to_string()
+    }
+}
+
+impl TempRepo {
+    /// Sign commits with a fresh SSH key trusted through an allowed signers
+    /// file inside `.git`, so `git log` reports them as good. Returns the
+    /// key's path, or `None` when `ssh-keygen` is not installed.
+    pub fn enable_ssh_signing(&self) -> Option<PathBuf> {
+        let key = self.generate_ssh_key("signing_key")?;
+        let public = fs::read_to_string(key.with_extension("pub")).ok()?;
+        let allowed = self.path.join(".git").join("allowed_signers");
+        fs::write(&allowed, format!("gitviz@example.com {}", public)).ok()?;
+
+        run_git(self.path(), &["config", "gpg.format", "ssh"]);
+        run_git(
+            self.path(),
+            &["config", "gpg.ssh.allowedSignersFile", path_str(&allowed)],
+        );
+        run_git(self.path(), &["config", "user.signingkey", path_str(&key)]);
+        Some(key)
+    }
+
+    /// A new passphrase-less ed25519 key in `.git`, or `None` when
+    /// `ssh-keygen` is not installed.
+    pub fn generate_ssh_key(&self, name: &str) -> Option<PathBuf> {
+        let key = self.path.join(".git").join(name);
+        let status = Command::new("ssh-keygen")
+            .args(["-q", "-t", "ed25519", "-N", "", "-C", name, "-f"])
+            .arg(&key)
+            .status()
+            .ok()?;
+        status.success().then_some(key)
+    }
+}
+
+fn path_str(path: &Path) -> &str {
+    path.to_str().expect("temp paths are UTF-8")
+}
+
+impl Drop for TempRepo {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
+
+pub fn run_git(repo: &Path, args: &[&str]) -> String {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args(args)
+        .output()
+        .expect("failed to run git command");
+
+    assert!(
+        output.status.success(),
+        "git {:?} failed: {}",
+        args,
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    String::from_utf8(output.stdout).expect("git output was not valid UTF-8")
+}
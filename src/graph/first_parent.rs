@@ -0,0 +1,152 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::git::model::Commit;
+
+/// The part of a history a first-parent view shows.
+pub(super) struct FirstParentView {
+    /// Shown commits in their original order, with parents rewritten to the
+    /// nearest shown commit so lines join up across hidden ones.
+    pub commits: Vec<Commit>,
+    /// Index in the full history of each shown commit.
+    pub positions: Vec<usize>,
+    /// Merges whose side parents are hidden.
+    pub collapsed: HashSet<String>,
+}
+
+/// Follow first parents from every branch tip and every commit nothing
+/// points at, like `git log --first-parent`. An expanded merge also shows
+/// the commits it brought in: the first-parent chains of its other parents,
+/// up to where they meet the history of its first parent.
+pub(super) fn first_parent_view(
+    commits: &[Commit],
+    tips: &HashMap<String, String>,
+    expanded: &HashSet<String>,
+) -> FirstParentView {
+    let index: HashMap<&str, usize> = commits
+        .iter()
+        .enumerate()
+        .map(|(i, commit)| (commit.oid.as_str(), i))
+        .collect();
+
+    let mut has_child = vec![false; commits.len()];
+    for commit in commits {
+        for parent in &commit.parents {
+            if let Some(&i) = index.get(parent.as_str()) {
+                has_child[i] = true;
+            }
+        }
+    }
+
+    let mut walk = Walk {
+        commits,
+        index: &index,
+        expanded,
+        shown: vec![false; commits.len()],
+        pending_merges: Vec::new(),
+    };
+    for (i, commit) in commits.iter().enumerate() {
+        if !has_child[i] || tips.contains_key(&commit.oid) {
+            walk.follow(i, &HashSet::new());
+        }
+    }
+    while let Some(merge) = walk.pending_merges.pop() {
+        let parents = &commits[merge].parents;
+        let base = parents
+            .first()
+            .map(|parent| ancestors(commits, &index, parent))
+            .unwrap_or_default();
+        for parent in &parents[1..] {
+            if let Some(&i) = index.get(parent.as_str()) {
+                walk.follow(i, &base);
+            }
+        }
+    }
+
+    let shown = walk.shown;
+    let nearest_shown = |oid: &str| -> String {
+        let mut current = index.get(oid).copied();
+        while let Some(i) = current {
+            if shown[i] {
+                return commits[i].oid.clone();
+            }
+            current = first_parent(commits, &index, i);
+        }
+        oid.to_string()
+    };
+
+    let mut view = FirstParentView {
+        commits: Vec::new(),
+        positions: Vec::new(),
+        collapsed: HashSet::new(),
+    };
+    for (i, commit) in commits.iter().enumerate().filter(|&(i, _)| shown[i]) {
+        let collapsed = commit.parents.len() > 1 && !expanded.contains(&commit.oid);
+        let kept = if collapsed { 1 } else { commit.parents.len() };
+        let mut parents: Vec<String> = Vec::with_capacity(kept);
+        for parent in commit.parents.iter().take(kept) {
+            let parent = nearest_shown(parent);
+            if !parents.contains(&parent) {
+                parents.push(parent);
+            }
+        }
+        if collapsed {
+            view.collapsed.insert(commit.oid.clone());
+        }
+        view.commits.push(Commit {
+            parents,
+            ..commit.clone()
+        });
+        view.positions.push(i);
+    }
+    view
+}
+
+struct Walk<'a> {
+    commits: &'a [Commit],
+    index: &'a HashMap<&'a str, usize>,
+    expanded: &'a HashSet<String>,
+    shown: Vec<bool>,
+    /// Shown expanded merges whose side parents are still to be walked.
+    pending_merges: Vec<usize>,
+}
+
+impl Walk<'_> {
+    /// Show `start` and its first parents until reaching a commit already
+    /// shown, one in `stop`, or the end of the loaded history.
+    fn follow(&mut self, start: usize, stop: &HashSet<usize>) {
+        let mut current = Some(start);
+        while let Some(i) = current {
+            if self.shown[i] || stop.contains(&i) {
+                break;
+            }
+            self.shown[i] = true;
+            let commit = &self.commits[i];
+            if commit.parents.len() > 1 && self.expanded.contains(&commit.oid) {
+                self.pending_merges.push(i);
+            }
+            current = first_parent(self.commits, self.index, i);
+        }
+    }
+}
+
+fn first_parent(commits: &[Commit], index: &HashMap<&str, usize>, i: usize) -> Option<usize> {
+    let parent = commits[i].parents.first()?;
+    index.get(parent.as_str()).copied()
+}
+
+/// Indices of `oid` and every loaded commit reachable from it.
+fn ancestors(commits: &[Commit], index: &HashMap<&str, usize>, oid: &str) -> HashSet<usize> {
+    let mut seen = HashSet::new();
+    let mut stack: Vec<usize> = index.get(oid).copied().into_iter().collect();
+    while let Some(i) = stack.pop() {
+        if seen.insert(i) {
+            stack.extend(
+                commits[i]
+                    .parents
+                    .iter()
+                    .filter_map(|parent| index.get(parent.as_str()).copied()),
+            );
+        }
+    }
+    seen
+}
@@ -1,117 +1,564 @@
-use crate::git::model::Commit;
+use std::collections::{HashMap, HashSet};
 
-#[allow(dead_code)]
+use super::first_parent::first_parent_view;
+use crate::git::model::{Commit, TRUNK_BRANCHES};
+
+/// Colour key of the trunk line; themes reserve their first lane colour for it.
+pub const TRUNK_COLOR: u64 = 0;
+
+/// A line that has run for this many rows keeps its column instead of
+/// closing up gaps to its left, so long-lived branches stay put.
+const STABLE_LANE_ROWS: usize = 20;
+
+/// One column of a graph row. Corners are named by the two edges their
+/// strokes leave through.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum GraphCell {
+    /// Nothing drawn.
     Empty,
+    /// A line passing straight down (`│`).
     Vertical,
+    /// A line passing across (`─`).
     Horizontal,
+    /// A commit with at most one parent (`●`).
     Commit,
+    /// A commit with several parents (`◎`).
     MergeCommit,
+    /// `╯`
     CornerUpLeft,
+    /// `╰`
     CornerUpRight,
+    /// `╮`
     CornerDownLeft,
+    /// `╭`
     CornerDownRight,
+    /// A line joining a vertical from the left (`┤`).
+    TeeLeft,
+    /// A line joining a vertical from the right (`├`).
+    TeeRight,
+    /// A horizontal with a line leaving downwards (`┬`).
+    TeeDown,
+    /// A horizontal with a line arriving from above (`┴`).
+    TeeUp,
+    /// A horizontal crossing a vertical (`┼`).
+    Crossing,
+    /// Stands in for lines beyond the maximum lane count (`…`).
+    Overflow,
+    /// A merge drawn without the commits it brought in (`⊕`).
+    CollapsedMerge,
+}
+
+const UP: u8 = 1;
+const DOWN: u8 = 2;
+const LEFT: u8 = 4;
+const RIGHT: u8 = 8;
+
+impl GraphCell {
+    fn from_directions(directions: u8) -> Self {
+        let has = |direction| directions & direction != 0;
+        GraphCell::from_strokes(has(UP), has(DOWN), has(LEFT), has(RIGHT))
+    }
+
+    /// The glyph joining strokes that leave the cell in the given directions.
+    pub fn from_strokes(up: bool, down: bool, left: bool, right: bool) -> Self {
+        match (up, down, left, right) {
+            (false, false, false, false) => GraphCell::Empty,
+            (_, _, false, false) => GraphCell::Vertical,
+            (false, false, _, _) => GraphCell::Horizontal,
+            (true, false, true, false) => GraphCell::CornerUpLeft,
+            (true, false, false, true) => GraphCell::CornerUpRight,
+            (false, true, true, false) => GraphCell::CornerDownLeft,
+            (false, true, false, true) => GraphCell::CornerDownRight,
+            (true, true, true, false) => GraphCell::TeeLeft,
+            (true, true, false, true) => GraphCell::TeeRight,
+            (false, true, true, true) => GraphCell::TeeDown,
+            (true, false, true, true) => GraphCell::TeeUp,
+            (true, true, true, true) => GraphCell::Crossing,
+        }
+    }
+
+    /// Whether a stroke leaves the cell's top edge.
+    pub fn connects_up(self) -> bool {
+        matches!(
+            self,
+            GraphCell::Vertical
+                | GraphCell::CornerUpLeft
+                | GraphCell::CornerUpRight
+                | GraphCell::TeeLeft
+                | GraphCell::TeeRight
+                | GraphCell::TeeUp
+                | GraphCell::Crossing
+        )
+    }
+
+    /// Whether a stroke leaves the cell's left edge.
+    pub fn connects_left(self) -> bool {
+        matches!(
+            self,
+            GraphCell::Horizontal
+                | GraphCell::CornerUpLeft
+                | GraphCell::CornerDownLeft
+                | GraphCell::TeeLeft
+                | GraphCell::TeeDown
+                | GraphCell::TeeUp
+                | GraphCell::Crossing
+        )
+    }
+
+    /// Whether a stroke leaves the cell's right edge.
+    pub fn connects_right(self) -> bool {
+        matches!(
+            self,
+            GraphCell::Horizontal
+                | GraphCell::CornerUpRight
+                | GraphCell::CornerDownRight
+                | GraphCell::TeeRight
+                | GraphCell::TeeDown
+                | GraphCell::TeeUp
+                | GraphCell::Crossing
+        )
+    }
+
+    /// Whether the cell is the row's commit.
+    pub fn is_commit(self) -> bool {
+        matches!(
+            self,
+            GraphCell::Commit | GraphCell::MergeCommit | GraphCell::CollapsedMerge
+        )
+    }
 }
 
+/// Layout of one commit's row: the commit's column and what every column
+/// draws. Trailing empty columns are left out.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct GraphRow {
+    /// Column of the commit.
     pub commit_lane: usize,
+    /// What each column draws, left to right.
     pub cells: Vec<GraphCell>,
+    /// Colour key of the branch line each cell belongs to, parallel to `cells`.
+    pub colors: Vec<u64>,
+    /// Whether the commit's own column carries on to the row below.
+    pub continues_below: bool,
+    /// The commit is left out of a first-parent view and not drawn.
+    pub hidden: bool,
 }
 
-pub fn compute_layout(commits: &[Commit]) -> Vec<GraphRow> {
-    let mut lanes: Vec<Option<String>> = Vec::new();
+impl GraphRow {
+    fn hidden() -> Self {
+        GraphRow {
+            commit_lane: 0,
+            cells: Vec::new(),
+            colors: Vec::new(),
+            continues_below: false,
+            hidden: true,
+        }
+    }
+}
+
+/// Settings for [`compute_layout`]. The default draws every lane of the full
+/// history with colours chosen from merge subjects alone.
+#[derive(Debug, Default, Clone)]
+pub struct LayoutOptions {
+    /// Branch name at each branch tip (oid -> name), used to colour the line
+    /// running down from it.
+    pub branch_names: HashMap<String, String>,
+    /// Columns drawn before the rest collapse into one overflow column;
+    /// `None` draws every lane.
+    pub max_lanes: Option<usize>,
+    /// Show only first-parent history, like `git log --first-parent`.
+    pub first_parent: bool,
+    /// Merges (by oid) that a first-parent view shows with the commits they
+    /// brought in.
+    pub expanded_merges: HashSet<String>,
+}
+
+/// Colour key for a branch line. Keys are stable across reloads because they
+/// depend only on the name; the trunk always gets [`TRUNK_COLOR`].
+pub fn color_for_name(name: &str) -> u64 {
+    if TRUNK_BRANCHES.contains(&name) {
+        TRUNK_COLOR
+    } else {
+        fnv1a(name).max(1)
+    }
+}
+
+/// A column of the graph: the commit it is heading for and its line colour.
+#[derive(Debug, Clone)]
+struct Lane {
+    oid: String,
+    color: u64,
+    /// Row the line started on.
+    since: usize,
+}
+
+/// Strokes drawn in one row, collected per column before picking glyphs.
+struct RowStrokes {
+    directions: Vec<u8>,
+    colors: Vec<u64>,
+}
+
+impl RowStrokes {
+    fn new(width: usize) -> Self {
+        RowStrokes {
+            directions: vec![0; width],
+            colors: vec![TRUNK_COLOR; width],
+        }
+    }
+
+    fn mark(&mut self, column: usize, direction: u8, color: u64) {
+        self.directions[column] |= direction;
+        self.colors[column] = color;
+    }
+
+    /// A horizontal stroke between two columns, ending in each of them.
+    fn link(&mut self, from: usize, to: usize, color: u64) {
+        let (low, high) = (from.min(to), from.max(to));
+        if low == high {
+            return;
+        }
+        self.mark(low, RIGHT, color);
+        for column in (low + 1)..high {
+            self.mark(column, LEFT | RIGHT, color);
+        }
+        self.mark(high, LEFT, color);
+    }
+}
+
+/// Assign every commit a column and work out the connectors drawn on its row.
+///
+/// Each row shows the lines arriving from the row above, the commit, and the
+/// lines leaving toward the rows below: a child's line that ends in a commit
+/// already drawn elsewhere forks off it (`├─●`), merge parents fan out to
+/// their columns (`●─┬─╮`), and a line moves left into a free column when
+/// nothing else is drawn between (`╭─╯`).
+///
+/// New lines take the free column nearest the line they join, and lines
+/// that have run for 20 rows no longer move. Past
+/// [`LayoutOptions::max_lanes`] the remaining columns collapse into a single
+/// overflow column, which shows the commit if it is drawn there.
+///
+/// With [`LayoutOptions::first_parent`] set, commits outside the first-parent
+/// history get hidden rows, collapsed merges draw without their side lines,
+/// and lines run straight past the hidden commits to the next shown one.
+pub fn compute_layout(commits: &[Commit], options: &LayoutOptions) -> Vec<GraphRow> {
+    if !options.first_parent {
+        return layout_rows(commits, options, &HashSet::new());
+    }
+
+    let view = first_parent_view(commits, &options.branch_names, &options.expanded_merges);
+    let rows = layout_rows(&view.commits, options, &view.collapsed);
+    let mut result = vec![GraphRow::hidden(); commits.len()];
+    for (position, row) in view.positions.into_iter().zip(rows) {
+        result[position] = row;
+    }
+    result
+}
+
+fn layout_rows(
+    commits: &[Commit],
+    options: &LayoutOptions,
+    collapsed: &HashSet<String>,
+) -> Vec<GraphRow> {
+    let mut lanes: Vec<Option<Lane>> = Vec::new();
     let mut result = Vec::with_capacity(commits.len());
 
-    for commit in commits {
-        let commit_lane = lanes
+    for (row, commit) in commits.iter().enumerate() {
+        let oid = commit.oid.as_str();
+        let tracking: Vec<usize> = (0..lanes.len())
+            .filter(|&column| lane_oid(&lanes, column) == Some(oid))
+            .collect();
+        // A new tip whose parent is already drawn opens just right of it,
+        // so the fork connector stays short.
+        let commit_lane = tracking.first().copied().unwrap_or_else(|| {
+            let parent_column = commit
+                .parents
+                .first()
+                .and_then(|parent| find_lane(&lanes, parent));
+            let start = parent_column.map_or(0, |column| column + 1);
+            (start..lanes.len())
+                .find(|&column| lanes[column].is_none())
+                .unwrap_or(lanes.len())
+        });
+        let since = tracking
+            .iter()
+            .filter_map(|&column| lanes[column].as_ref())
+            .map(|lane| lane.since)
+            .min()
+            .unwrap_or(row);
+
+        // A named tip recolours its line from here down; an unnamed new tip
+        // is keyed by its own oid so the colour survives reloads. Where a
+        // branch line meets the trunk, the commit is the trunk's.
+        let mut color = match tracking.first() {
+            Some(&column) => lane_color(&lanes, column),
+            None => fnv1a(oid).max(1),
+        };
+        if tracking
             .iter()
-            .position(|lane| lane.as_deref() == Some(commit.oid.as_str()))
-            .unwrap_or_else(|| allocate_lane(&mut lanes));
+            .any(|&column| lane_color(&lanes, column) == TRUNK_COLOR)
+        {
+            color = TRUNK_COLOR;
+        }
+        if let Some(name) = options.branch_names.get(oid) {
+            color = color_for_name(name);
+        }
 
-        if commit_lane >= lanes.len() {
-            lanes.resize(commit_lane + 1, None);
+        let before = lanes;
+        let mut after = before.clone();
+        for &column in &tracking {
+            after[column] = None;
+        }
+        if after.len() <= commit_lane {
+            after.resize(commit_lane + 1, None);
         }
 
-        let active_before = snapshot_active(&lanes, commit_lane);
+        // Columns whose line stops on this row.
+        let mut ended = tracking.clone();
+        let mut strokes = RowStrokes::new(before.len().max(after.len()) + commit.parents.len());
+        let mut span = (commit_lane, commit_lane);
+        let mut widen = |column: usize| span = (span.0.min(column), span.1.max(column));
 
-        lanes[commit_lane] = commit.parents.first().cloned();
+        // Other lines that were heading for this commit end here.
+        for &column in tracking.iter().skip(1) {
+            strokes.mark(column, UP, lane_color(&before, column));
+            strokes.link(column, commit_lane, lane_color(&before, column));
+            widen(column);
+        }
 
-        let mut extra_parent_lanes = Vec::new();
-        for parent_oid in commit.parents.iter().skip(1) {
-            let lane = lanes
-                .iter()
-                .position(|tracked| tracked.as_deref() == Some(parent_oid.as_str()))
-                .unwrap_or_else(|| {
-                    let lane = allocate_lane(&mut lanes);
-                    lanes[lane] = Some(parent_oid.clone());
-                    lane
-                });
-            extra_parent_lanes.push(lane);
-        }
-
-        let cols = lanes.len().max(commit_lane + 1);
-        let mut cells = vec![GraphCell::Empty; cols];
-        for (lane, is_active) in active_before.iter().copied().enumerate() {
-            if is_active {
-                cells[lane] = GraphCell::Vertical;
+        if let Some(parent) = commit.parents.first() {
+            match find_lane(&after, parent) {
+                // The parent is already drawn further left: fork off its line.
+                Some(column) if column < commit_lane => {
+                    strokes.link(commit_lane, column, color);
+                    if color == TRUNK_COLOR {
+                        if let Some(lane) = after[column].as_mut() {
+                            lane.color = TRUNK_COLOR;
+                        }
+                    }
+                    widen(column);
+                }
+                // Further right: that line folds into this one instead.
+                Some(column) => {
+                    let joining = lane_color(&after, column);
+                    let joining_since = after[column].take().map_or(since, |lane| lane.since);
+                    ended.push(column);
+                    after[commit_lane] = Some(Lane {
+                        oid: parent.clone(),
+                        color: if joining == TRUNK_COLOR {
+                            TRUNK_COLOR
+                        } else {
+                            color
+                        },
+                        since: since.min(joining_since),
+                    });
+                    strokes.mark(column, UP, joining);
+                    strokes.link(column, commit_lane, joining);
+                    strokes.mark(commit_lane, DOWN, color);
+                    widen(column);
+                }
+                None => {
+                    after[commit_lane] = Some(Lane {
+                        oid: parent.clone(),
+                        color,
+                        since,
+                    });
+                    strokes.mark(commit_lane, DOWN, color);
+                }
             }
         }
 
-        cells[commit_lane] = if commit.parents.len() > 1 {
-            GraphCell::MergeCommit
-        } else {
-            GraphCell::Commit
-        };
-
-        for parent_lane in extra_parent_lanes {
-            if parent_lane > commit_lane {
-                for lane in (commit_lane + 1)..parent_lane {
-                    cells[lane] = GraphCell::Horizontal;
+        for (idx, parent) in commit.parents.iter().enumerate().skip(1) {
+            let column = match find_lane(&after, parent) {
+                Some(column) => column,
+                None => {
+                    let column = free_lane(&before, &after, commit_lane);
+                    if after.len() <= column {
+                        after.resize(column + 1, None);
+                    }
+                    let parent_color = match merged_branch_name(&commit.subject) {
+                        Some(name) if idx == 1 => color_for_name(name),
+                        _ => fnv1a(parent).max(1),
+                    };
+                    after[column] = Some(Lane {
+                        oid: parent.clone(),
+                        color: parent_color,
+                        since: row,
+                    });
+                    column
                 }
-                cells[parent_lane] = GraphCell::CornerDownLeft;
-            } else if parent_lane < commit_lane {
-                for lane in (parent_lane + 1)..commit_lane {
-                    cells[lane] = GraphCell::Horizontal;
+            };
+            // Connectors take the colour of the line they lead into.
+            let parent_color = lane_color(&after, column);
+            strokes.mark(column, DOWN, parent_color);
+            strokes.link(commit_lane, column, parent_color);
+            widen(column);
+        }
+
+        // Lines passing this row, shifted left into free columns where the
+        // move does not cross anything else drawn on the row. Marked last so
+        // a crossing keeps the colour of the vertical line.
+        for column in 0..before.len() {
+            let Some(lane) = before[column].as_ref().filter(|_| !ended.contains(&column)) else {
+                continue;
+            };
+            let settled = row - lane.since >= STABLE_LANE_ROWS;
+            let mut target = column;
+            if !settled && !(span.0..=span.1).contains(&column) {
+                while target > 0
+                    && before[target - 1].is_none()
+                    && after[target - 1].is_none()
+                    && !(span.0..=span.1).contains(&(target - 1))
+                {
+                    target -= 1;
                 }
-                cells[parent_lane] = GraphCell::CornerDownRight;
             }
+            let lane_color = lane_color(&before, column);
+            if target != column {
+                after[target] = after[column].take();
+                strokes.link(column, target, lane_color);
+            }
+            strokes.mark(column, UP, lane_color);
+            strokes.mark(target, DOWN, lane_color);
         }
 
+        let mut cells: Vec<GraphCell> = strokes
+            .directions
+            .iter()
+            .map(|&directions| GraphCell::from_directions(directions))
+            .collect();
+        let mut colors = strokes.colors;
+        cells[commit_lane] = if collapsed.contains(&commit.oid) {
+            GraphCell::CollapsedMerge
+        } else if commit.parents.len() > 1 {
+            GraphCell::MergeCommit
+        } else {
+            GraphCell::Commit
+        };
+        colors[commit_lane] = color;
+        let continues_below = after.get(commit_lane).is_some_and(Option::is_some);
+
         while matches!(cells.last(), Some(GraphCell::Empty)) {
             cells.pop();
         }
-        while lanes.last() == Some(&None) {
-            lanes.pop();
+        colors.truncate(cells.len());
+        let mut commit_lane = commit_lane;
+        if let Some(max_lanes) = options.max_lanes {
+            collapse_overflow(&mut cells, &mut colors, &mut commit_lane, max_lanes);
+        }
+        while matches!(after.last(), Some(None)) {
+            after.pop();
         }
+        lanes = after;
 
-        result.push(GraphRow { commit_lane, cells });
+        result.push(GraphRow {
+            commit_lane,
+            cells,
+            colors,
+            continues_below,
+            hidden: false,
+        });
     }
 
     result
 }
 
-fn snapshot_active(lanes: &[Option<String>], commit_lane: usize) -> Vec<bool> {
-    let mut active = Vec::with_capacity(lanes.len());
-    for (i, lane) in lanes.iter().enumerate() {
-        active.push(i == commit_lane || lane.is_some());
+/// Replace every column from `max_lanes` on with one overflow column holding
+/// the commit, if it was drawn there, or an overflow marker.
+fn collapse_overflow(
+    cells: &mut Vec<GraphCell>,
+    colors: &mut Vec<u64>,
+    commit_lane: &mut usize,
+    max_lanes: usize,
+) {
+    if cells.len() <= max_lanes {
+        return;
     }
-    active
+    let (cell, color) = if *commit_lane >= max_lanes {
+        (cells[*commit_lane], colors[*commit_lane])
+    } else {
+        let first = (max_lanes..cells.len())
+            .find(|&column| cells[column] != GraphCell::Empty)
+            .unwrap_or(max_lanes);
+        (GraphCell::Overflow, colors[first])
+    };
+    cells.truncate(max_lanes);
+    colors.truncate(max_lanes);
+    cells.push(cell);
+    colors.push(color);
+    *commit_lane = (*commit_lane).min(max_lanes);
+}
+
+fn lane_oid(lanes: &[Option<Lane>], column: usize) -> Option<&str> {
+    lanes.get(column)?.as_ref().map(|lane| lane.oid.as_str())
 }
 
-fn allocate_lane(lanes: &mut Vec<Option<String>>) -> usize {
-    if let Some(pos) = lanes.iter().position(|lane| lane.is_none()) {
-        pos
+fn lane_color(lanes: &[Option<Lane>], column: usize) -> u64 {
+    lanes
+        .get(column)
+        .and_then(Option::as_ref)
+        .map(|lane| lane.color)
+        .unwrap_or(TRUNK_COLOR)
+}
+
+fn find_lane(lanes: &[Option<Lane>], oid: &str) -> Option<usize> {
+    (0..lanes.len()).find(|&column| lane_oid(lanes, column) == Some(oid))
+}
+
+/// Column nearest the commit that is free both above and below this row, so
+/// a new line never starts where another one just ended. Ties go right,
+/// where merge connectors fan out.
+fn free_lane(before: &[Option<Lane>], after: &[Option<Lane>], commit_lane: usize) -> usize {
+    let width = before.len().max(after.len());
+    (0..=width)
+        .filter(|&column| {
+            column != commit_lane
+                && before.get(column).is_none_or(Option::is_none)
+                && after.get(column).is_none_or(Option::is_none)
+        })
+        .min_by_key(|&column| (column.abs_diff(commit_lane), column < commit_lane))
+        .unwrap_or(width + 1)
+}
+
+/// Branch named in a merge subject such as `Merge branch 'topic'` or
+/// `Merge pull request #12 from owner/topic`, without any remote prefix.
+fn merged_branch_name(subject: &str) -> Option<&str> {
+    if let Some(rest) = subject.strip_prefix("Merge pull request ") {
+        let (_, source) = rest.split_once(" from ")?;
+        let source = source.split_whitespace().next()?;
+        return source.split_once('/').map(|(_, branch)| branch);
+    }
+    let rest = subject
+        .strip_prefix("Merge branch '")
+        .map(|rest| (rest, false))
+        .or_else(|| {
+            subject
+                .strip_prefix("Merge remote-tracking branch '")
+                .map(|rest| (rest, true))
+        });
+    let (rest, remote) = rest?;
+    let (name, _) = rest.split_once('\'')?;
+    if remote {
+        name.split_once('/').map(|(_, branch)| branch)
     } else {
-        lanes.push(None);
-        lanes.len() - 1
+        Some(name)
     }
 }
 
+/// 64-bit FNV-1a; unlike `DefaultHasher` its output is fixed across releases.
+fn fnv1a(text: &str) -> u64 {
+    text.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::git::load_commits;
+    use crate::git::testing::{run_git, TempRepo};
+    use crate::graph::render::graph_prefix;
+    use std::collections::BTreeSet;
 
     fn make_commit(oid: &str, parents: &[&str]) -> Commit {
         Commit {
@@ -122,6 +569,21 @@ mod tests {
             timestamp: 0,
             subject: "test".to_string(),
             body: String::new(),
+            ..Default::default()
+        }
+    }
+
+    fn commit_color(row: &GraphRow) -> u64 {
+        row.colors[row.commit_lane]
+    }
+
+    fn named(tips: &[(&str, &str)]) -> LayoutOptions {
+        LayoutOptions {
+            branch_names: tips
+                .iter()
+                .map(|(oid, name)| (oid.to_string(), name.to_string()))
+                .collect(),
+            ..LayoutOptions::default()
         }
     }
 
@@ -132,7 +594,7 @@ mod tests {
             make_commit("b", &["a"]),
             make_commit("a", &[]),
         ];
-        let rows = compute_layout(&commits);
+        let rows = compute_layout(&commits, &LayoutOptions::default());
         assert!(rows.iter().all(|row| row.commit_lane == 0));
         assert_eq!(rows[0].cells, vec![GraphCell::Commit]);
     }
@@ -145,7 +607,7 @@ mod tests {
             make_commit("B", &["root"]),
             make_commit("root", &[]),
         ];
-        let rows = compute_layout(&commits);
+        let rows = compute_layout(&commits, &LayoutOptions::default());
         assert_eq!(
             rows[0].cells,
             vec![
@@ -154,4 +616,367 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    fn test_trunk_keeps_its_colour_when_lanes_shift() {
+        // `main` sits in lane 0 in the first history and lane 1 in the second.
+        let first = vec![
+            make_commit("m2", &["m1"]),
+            make_commit("f1", &["m1"]),
+            make_commit("m1", &[]),
+        ];
+        let second = vec![
+            make_commit("f2", &["f1"]),
+            make_commit("m2", &["m1"]),
+            make_commit("f1", &["m1"]),
+            make_commit("m1", &[]),
+        ];
+
+        let rows = compute_layout(&first, &named(&[("m2", "main"), ("f1", "feature")]));
+        assert_eq!(commit_color(&rows[0]), TRUNK_COLOR);
+        assert_eq!(commit_color(&rows[1]), color_for_name("feature"));
+
+        let rows = compute_layout(&second, &named(&[("m2", "main"), ("f2", "feature")]));
+        assert_eq!(rows[1].commit_lane, 1);
+        assert_eq!(commit_color(&rows[1]), TRUNK_COLOR);
+        assert_eq!(commit_color(&rows[0]), color_for_name("feature"));
+        // The feature line keeps its colour down its first-parent chain
+        // until it meets the trunk.
+        assert_eq!(commit_color(&rows[2]), color_for_name("feature"));
+        assert_eq!(commit_color(&rows[3]), TRUNK_COLOR);
+    }
+
+    #[test]
+    fn test_merged_branch_colour_comes_from_merge_subject() {
+        let mut merge = make_commit("M", &["A", "B"]);
+        merge.subject = "Merge branch 'topic' into main".to_string();
+        let commits = vec![
+            merge,
+            make_commit("A", &["root"]),
+            make_commit("B", &["root"]),
+            make_commit("root", &[]),
+        ];
+        let rows = compute_layout(&commits, &named(&[("M", "main")]));
+        assert_eq!(rows[0].colors, vec![TRUNK_COLOR, color_for_name("topic")]);
+        assert_eq!(commit_color(&rows[2]), color_for_name("topic"));
+        assert_eq!(commit_color(&rows[3]), TRUNK_COLOR);
+    }
+
+    #[test]
+    fn test_merged_branch_name() {
+        assert_eq!(merged_branch_name("Merge branch 'topic'"), Some("topic"));
+        assert_eq!(
+            merged_branch_name("Merge branch 'fix/x' into 'main'"),
+            Some("fix/x")
+        );
+        assert_eq!(
+            merged_branch_name("Merge remote-tracking branch 'origin/topic'"),
+            Some("topic")
+        );
+        assert_eq!(
+            merged_branch_name("Merge pull request #12 from owner/feature/y"),
+            Some("feature/y")
+        );
+        assert_eq!(merged_branch_name("Fix the merge code"), None);
+    }
+
+    fn render(commits: &[Commit]) -> Vec<String> {
+        compute_layout(commits, &LayoutOptions::default())
+            .iter()
+            .map(graph_prefix)
+            .collect()
+    }
+
+    #[test]
+    fn test_freed_column_pulls_lines_left() {
+        let commits = vec![
+            make_commit("a", &["a0"]),
+            make_commit("b", &["b0"]),
+            make_commit("c", &["c0"]),
+            make_commit("b0", &[]),
+            make_commit("a0", &["base"]),
+            make_commit("c0", &["base"]),
+            make_commit("base", &[]),
+        ];
+        assert_eq!(
+            render(&commits),
+            ["●", "│ ●", "│ │ ●", "│ ● │", "● ╭─╯", "├─●", "●"]
+        );
+    }
+
+    #[test]
+    fn test_merge_into_existing_line_joins_it() {
+        // M's second parent B is already drawn by T's line.
+        let commits = vec![
+            make_commit("T", &["B"]),
+            make_commit("M", &["A", "B"]),
+            make_commit("B", &["root"]),
+            make_commit("A", &["root"]),
+            make_commit("root", &[]),
+        ];
+        assert_eq!(render(&commits), ["●", "├─◎", "● │", "├─●", "●"]);
+    }
+
+    #[test]
+    fn test_lanes_past_the_maximum_collapse_into_overflow() {
+        let commits = vec![
+            make_commit("a", &["a0"]),
+            make_commit("b", &["b0"]),
+            make_commit("c", &["c0"]),
+            make_commit("d", &["d0"]),
+            make_commit("a0", &[]),
+            make_commit("b0", &[]),
+            make_commit("c0", &[]),
+            make_commit("d0", &[]),
+        ];
+        let options = LayoutOptions {
+            max_lanes: Some(2),
+            ..LayoutOptions::default()
+        };
+        let rendered: Vec<String> = compute_layout(&commits, &options)
+            .iter()
+            .map(graph_prefix)
+            .collect();
+        assert_eq!(
+            rendered,
+            [
+                "●",
+                "│ ●",
+                "│ │ ●",
+                "│ │ ●",
+                "● │ …",
+                "  ● …",
+                "    ●",
+                "    ●"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_long_lived_line_keeps_its_column() {
+        // `s` runs alongside a long chain; when column 0 frees up it stays put.
+        let chain_length = STABLE_LANE_ROWS + 2;
+        let mut commits = vec![make_commit("t", &["t0"]), make_commit("s", &["s0"])];
+        for i in 0..chain_length {
+            let parent = format!("c{}", i + 1);
+            commits.push(make_commit(&format!("c{}", i), &[&parent]));
+        }
+        commits.push(make_commit("t0", &[]));
+        commits.push(make_commit(&format!("c{}", chain_length), &[]));
+        commits.push(make_commit("s0", &[]));
+
+        let rendered = render(&commits);
+        assert_eq!(rendered[rendered.len() - 3], "● │ │");
+        assert_eq!(rendered[rendered.len() - 2], "  │ ●");
+        assert_eq!(rendered[rendered.len() - 1], "  ●");
+    }
+
+    #[test]
+    fn test_new_lines_take_the_nearest_free_column() {
+        // Column 0 is free when M is drawn, but M opens beside its first
+        // parent and its second parent opens next to M.
+        let commits = vec![
+            make_commit("a", &["a0"]),
+            make_commit("b", &["b0"]),
+            make_commit("a0", &[]),
+            make_commit("M", &["b0", "x"]),
+        ];
+        assert_eq!(render(&commits)[3], "  ├─◎─╮");
+    }
+
+    #[test]
+    fn test_first_parent_view_collapses_and_expands_merges() {
+        let commits = vec![
+            make_commit("m2", &["M"]),
+            make_commit("M", &["m1", "t2"]),
+            make_commit("t2", &["t1"]),
+            make_commit("t1", &["base"]),
+            make_commit("m1", &["base"]),
+            make_commit("base", &[]),
+        ];
+        let mut options = LayoutOptions {
+            first_parent: true,
+            ..LayoutOptions::default()
+        };
+        let shown = |rows: &[GraphRow]| -> Vec<String> {
+            rows.iter()
+                .filter(|row| !row.hidden)
+                .map(graph_prefix)
+                .collect()
+        };
+
+        let rows = compute_layout(&commits, &options);
+        assert_eq!(shown(&rows), ["●", "⊕", "●", "●"]);
+        assert!(rows[2].hidden && rows[3].hidden);
+
+        options.expanded_merges.insert("M".to_string());
+        let rows = compute_layout(&commits, &options);
+        assert_eq!(shown(&rows), render(&commits));
+    }
+
+    #[test]
+    fn test_first_parent_view_keeps_named_tips() {
+        // `topic` was merged but still has a branch pointing at it.
+        let commits = vec![
+            make_commit("M", &["m1", "t1"]),
+            make_commit("t1", &["base"]),
+            make_commit("m1", &["base"]),
+            make_commit("base", &[]),
+        ];
+        let mut options = named(&[("M", "main"), ("t1", "topic")]);
+        options.first_parent = true;
+        let rendered: Vec<String> = compute_layout(&commits, &options)
+            .iter()
+            .map(graph_prefix)
+            .collect();
+        assert_eq!(rendered, ["⊕", "│ ●", "●─╯", "●"]);
+    }
+
+    /// Lay out the repository's history and check every commit lands in the
+    /// same column as its `*` in `git log --graph`, and that the lines below
+    /// it cross between the same pairs of neighbouring lanes as git's `/`,
+    /// `\` and `_` connectors. Returns our rendering.
+    fn assert_lanes_match_git(repo: &TempRepo) -> Vec<String> {
+        let commits = load_commits(repo.path(), 100, &["--all".to_string()], None)
+            .expect("commits should load");
+        let rows = compute_layout(&commits, &LayoutOptions::default());
+        let graph = run_git(
+            repo.path(),
+            &["log", "--graph", "--topo-order", "--all", "--format=%H"],
+        );
+
+        // Each commit with its column and the lane gaps that git's
+        // connector lines cross before the next commit.
+        let mut expected: Vec<(String, usize, BTreeSet<usize>)> = Vec::new();
+        for line in graph.lines() {
+            if let Some(column) = line.find('*') {
+                let oid = line.split_whitespace().last().unwrap_or_default();
+                expected.push((oid.to_string(), column / 2, BTreeSet::new()));
+            } else if let Some((_, _, gaps)) = expected.last_mut() {
+                gaps.extend(
+                    line.char_indices()
+                        .filter(|&(i, c)| i % 2 == 1 && matches!(c, '/' | '\\' | '_'))
+                        .map(|(i, _)| i / 2),
+                );
+            }
+        }
+        let rendered: Vec<String> = rows.iter().map(graph_prefix).collect();
+        // Our connectors share the commit's row, drawn as `─` between lanes.
+        let actual: Vec<(String, usize, BTreeSet<usize>)> = commits
+            .iter()
+            .zip(&rows)
+            .zip(&rendered)
+            .map(|((commit, row), prefix)| {
+                let gaps = prefix
+                    .chars()
+                    .enumerate()
+                    .filter(|&(i, c)| i % 2 == 1 && c == '─')
+                    .map(|(i, _)| i / 2)
+                    .collect();
+                (commit.oid.clone(), row.commit_lane, gaps)
+            })
+            .collect();
+        assert_eq!(
+            actual,
+            expected,
+            "git log --graph:\n{}\ngitviz:\n{}",
+            graph,
+            rendered.join("\n")
+        );
+        rendered
+    }
+
+    fn checkout(repo: &TempRepo, args: &[&str]) {
+        let mut full = vec!["checkout", "-q"];
+        full.extend_from_slice(args);
+        run_git(repo.path(), &full);
+    }
+
+    fn merge(repo: &TempRepo, branches: &[&str]) {
+        let mut args = vec!["merge", "-q", "--no-ff", "-m", "merge"];
+        args.extend_from_slice(branches);
+        run_git(repo.path(), &args);
+    }
+
+    #[test]
+    fn test_golden_branch_and_merge() {
+        let repo = TempRepo::new();
+        repo.commit_file("base", "base", "base");
+        checkout(&repo, &["-b", "topic"]);
+        repo.commit_file("t1", "t1", "t1");
+        repo.commit_file("t2", "t2", "t2");
+        checkout(&repo, &["main"]);
+        repo.commit_file("m1", "m1", "m1");
+        merge(&repo, &["topic"]);
+        repo.commit_file("m2", "m2", "m2");
+
+        let rendered = assert_lanes_match_git(&repo);
+        assert_eq!(rendered, ["●", "◎─╮", "│ ●", "│ ●", "●─╯", "●"]);
+    }
+
+    #[test]
+    fn test_golden_forks() {
+        let repo = TempRepo::new();
+        repo.commit_file("base", "base", "base");
+        checkout(&repo, &["-b", "one"]);
+        repo.commit_file("one", "one", "one");
+        checkout(&repo, &["-b", "two", "main"]);
+        repo.commit_file("two", "two", "two");
+        checkout(&repo, &["main"]);
+        repo.commit_file("m1", "m1", "m1");
+
+        let rendered = assert_lanes_match_git(&repo);
+        assert_eq!(rendered, ["●", "├─●", "├─●", "●"]);
+    }
+
+    #[test]
+    fn test_golden_octopus_merge() {
+        let repo = TempRepo::new();
+        repo.commit_file("base", "base", "base");
+        for name in ["a", "b", "c"] {
+            checkout(&repo, &["-b", name, "main"]);
+            repo.commit_file(name, name, name);
+        }
+        checkout(&repo, &["main"]);
+        merge(&repo, &["a", "b", "c"]);
+
+        let rendered = assert_lanes_match_git(&repo);
+        assert_eq!(rendered, ["◎─┬─┬─╮", "├─┼─┼─●", "├─┼─●", "├─●", "●"]);
+    }
+
+    #[test]
+    fn test_golden_crossing() {
+        let repo = TempRepo::new();
+        repo.commit_file("base", "base", "base");
+        checkout(&repo, &["-b", "a"]);
+        repo.commit_file("a1", "a1", "a1");
+        checkout(&repo, &["-b", "b", "main"]);
+        repo.commit_file("b1", "b1", "b1");
+        checkout(&repo, &["main"]);
+        repo.commit_file("m1", "m1", "m1");
+        merge(&repo, &["b"]);
+        checkout(&repo, &["a"]);
+        repo.commit_file("a2", "a2", "a2");
+
+        let rendered = assert_lanes_match_git(&repo);
+        assert_eq!(rendered, ["●", "●", "│ ◎─╮", "├─┼─●", "├─●", "●"]);
+    }
+
+    #[test]
+    fn test_golden_fork_from_merge() {
+        let repo = TempRepo::new();
+        repo.commit_file("base", "base", "base");
+        checkout(&repo, &["-b", "topic"]);
+        repo.commit_file("t1", "t1", "t1");
+        checkout(&repo, &["main"]);
+        repo.commit_file("m1", "m1", "m1");
+        merge(&repo, &["topic"]);
+        checkout(&repo, &["-b", "feature"]);
+        repo.commit_file("f1", "f1", "f1");
+        checkout(&repo, &["main"]);
+        repo.commit_file("m2", "m2", "m2");
+
+        let rendered = assert_lanes_match_git(&repo);
+        assert_eq!(rendered, ["●", "├─●", "◎─╮", "│ ●", "●─╯", "●"]);
+    }
 }
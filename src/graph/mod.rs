@@ -1,5 +1,8 @@
+mod first_parent;
+/// Lane assignment: which column each commit and line occupies.
 pub mod lanes;
+/// Drawing laid-out rows as text.
 pub mod render;
 
-pub use lanes::{compute_layout, GraphRow};
-pub use render::graph_prefix;
+pub use lanes::{compute_layout, GraphRow, LayoutOptions};
+pub use render::{graph_lines, row_height, GraphGlyphs, GraphSpacing, GraphStyle};
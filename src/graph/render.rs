@@ -1,27 +1,210 @@
 use super::lanes::{GraphCell, GraphRow};
 
-pub fn graph_prefix(row: &GraphRow) -> String {
-    let mut output = String::with_capacity(row.cells.len() * 2);
-    for (i, cell) in row.cells.iter().enumerate() {
-        if i > 0 {
-            output.push(' ');
+/// Character set used to draw the graph.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GraphGlyphs {
+    /// Box drawing with rounded corners (`╮ ╯`).
+    #[default]
+    Rounded,
+    /// Box drawing with square corners (`┐ ┘`).
+    Square,
+    /// The characters `git log --graph` uses, for fonts without box drawing.
+    Ascii,
+}
+
+impl GraphGlyphs {
+    /// Parse `rounded`, `square` or `ascii`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "rounded" => Some(GraphGlyphs::Rounded),
+            "square" => Some(GraphGlyphs::Square),
+            "ascii" => Some(GraphGlyphs::Ascii),
+            _ => None,
+        }
+    }
+}
+
+/// How much room the graph takes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GraphSpacing {
+    /// One character per lane, with no gaps between lanes.
+    Compact,
+    /// One row per commit, with a gap between lanes.
+    #[default]
+    Normal,
+    /// Like `Normal`, with connectors drawn on their own row beneath the
+    /// commit wherever lines branch, merge or shift.
+    Wide,
+}
+
+impl GraphSpacing {
+    /// Parse `compact`, `normal` or `wide`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "compact" => Some(GraphSpacing::Compact),
+            "normal" => Some(GraphSpacing::Normal),
+            "wide" => Some(GraphSpacing::Wide),
+            _ => None,
         }
-        output.push(cell_glyph(*cell));
     }
-    output
 }
 
-fn cell_glyph(cell: GraphCell) -> char {
+/// How [`graph_lines`] draws rows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct GraphStyle {
+    /// Character set.
+    pub glyphs: GraphGlyphs,
+    /// Room between lanes and rows.
+    pub spacing: GraphSpacing,
+}
+
+/// One line of graph drawing, split into pieces paired with their line's
+/// colour key.
+pub type GraphSegments = Vec<(String, u64)>;
+
+/// The row's graph drawing as plain text, in the default style.
+pub fn graph_prefix(row: &GraphRow) -> String {
+    graph_segments(row)
+        .into_iter()
+        .map(|(text, _)| text)
+        .collect()
+}
+
+/// The row's commit line in the default style.
+pub fn graph_segments(row: &GraphRow) -> GraphSegments {
+    graph_lines(row, GraphStyle::default())
+        .into_iter()
+        .next()
+        .unwrap_or_default()
+}
+
+/// Lines drawn for a row: the commit line, followed in wide spacing by a
+/// connector line when the row has any horizontal strokes.
+pub fn graph_lines(row: &GraphRow, style: GraphStyle) -> Vec<GraphSegments> {
+    if style.spacing != GraphSpacing::Wide || !has_connectors(row) {
+        return vec![segments(&row.cells, &row.colors, style)];
+    }
+
+    // The commit line keeps only what arrives from above; everything that
+    // bends moves to the connector line beneath it.
+    let mut commit_line: Vec<GraphCell> = row
+        .cells
+        .iter()
+        .enumerate()
+        .map(|(column, cell)| {
+            if column == row.commit_lane || *cell == GraphCell::Overflow {
+                *cell
+            } else if cell.connects_up() {
+                GraphCell::Vertical
+            } else {
+                GraphCell::Empty
+            }
+        })
+        .collect();
+    while matches!(commit_line.last(), Some(GraphCell::Empty)) {
+        commit_line.pop();
+    }
+
+    let lane = row.commit_lane;
+    let mut connectors = row.cells.clone();
+    connectors[lane] = GraphCell::from_strokes(
+        true,
+        row.continues_below,
+        lane > 0 && row.cells[lane - 1].connects_right(),
+        row.cells
+            .get(lane + 1)
+            .is_some_and(|cell| cell.connects_left()),
+    );
+
+    vec![
+        segments(&commit_line, &row.colors, style),
+        segments(&connectors, &row.colors, style),
+    ]
+}
+
+/// Number of terminal lines the row takes.
+pub fn row_height(row: &GraphRow, style: GraphStyle) -> usize {
+    if style.spacing == GraphSpacing::Wide && has_connectors(row) {
+        2
+    } else {
+        1
+    }
+}
+
+fn has_connectors(row: &GraphRow) -> bool {
+    row.cells
+        .iter()
+        .any(|cell| cell.connects_left() || cell.connects_right())
+}
+
+fn segments(cells: &[GraphCell], colors: &[u64], style: GraphStyle) -> GraphSegments {
+    cells
+        .iter()
+        .enumerate()
+        .map(|(i, cell)| {
+            let mut text = String::with_capacity(8);
+            if i > 0 && style.spacing != GraphSpacing::Compact {
+                text.push(separator(cells[i - 1], *cell, style.glyphs));
+            }
+            text.push(cell_glyph(*cell, style.glyphs));
+            (text, colors.get(i).copied().unwrap_or_default())
+        })
+        .collect()
+}
+
+/// The gap between two cells continues a horizontal line running through both.
+fn separator(left: GraphCell, right: GraphCell, glyphs: GraphGlyphs) -> char {
+    let joins = if right == GraphCell::Overflow {
+        left.connects_right()
+    } else {
+        (left.connects_right() || left.is_commit()) && (right.connects_left() || right.is_commit())
+    };
+    if joins {
+        cell_glyph(GraphCell::Horizontal, glyphs)
+    } else {
+        ' '
+    }
+}
+
+fn cell_glyph(cell: GraphCell, glyphs: GraphGlyphs) -> char {
+    match (glyphs, cell) {
+        (_, GraphCell::Empty) => ' ',
+        (GraphGlyphs::Ascii, cell) => ascii_glyph(cell),
+        (_, GraphCell::Vertical) => '│',
+        (_, GraphCell::Horizontal) => '─',
+        (_, GraphCell::Commit) => '●',
+        (_, GraphCell::MergeCommit) => '◎',
+        (GraphGlyphs::Square, GraphCell::CornerUpLeft) => '┘',
+        (GraphGlyphs::Square, GraphCell::CornerUpRight) => '└',
+        (GraphGlyphs::Square, GraphCell::CornerDownLeft) => '┐',
+        (GraphGlyphs::Square, GraphCell::CornerDownRight) => '┌',
+        (_, GraphCell::CornerUpLeft) => '╯',
+        (_, GraphCell::CornerUpRight) => '╰',
+        (_, GraphCell::CornerDownLeft) => '╮',
+        (_, GraphCell::CornerDownRight) => '╭',
+        (_, GraphCell::TeeLeft) => '┤',
+        (_, GraphCell::TeeRight) => '├',
+        (_, GraphCell::TeeDown) => '┬',
+        (_, GraphCell::TeeUp) => '┴',
+        (_, GraphCell::Crossing) => '┼',
+        (_, GraphCell::Overflow) => '…',
+        (_, GraphCell::CollapsedMerge) => '⊕',
+    }
+}
+
+/// `git log --graph` has no junction characters, so tees and crossings
+/// fall back to the stroke that matters most for reading the line.
+fn ascii_glyph(cell: GraphCell) -> char {
     match cell {
         GraphCell::Empty => ' ',
-        GraphCell::Vertical => '│',
-        GraphCell::Horizontal => '─',
-        GraphCell::Commit => '●',
-        GraphCell::MergeCommit => '◎',
-        GraphCell::CornerUpLeft => '╯',
-        GraphCell::CornerUpRight => '╰',
-        GraphCell::CornerDownLeft => '╮',
-        GraphCell::CornerDownRight => '╭',
+        GraphCell::Vertical | GraphCell::TeeLeft | GraphCell::TeeRight | GraphCell::Crossing => '|',
+        GraphCell::Horizontal | GraphCell::TeeUp => '-',
+        GraphCell::Commit | GraphCell::MergeCommit => '*',
+        GraphCell::CornerUpLeft | GraphCell::CornerDownRight => '/',
+        GraphCell::CornerUpRight | GraphCell::CornerDownLeft => '\\',
+        GraphCell::TeeDown => '.',
+        GraphCell::Overflow => '~',
+        GraphCell::CollapsedMerge => '+',
     }
 }
 
@@ -30,13 +213,26 @@ mod tests {
     use super::*;
     use crate::graph::lanes::GraphCell;
 
-    #[test]
-    fn test_graph_prefix_renders_merge_row() {
-        let row = GraphRow {
+    fn text(lines: Vec<GraphSegments>) -> Vec<String> {
+        lines
+            .into_iter()
+            .map(|line| line.into_iter().map(|(text, _)| text).collect())
+            .collect()
+    }
+
+    fn merge_row() -> GraphRow {
+        GraphRow {
             commit_lane: 0,
             cells: vec![GraphCell::MergeCommit, GraphCell::CornerDownLeft],
-        };
-        assert_eq!(graph_prefix(&row), "◎ ╮");
+            colors: vec![0, 7],
+            continues_below: true,
+            hidden: false,
+        }
+    }
+
+    #[test]
+    fn test_graph_prefix_renders_merge_row() {
+        assert_eq!(graph_prefix(&merge_row()), "◎─╮");
     }
 
     #[test]
@@ -48,8 +244,64 @@ mod tests {
                 GraphCell::Horizontal,
                 GraphCell::Commit,
             ],
+            colors: vec![3, 3, 5],
+            continues_below: true,
+            hidden: false,
         };
-        assert_eq!(graph_prefix(&row), "╭ ─ ●");
+        assert_eq!(graph_prefix(&row), "╭───●");
+        assert_eq!(
+            graph_segments(&row),
+            vec![
+                ("╭".to_string(), 3),
+                ("──".to_string(), 3),
+                ("─●".to_string(), 5)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_graph_styles() {
+        let style = |glyphs, spacing| GraphStyle { glyphs, spacing };
+        let row = merge_row();
+
+        let lines = graph_lines(&row, style(GraphGlyphs::Square, GraphSpacing::Normal));
+        assert_eq!(text(lines), ["◎─┐"]);
+        let lines = graph_lines(&row, style(GraphGlyphs::Ascii, GraphSpacing::Normal));
+        assert_eq!(text(lines), ["*-\\"]);
+        let lines = graph_lines(&row, style(GraphGlyphs::Rounded, GraphSpacing::Compact));
+        assert_eq!(text(lines), ["◎╮"]);
+
+        let wide = style(GraphGlyphs::Rounded, GraphSpacing::Wide);
+        assert_eq!(text(graph_lines(&row, wide)), ["◎", "├─╮"]);
+        assert_eq!(row_height(&row, wide), 2);
+        let ascii_wide = style(GraphGlyphs::Ascii, GraphSpacing::Wide);
+        assert_eq!(text(graph_lines(&row, ascii_wide)), ["*", "|-\\"]);
     }
 
+    #[test]
+    fn test_wide_fork_row() {
+        // A branch tip whose parent is drawn in the column to its left.
+        let row = GraphRow {
+            commit_lane: 1,
+            cells: vec![GraphCell::TeeRight, GraphCell::Commit],
+            colors: vec![0, 4],
+            continues_below: false,
+            hidden: false,
+        };
+        let wide = GraphStyle {
+            glyphs: GraphGlyphs::Rounded,
+            spacing: GraphSpacing::Wide,
+        };
+        assert_eq!(text(graph_lines(&row, wide)), ["│ ●", "├─╯"]);
+
+        let plain = GraphRow {
+            commit_lane: 0,
+            cells: vec![GraphCell::Commit, GraphCell::Vertical],
+            colors: vec![0, 4],
+            continues_below: true,
+            hidden: false,
+        };
+        assert_eq!(text(graph_lines(&plain, wide)), ["● │"]);
+        assert_eq!(row_height(&plain, wide), 1);
+    }
 }
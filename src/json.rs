@@ -0,0 +1,373 @@
+use std::io::{self, Write};
+
+use serde::Serialize;
+
+use crate::git::model::{BranchTracking, Commit, Refs};
+use crate::graph::lanes::GraphCell;
+use crate::graph::render::graph_prefix;
+use crate::graph::GraphRow;
+
+/// Version of the `--json` output. Bumped when a field is removed or changes
+/// meaning; new fields may appear without a bump.
+pub const SCHEMA_VERSION: u32 = 2;
+
+/// How `--json` lays out its output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JsonStyle {
+    /// One JSON document holding everything.
+    Document,
+    /// One record per line: a header, the refs, then each commit in order.
+    Lines,
+}
+
+impl JsonStyle {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "document" => Some(JsonStyle::Document),
+            "lines" => Some(JsonStyle::Lines),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct Document<'a> {
+    schema_version: u32,
+    refs: RefsJson<'a>,
+    commits: Vec<CommitJson<'a>>,
+}
+
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Record<'a> {
+    Header { schema_version: u32 },
+    Refs(RefsJson<'a>),
+    Commit(Box<CommitJson<'a>>),
+}
+
+#[derive(Serialize)]
+struct RefsJson<'a> {
+    head: HeadJson<'a>,
+    branches: Vec<BranchJson<'a>>,
+    remotes: Vec<RefJson<'a>>,
+    tags: Vec<RefJson<'a>>,
+    stashes: Vec<RefJson<'a>>,
+}
+
+#[derive(Serialize)]
+struct HeadJson<'a> {
+    oid: &'a str,
+    /// Full refname of the checked-out branch; `null` when detached.
+    branch: Option<&'a str>,
+}
+
+#[derive(Serialize)]
+struct RefJson<'a> {
+    name: &'a str,
+    oid: &'a str,
+}
+
+#[derive(Serialize)]
+struct BranchJson<'a> {
+    name: &'a str,
+    oid: &'a str,
+    upstream: Option<UpstreamJson<'a>>,
+}
+
+#[derive(Serialize)]
+struct UpstreamJson<'a> {
+    name: &'a str,
+    ahead: usize,
+    behind: usize,
+    gone: bool,
+}
+
+#[derive(Serialize)]
+struct CommitJson<'a> {
+    oid: &'a str,
+    parents: &'a [String],
+    /// Author name and email mapped through `.mailmap`.
+    author: &'a str,
+    author_email: &'a str,
+    /// Author name and email as recorded in the commit.
+    raw_author: &'a str,
+    raw_author_email: &'a str,
+    /// Author date as Unix seconds.
+    timestamp: i64,
+    /// Minutes east of UTC.
+    author_tz_offset: i32,
+    committer: &'a str,
+    committer_email: &'a str,
+    raw_committer: &'a str,
+    raw_committer_email: &'a str,
+    commit_timestamp: i64,
+    committer_tz_offset: i32,
+    signature: SignatureJson<'a>,
+    subject: &'a str,
+    body: &'a str,
+    trailers: Vec<TrailerJson<'a>>,
+    labels: Vec<String>,
+    row: RowJson,
+}
+
+#[derive(Serialize)]
+struct SignatureJson<'a> {
+    status: &'static str,
+    trust: &'static str,
+    signer: &'a str,
+    key: &'a str,
+}
+
+#[derive(Serialize)]
+struct TrailerJson<'a> {
+    key: &'a str,
+    value: &'a str,
+}
+
+#[derive(Serialize)]
+struct RowJson {
+    commit_lane: usize,
+    continues_below: bool,
+    cells: Vec<&'static str>,
+    /// Branch colour key of each cell as 16 hex digits; a string because
+    /// 64-bit integers do not survive JavaScript's number type.
+    color_keys: Vec<String>,
+    /// The row as drawn with the default glyphs.
+    text: String,
+}
+
+/// Write commits, refs and graph rows to `out` in the given style.
+/// Rows hidden by a first-parent view are left out.
+pub fn write_json(
+    out: &mut impl Write,
+    commits: &[Commit],
+    refs: &Refs,
+    graph: &[GraphRow],
+    style: JsonStyle,
+) -> io::Result<()> {
+    let mut commits = commits
+        .iter()
+        .zip(graph)
+        .filter(|(_, row)| !row.hidden)
+        .map(|(commit, row)| commit_json(commit, row, refs));
+
+    match style {
+        JsonStyle::Document => {
+            let document = Document {
+                schema_version: SCHEMA_VERSION,
+                refs: refs_json(refs),
+                commits: commits.collect(),
+            };
+            serde_json::to_writer_pretty(&mut *out, &document)?;
+            writeln!(out)
+        }
+        JsonStyle::Lines => {
+            let header = Record::Header {
+                schema_version: SCHEMA_VERSION,
+            };
+            write_line(out, &header)?;
+            write_line(out, &Record::Refs(refs_json(refs)))?;
+            commits.try_for_each(|commit| write_line(out, &Record::Commit(Box::new(commit))))
+        }
+    }
+}
+
+fn write_line(out: &mut impl Write, record: &Record) -> io::Result<()> {
+    serde_json::to_writer(&mut *out, record)?;
+    writeln!(out)
+}
+
+fn refs_json(refs: &Refs) -> RefsJson<'_> {
+    RefsJson {
+        head: HeadJson {
+            oid: &refs.head_oid,
+            branch: refs.head_name.as_deref(),
+        },
+        branches: refs
+            .branches
+            .iter()
+            .map(|(name, oid)| BranchJson {
+                name,
+                oid,
+                upstream: refs.tracking.get(name).map(upstream_json),
+            })
+            .collect(),
+        remotes: ref_list(&refs.remotes),
+        tags: ref_list(&refs.tags),
+        stashes: ref_list(&refs.stashes),
+    }
+}
+
+fn ref_list(list: &[(String, String)]) -> Vec<RefJson<'_>> {
+    list.iter()
+        .map(|(name, oid)| RefJson { name, oid })
+        .collect()
+}
+
+fn upstream_json(tracking: &BranchTracking) -> UpstreamJson<'_> {
+    UpstreamJson {
+        name: &tracking.upstream,
+        ahead: tracking.ahead,
+        behind: tracking.behind,
+        gone: tracking.gone,
+    }
+}
+
+fn commit_json<'a>(commit: &'a Commit, row: &GraphRow, refs: &Refs) -> CommitJson<'a> {
+    CommitJson {
+        oid: &commit.oid,
+        parents: &commit.parents,
+        author: &commit.author,
+        author_email: &commit.author_email,
+        raw_author: &commit.raw_author,
+        raw_author_email: &commit.raw_author_email,
+        timestamp: commit.timestamp,
+        author_tz_offset: commit.author_tz_offset,
+        committer: &commit.committer,
+        committer_email: &commit.committer_email,
+        raw_committer: &commit.raw_committer,
+        raw_committer_email: &commit.raw_committer_email,
+        commit_timestamp: commit.commit_timestamp,
+        committer_tz_offset: commit.committer_tz_offset,
+        signature: SignatureJson {
+            status: commit.signature.status.name(),
+            trust: commit.signature.status.trust().name(),
+            signer: &commit.signature.signer,
+            key: &commit.signature.key,
+        },
+        subject: &commit.subject,
+        body: &commit.body,
+        trailers: commit
+            .trailers
+            .iter()
+            .map(|trailer| TrailerJson {
+                key: &trailer.key,
+                value: &trailer.value,
+            })
+            .collect(),
+        labels: refs.labels_for(&commit.oid),
+        row: RowJson {
+            commit_lane: row.commit_lane,
+            continues_below: row.continues_below,
+            cells: row.cells.iter().map(|&cell| cell_name(cell)).collect(),
+            color_keys: row
+                .colors
+                .iter()
+                .map(|key| format!("{:016x}", key))
+                .collect(),
+            text: graph_prefix(row),
+        },
+    }
+}
+
+/// Schema name of a cell; kept apart from the enum so renaming a variant
+/// cannot change the output.
+fn cell_name(cell: GraphCell) -> &'static str {
+    match cell {
+        GraphCell::Empty => "empty",
+        GraphCell::Vertical => "vertical",
+        GraphCell::Horizontal => "horizontal",
+        GraphCell::Commit => "commit",
+        GraphCell::MergeCommit => "merge_commit",
+        GraphCell::CornerUpLeft => "corner_up_left",
+        GraphCell::CornerUpRight => "corner_up_right",
+        GraphCell::CornerDownLeft => "corner_down_left",
+        GraphCell::CornerDownRight => "corner_down_right",
+        GraphCell::TeeLeft => "tee_left",
+        GraphCell::TeeRight => "tee_right",
+        GraphCell::TeeDown => "tee_down",
+        GraphCell::TeeUp => "tee_up",
+        GraphCell::Crossing => "crossing",
+        GraphCell::Overflow => "overflow",
+        GraphCell::CollapsedMerge => "collapsed_merge",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{compute_layout, LayoutOptions};
+    use serde_json::Value;
+
+    fn history() -> (Vec<Commit>, Refs, Vec<GraphRow>) {
+        let commit = |oid: &str, parents: &[&str]| Commit {
+            oid: oid.to_string(),
+            parents: parents.iter().map(|parent| parent.to_string()).collect(),
+            author: "Ada".to_string(),
+            author_email: "ada@example.com".to_string(),
+            timestamp: 1_700_000_000,
+            subject: format!("commit {}", oid),
+            body: String::new(),
+            ..Default::default()
+        };
+        let commits = vec![
+            commit("m", &["a", "b"]),
+            commit("b", &["a"]),
+            commit("a", &[]),
+        ];
+        let mut refs = Refs {
+            head_oid: "m".to_string(),
+            head_name: Some("refs/heads/main".to_string()),
+            ..Refs::default()
+        };
+        refs.branches
+            .push(("refs/heads/main".to_string(), "m".to_string()));
+        refs.tracking.insert(
+            "refs/heads/main".to_string(),
+            BranchTracking {
+                upstream: "refs/remotes/origin/main".to_string(),
+                ahead: 2,
+                behind: 0,
+                gone: false,
+            },
+        );
+        let graph = compute_layout(&commits, &LayoutOptions::default());
+        (commits, refs, graph)
+    }
+
+    fn write(style: JsonStyle) -> String {
+        let (commits, refs, graph) = history();
+        let mut out = Vec::new();
+        write_json(&mut out, &commits, &refs, &graph, style).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_document_holds_refs_commits_and_rows() {
+        let document: Value = serde_json::from_str(&write(JsonStyle::Document)).unwrap();
+        assert_eq!(document["schema_version"], SCHEMA_VERSION);
+        assert_eq!(document["refs"]["head"]["branch"], "refs/heads/main");
+        assert_eq!(document["refs"]["branches"][0]["upstream"]["ahead"], 2);
+
+        let merge = &document["commits"][0];
+        assert_eq!(merge["oid"], "m");
+        assert_eq!(merge["parents"], serde_json::json!(["a", "b"]));
+        assert_eq!(merge["labels"], serde_json::json!(["HEAD -> main"]));
+        assert_eq!(merge["row"]["commit_lane"], 0);
+        assert_eq!(
+            merge["row"]["cells"],
+            serde_json::json!(["merge_commit", "corner_down_left"])
+        );
+        let key = merge["row"]["color_keys"][0].as_str().unwrap();
+        assert!(key.len() == 16 && u64::from_str_radix(key, 16).is_ok());
+        assert_eq!(merge["row"]["text"], "◎─╮");
+        assert_eq!(document["commits"].as_array().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_lines_stream_one_record_per_line() {
+        let output = write(JsonStyle::Lines);
+        let records: Vec<Value> = output
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        let types: Vec<&str> = records
+            .iter()
+            .map(|record| record["type"].as_str().unwrap())
+            .collect();
+        assert_eq!(types, ["header", "refs", "commit", "commit", "commit"]);
+        assert_eq!(records[0]["schema_version"], SCHEMA_VERSION);
+        assert_eq!(records[3]["oid"], "b");
+        assert_eq!(records[3]["row"]["commit_lane"], 1);
+    }
+}
@@ -0,0 +1,786 @@
+use std::time::{Duration, Instant};
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+/// Commands that can be bound to keys in normal mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    MoveDown,
+    MoveUp,
+    MoveToTop,
+    MoveToBottom,
+    SearchNext,
+    SearchPrevious,
+    ToggleDetails,
+    ToggleFullscreen,
+    CycleLayout,
+    GrowDetails,
+    ShrinkDetails,
+    NextTab,
+    PreviousTab,
+    ScrollDetailsDown,
+    ScrollDetailsUp,
+    HalfPageDown,
+    HalfPageUp,
+    Filter,
+    RevisionRange,
+    Reload,
+    CopyHash,
+    OpenCommit,
+    OpenReference,
+    ToggleBranches,
+    ToggleStats,
+    ToggleSidebar,
+    ToggleFirstParent,
+    ToggleMerge,
+    ToggleRawIdentity,
+    ToggleHelp,
+    Quit,
+}
+
+impl Action {
+    pub const ALL: [Action; 31] = [
+        Action::MoveDown,
+        Action::MoveUp,
+        Action::MoveToTop,
+        Action::MoveToBottom,
+        Action::SearchNext,
+        Action::SearchPrevious,
+        Action::ToggleDetails,
+        Action::ToggleFullscreen,
+        Action::CycleLayout,
+        Action::GrowDetails,
+        Action::ShrinkDetails,
+        Action::NextTab,
+        Action::PreviousTab,
+        Action::ScrollDetailsDown,
+        Action::ScrollDetailsUp,
+        Action::HalfPageDown,
+        Action::HalfPageUp,
+        Action::Filter,
+        Action::RevisionRange,
+        Action::Reload,
+        Action::CopyHash,
+        Action::OpenCommit,
+        Action::OpenReference,
+        Action::ToggleBranches,
+        Action::ToggleStats,
+        Action::ToggleSidebar,
+        Action::ToggleFirstParent,
+        Action::ToggleMerge,
+        Action::ToggleRawIdentity,
+        Action::ToggleHelp,
+        Action::Quit,
+    ];
+
+    /// Name used in the `[keys]` table of the config file.
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::MoveDown => "move_down",
+            Action::MoveUp => "move_up",
+            Action::MoveToTop => "move_to_top",
+            Action::MoveToBottom => "move_to_bottom",
+            Action::SearchNext => "search_next",
+            Action::SearchPrevious => "search_previous",
+            Action::ToggleDetails => "toggle_details",
+            Action::ToggleFullscreen => "toggle_fullscreen",
+            Action::CycleLayout => "cycle_layout",
+            Action::GrowDetails => "grow_details",
+            Action::ShrinkDetails => "shrink_details",
+            Action::NextTab => "next_tab",
+            Action::PreviousTab => "previous_tab",
+            Action::ScrollDetailsDown => "scroll_details_down",
+            Action::ScrollDetailsUp => "scroll_details_up",
+            Action::HalfPageDown => "half_page_down",
+            Action::HalfPageUp => "half_page_up",
+            Action::Filter => "filter",
+            Action::RevisionRange => "revision_range",
+            Action::Reload => "reload",
+            Action::CopyHash => "copy_hash",
+            Action::OpenCommit => "open_commit",
+            Action::OpenReference => "open_reference",
+            Action::ToggleBranches => "toggle_branches",
+            Action::ToggleStats => "toggle_stats",
+            Action::ToggleSidebar => "toggle_sidebar",
+            Action::ToggleFirstParent => "toggle_first_parent",
+            Action::ToggleMerge => "toggle_merge",
+            Action::ToggleRawIdentity => "toggle_raw_identity",
+            Action::ToggleHelp => "toggle_help",
+            Action::Quit => "quit",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Action::ALL.into_iter().find(|action| action.name() == name)
+    }
+
+    /// One-line description shown in the help overlay.
+    pub fn description(self) -> &'static str {
+        match self {
+            Action::MoveDown => "move selection down",
+            Action::MoveUp => "move selection up",
+            Action::MoveToTop => "jump to top (with count: to row N)",
+            Action::MoveToBottom => "jump to bottom (with count: to row N)",
+            Action::SearchNext => "next search result",
+            Action::SearchPrevious => "previous search result",
+            Action::ToggleDetails => "collapse or expand details",
+            Action::ToggleFullscreen => "toggle fullscreen details",
+            Action::CycleLayout => "cycle auto / stacked / side-by-side layout",
+            Action::GrowDetails => "grow the details pane",
+            Action::ShrinkDetails => "shrink the details pane",
+            Action::NextTab => "next detail tab",
+            Action::PreviousTab => "previous detail tab",
+            Action::ScrollDetailsDown => "scroll details down a page",
+            Action::ScrollDetailsUp => "scroll details up a page",
+            Action::HalfPageDown => "scroll details down half a page",
+            Action::HalfPageUp => "scroll details up half a page",
+            Action::Filter => "enter search",
+            Action::RevisionRange => "set revision range (Up/Down: history)",
+            Action::Reload => "reload repository state",
+            Action::CopyHash => "copy commit hash",
+            Action::OpenCommit => "open commit in the browser",
+            Action::OpenReference => "open a link from the message (with count: link N)",
+            Action::ToggleBranches => "branches sorted by ahead/behind",
+            Action::ToggleStats => "author statistics of the shown commits",
+            Action::ToggleSidebar => "open / focus / close the ref sidebar",
+            Action::ToggleFirstParent => "toggle first-parent history",
+            Action::ToggleMerge => "expand or collapse the selected merge",
+            Action::ToggleRawIdentity => "show authors as recorded or through .mailmap",
+            Action::ToggleHelp => "toggle help",
+            Action::Quit => "quit",
+        }
+    }
+
+    /// Help overlay section the action is listed under.
+    pub fn group(self) -> &'static str {
+        match self {
+            Action::MoveDown | Action::MoveUp | Action::MoveToTop | Action::MoveToBottom => {
+                "Navigation"
+            }
+            Action::SearchNext
+            | Action::SearchPrevious
+            | Action::Filter
+            | Action::RevisionRange => "Search",
+            Action::ToggleDetails
+            | Action::NextTab
+            | Action::PreviousTab
+            | Action::ScrollDetailsDown
+            | Action::ScrollDetailsUp
+            | Action::HalfPageDown
+            | Action::HalfPageUp
+            | Action::ToggleRawIdentity => "Details",
+            Action::ToggleFullscreen
+            | Action::CycleLayout
+            | Action::GrowDetails
+            | Action::ShrinkDetails => "Layout",
+            Action::ToggleFirstParent | Action::ToggleMerge => "Graph",
+            Action::Reload
+            | Action::CopyHash
+            | Action::OpenCommit
+            | Action::OpenReference
+            | Action::ToggleBranches
+            | Action::ToggleStats
+            | Action::ToggleSidebar => "Actions",
+            Action::ToggleHelp | Action::Quit => "Quit",
+        }
+    }
+
+    /// Whether a count prefix repeats the action (`10j`).
+    pub fn repeatable(self) -> bool {
+        matches!(
+            self,
+            Action::MoveDown
+                | Action::MoveUp
+                | Action::SearchNext
+                | Action::SearchPrevious
+                | Action::NextTab
+                | Action::PreviousTab
+                | Action::ScrollDetailsDown
+                | Action::ScrollDetailsUp
+                | Action::HalfPageDown
+                | Action::HalfPageUp
+                | Action::GrowDetails
+                | Action::ShrinkDetails
+        )
+    }
+}
+
+/// A single key press with modifiers, e.g. `Ctrl-d`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyChord {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl KeyChord {
+    pub const fn plain(code: KeyCode) -> Self {
+        KeyChord {
+            code,
+            modifiers: KeyModifiers::NONE,
+        }
+    }
+
+    pub const fn ctrl(c: char) -> Self {
+        KeyChord {
+            code: KeyCode::Char(c),
+            modifiers: KeyModifiers::CONTROL,
+        }
+    }
+
+    /// Normalise a terminal key event. Shift is dropped for characters since
+    /// it is already reflected in the character itself (`G`, `?`).
+    pub fn from_event(key: KeyEvent) -> Self {
+        let mut modifiers =
+            key.modifiers & (KeyModifiers::CONTROL | KeyModifiers::ALT | KeyModifiers::SHIFT);
+        if matches!(key.code, KeyCode::Char(_) | KeyCode::BackTab) {
+            modifiers.remove(KeyModifiers::SHIFT);
+        }
+        KeyChord {
+            code: key.code,
+            modifiers,
+        }
+    }
+
+    /// Parse a key written as `j`, `G`, `Ctrl-d`, `Alt-x`, `Shift-Tab`,
+    /// `PageDown`, `Space`, ...
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut rest = spec;
+        loop {
+            let lower = rest.to_ascii_lowercase();
+            if rest.len() > 1 && (lower.starts_with("ctrl-") || lower.starts_with("c-")) {
+                modifiers |= KeyModifiers::CONTROL;
+            } else if rest.len() > 1 && (lower.starts_with("alt-") || lower.starts_with("m-")) {
+                modifiers |= KeyModifiers::ALT;
+            } else if rest.len() > 1 && (lower.starts_with("shift-") || lower.starts_with("s-")) {
+                modifiers |= KeyModifiers::SHIFT;
+            } else {
+                break;
+            }
+            rest = &rest[rest.find('-').map(|i| i + 1).unwrap_or(rest.len())..];
+        }
+
+        let mut chars = rest.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) => KeyCode::Char(c),
+            _ => match rest.to_ascii_lowercase().as_str() {
+                "enter" | "return" => KeyCode::Enter,
+                "esc" | "escape" => KeyCode::Esc,
+                "tab" => KeyCode::Tab,
+                "backtab" => KeyCode::BackTab,
+                "backspace" => KeyCode::Backspace,
+                "space" => KeyCode::Char(' '),
+                "up" => KeyCode::Up,
+                "down" => KeyCode::Down,
+                "left" => KeyCode::Left,
+                "right" => KeyCode::Right,
+                "home" => KeyCode::Home,
+                "end" => KeyCode::End,
+                "pageup" => KeyCode::PageUp,
+                "pagedown" => KeyCode::PageDown,
+                "delete" | "del" => KeyCode::Delete,
+                "insert" => KeyCode::Insert,
+                other => match other.strip_prefix('f').and_then(|n| n.parse::<u8>().ok()) {
+                    Some(n) if (1..=12).contains(&n) => KeyCode::F(n),
+                    _ => return Err(format!("unknown key '{}'", spec)),
+                },
+            },
+        };
+
+        let mut chord = KeyChord { code, modifiers };
+        if chord.code == KeyCode::Tab && chord.modifiers.contains(KeyModifiers::SHIFT) {
+            chord = KeyChord::plain(KeyCode::BackTab);
+        } else if let KeyCode::Char(c) = chord.code {
+            if chord.modifiers.contains(KeyModifiers::SHIFT) {
+                chord.modifiers.remove(KeyModifiers::SHIFT);
+                chord.code = KeyCode::Char(c.to_ascii_uppercase());
+            }
+        }
+        Ok(chord)
+    }
+
+    fn is_plain_char(&self) -> bool {
+        matches!(self.code, KeyCode::Char(c) if c != ' ') && self.modifiers.is_empty()
+    }
+}
+
+impl std::fmt::Display for KeyChord {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            write!(f, "Ctrl-")?;
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            write!(f, "Alt-")?;
+        }
+        if self.modifiers.contains(KeyModifiers::SHIFT) {
+            write!(f, "Shift-")?;
+        }
+        match self.code {
+            KeyCode::Char(' ') => write!(f, "Space"),
+            KeyCode::Char(c) => write!(f, "{}", c),
+            KeyCode::BackTab => write!(f, "Shift-Tab"),
+            KeyCode::PageUp => write!(f, "PageUp"),
+            KeyCode::PageDown => write!(f, "PageDown"),
+            KeyCode::F(n) => write!(f, "F{}", n),
+            code => write!(f, "{:?}", code),
+        }
+    }
+}
+
+/// Keys pressed one after another, e.g. `g g` or `]c`.
+pub type KeySequence = Vec<KeyChord>;
+
+/// Parse a binding: a single key (`j`, `Ctrl-d`, `PageDown`), space-separated
+/// keys (`g g`, `Ctrl-w j`), or a run of plain characters typed in order
+/// (`]c`). A run of letters alone is read as a misspelt key name such as
+/// `Pgdown`, not as a sequence.
+pub fn parse_sequence(spec: &str) -> Result<KeySequence, String> {
+    let mut sequence = Vec::new();
+    for token in spec.split_whitespace() {
+        match KeyChord::parse(token) {
+            Ok(chord) => sequence.push(chord),
+            Err(err) if !is_char_run(token) => return Err(err),
+            Err(_) => sequence.extend(token.chars().map(|c| KeyChord::plain(KeyCode::Char(c)))),
+        }
+    }
+    if sequence.is_empty() {
+        return Err("empty key binding".to_string());
+    }
+    Ok(sequence)
+}
+
+/// Whether `token` may be read as keys typed one after another: printable
+/// characters other than `-`, not all of them letters.
+fn is_char_run(token: &str) -> bool {
+    token.chars().all(|c| !c.is_control() && c != '-') && !token.chars().all(char::is_alphabetic)
+}
+
+pub fn format_sequence(sequence: &[KeyChord]) -> String {
+    let letters_only = sequence
+        .iter()
+        .all(|chord| matches!(chord.code, KeyCode::Char(c) if c.is_alphabetic()));
+    if sequence.len() > 1 && !letters_only && sequence.iter().all(KeyChord::is_plain_char) {
+        sequence.iter().map(KeyChord::to_string).collect()
+    } else {
+        sequence
+            .iter()
+            .map(KeyChord::to_string)
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+/// Keys typed so far towards a sequence or count prefix.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct PendingKeys {
+    pub count: Option<usize>,
+    pub keys: KeySequence,
+    /// When the last key of `keys` was typed.
+    typed_at: Option<Instant>,
+}
+
+impl PendingKeys {
+    pub fn is_empty(&self) -> bool {
+        self.count.is_none() && self.keys.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.count = None;
+        self.keys.clear();
+        self.typed_at = None;
+    }
+}
+
+impl std::fmt::Display for PendingKeys {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(count) = self.count {
+            write!(f, "{}", count)?;
+        }
+        write!(f, "{}", format_sequence(&self.keys))
+    }
+}
+
+const MAX_COUNT: usize = 99_999;
+
+/// How long a key that is both a binding and the start of a longer one
+/// waits for the rest of the sequence.
+pub const SEQUENCE_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Key bindings for normal mode.
+#[derive(Debug, Clone)]
+pub struct Keymap {
+    bindings: Vec<(KeySequence, Action)>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        use KeyCode::*;
+
+        let single = |chord: KeyChord, action: Action| (vec![chord], action);
+        let bindings = vec![
+            single(KeyChord::plain(Char('j')), Action::MoveDown),
+            single(KeyChord::plain(Down), Action::MoveDown),
+            single(KeyChord::plain(Char('k')), Action::MoveUp),
+            single(KeyChord::plain(Up), Action::MoveUp),
+            single(KeyChord::plain(Char('g')), Action::MoveToTop),
+            single(KeyChord::plain(Home), Action::MoveToTop),
+            single(KeyChord::plain(Char('G')), Action::MoveToBottom),
+            single(KeyChord::plain(End), Action::MoveToBottom),
+            single(KeyChord::plain(Char('n')), Action::SearchNext),
+            single(KeyChord::plain(Char('N')), Action::SearchPrevious),
+            single(KeyChord::plain(Enter), Action::ToggleDetails),
+            single(KeyChord::plain(Char('f')), Action::ToggleFullscreen),
+            single(KeyChord::plain(Char('L')), Action::CycleLayout),
+            single(KeyChord::plain(Char('+')), Action::GrowDetails),
+            single(KeyChord::plain(Char('=')), Action::GrowDetails),
+            single(KeyChord::plain(Char('-')), Action::ShrinkDetails),
+            single(KeyChord::plain(Tab), Action::NextTab),
+            single(KeyChord::plain(BackTab), Action::PreviousTab),
+            single(KeyChord::plain(PageDown), Action::ScrollDetailsDown),
+            single(KeyChord::plain(PageUp), Action::ScrollDetailsUp),
+            single(KeyChord::ctrl('d'), Action::HalfPageDown),
+            single(KeyChord::ctrl('u'), Action::HalfPageUp),
+            single(KeyChord::plain(Char('/')), Action::Filter),
+            single(KeyChord::plain(Char('R')), Action::RevisionRange),
+            single(KeyChord::plain(Char('r')), Action::Reload),
+            single(KeyChord::plain(Char('y')), Action::CopyHash),
+            single(KeyChord::plain(Char('o')), Action::OpenCommit),
+            single(KeyChord::plain(Char('O')), Action::OpenReference),
+            single(KeyChord::plain(Char('b')), Action::ToggleBranches),
+            single(KeyChord::plain(Char('A')), Action::ToggleStats),
+            single(KeyChord::plain(Char('s')), Action::ToggleSidebar),
+            single(KeyChord::plain(Char('F')), Action::ToggleFirstParent),
+            single(KeyChord::plain(Char('x')), Action::ToggleMerge),
+            single(KeyChord::plain(Char('M')), Action::ToggleRawIdentity),
+            single(KeyChord::plain(Char('?')), Action::ToggleHelp),
+            single(KeyChord::plain(Char('q')), Action::Quit),
+        ];
+        Keymap { bindings }
+    }
+}
+
+impl Keymap {
+    /// Replace every binding of `action` with `sequences`. Sequences taken
+    /// from other actions are unbound from them first.
+    pub fn bind(&mut self, action: Action, sequences: &[KeySequence]) {
+        self.bindings
+            .retain(|(sequence, bound)| *bound != action && !sequences.contains(sequence));
+        self.bindings
+            .extend(sequences.iter().map(|sequence| (sequence.clone(), action)));
+    }
+
+    /// Key sequences bound to `action`, formatted for display.
+    pub fn keys_for(&self, action: Action) -> Vec<String> {
+        self.bindings
+            .iter()
+            .filter(|(_, bound)| *bound == action)
+            .map(|(sequence, _)| format_sequence(sequence))
+            .collect()
+    }
+
+    fn exact(&self, keys: &[KeyChord]) -> Option<Action> {
+        self.bindings
+            .iter()
+            .find(|(sequence, _)| sequence.as_slice() == keys)
+            .map(|(_, action)| *action)
+    }
+
+    fn extends(&self, keys: &[KeyChord]) -> bool {
+        self.bindings
+            .iter()
+            .any(|(sequence, _)| sequence.len() > keys.len() && sequence.starts_with(keys))
+    }
+
+    /// Feed one key press. Returns the actions (with their count prefix)
+    /// that became complete; an empty result means the key was consumed as
+    /// part of a count or sequence, or was not bound.
+    ///
+    /// A key bound on its own and as the start of a longer sequence waits
+    /// for the next key; if that key does not continue the sequence, the
+    /// shorter binding runs and the new key is processed afresh.
+    pub fn feed(&self, pending: &mut PendingKeys, key: KeyEvent) -> Vec<(Action, Option<usize>)> {
+        let chord = KeyChord::from_event(key);
+
+        if chord == KeyChord::plain(KeyCode::Esc) && !pending.is_empty() {
+            pending.clear();
+            return Vec::new();
+        }
+
+        if pending.keys.is_empty() && chord.modifiers.is_empty() {
+            if let KeyCode::Char(digit @ '0'..='9') = chord.code {
+                let starts_count = digit != '0' || pending.count.is_some();
+                if starts_count && self.exact(&[chord]).is_none() && !self.extends(&[chord]) {
+                    let value = digit.to_digit(10).unwrap_or(0) as usize;
+                    let count = pending.count.unwrap_or(0).saturating_mul(10) + value;
+                    pending.count = Some(count.min(MAX_COUNT));
+                    return Vec::new();
+                }
+            }
+        }
+
+        pending.keys.push(chord);
+        if self.extends(&pending.keys) {
+            pending.typed_at = Some(Instant::now());
+            return Vec::new();
+        }
+
+        if let Some(action) = self.exact(&pending.keys) {
+            let count = pending.count;
+            pending.clear();
+            return vec![(action, count)];
+        }
+
+        // The new key broke the sequence: run a complete shorter binding,
+        // then treat the new key as the start of the next command.
+        let typed = std::mem::take(&mut pending.keys);
+        let count = pending.count.take();
+        pending.typed_at = None;
+        let mut actions = Vec::new();
+        if typed.len() > 1 {
+            if let Some(action) = self.exact(&typed[..typed.len() - 1]) {
+                actions.push((action, count));
+                actions.extend(self.feed(pending, key));
+            }
+        }
+        actions
+    }
+
+    /// Run the binding the pending keys already complete once no further key
+    /// has arrived within `timeout`, as `g` does when `g g` is also bound.
+    /// Keys that only start a sequence keep waiting.
+    pub fn expire(
+        &self,
+        pending: &mut PendingKeys,
+        timeout: Duration,
+    ) -> Vec<(Action, Option<usize>)> {
+        let expired = pending
+            .typed_at
+            .is_some_and(|typed_at| typed_at.elapsed() >= timeout);
+        if !expired {
+            return Vec::new();
+        }
+        match self.exact(&pending.keys) {
+            Some(action) => {
+                let count = pending.count;
+                pending.clear();
+                vec![(action, count)]
+            }
+            None => Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent::new(code, modifiers)
+    }
+
+    #[test]
+    fn test_parse_key_specs() {
+        assert_eq!(
+            KeyChord::parse("j"),
+            Ok(KeyChord::plain(KeyCode::Char('j')))
+        );
+        assert_eq!(KeyChord::parse("Ctrl-d"), Ok(KeyChord::ctrl('d')));
+        assert_eq!(KeyChord::parse("C-d"), Ok(KeyChord::ctrl('d')));
+        assert_eq!(
+            KeyChord::parse("Shift-Tab"),
+            Ok(KeyChord::plain(KeyCode::BackTab))
+        );
+        assert_eq!(
+            KeyChord::parse("Shift-g"),
+            Ok(KeyChord::plain(KeyCode::Char('G')))
+        );
+        assert_eq!(
+            KeyChord::parse("PageDown"),
+            Ok(KeyChord::plain(KeyCode::PageDown))
+        );
+        assert_eq!(
+            KeyChord::parse("space"),
+            Ok(KeyChord::plain(KeyCode::Char(' ')))
+        );
+        assert_eq!(
+            KeyChord::parse("-"),
+            Ok(KeyChord::plain(KeyCode::Char('-')))
+        );
+        assert_eq!(KeyChord::parse("F5"), Ok(KeyChord::plain(KeyCode::F(5))));
+        assert!(KeyChord::parse("Hyper-x").is_err());
+    }
+
+    #[test]
+    fn test_action_names_round_trip() {
+        for action in Action::ALL {
+            assert_eq!(Action::from_name(action.name()), Some(action));
+        }
+        assert_eq!(Action::from_name("launch_rockets"), None);
+    }
+
+    fn press(
+        keymap: &Keymap,
+        pending: &mut PendingKeys,
+        keys: &str,
+    ) -> Vec<(Action, Option<usize>)> {
+        keys.chars()
+            .flat_map(|c| keymap.feed(pending, key(KeyCode::Char(c), KeyModifiers::NONE)))
+            .collect()
+    }
+
+    #[test]
+    fn test_keymap_ignores_shift_on_characters() {
+        let keymap = Keymap::default();
+        let mut pending = PendingKeys::default();
+        assert_eq!(
+            keymap.feed(&mut pending, key(KeyCode::Char('G'), KeyModifiers::SHIFT)),
+            vec![(Action::MoveToBottom, None)]
+        );
+        assert_eq!(
+            keymap.feed(&mut pending, key(KeyCode::Char('d'), KeyModifiers::CONTROL)),
+            vec![(Action::HalfPageDown, None)]
+        );
+        assert!(keymap
+            .feed(&mut pending, key(KeyCode::Char('d'), KeyModifiers::NONE))
+            .is_empty());
+        assert!(pending.is_empty());
+    }
+
+    #[test]
+    fn test_bind_replaces_defaults_and_steals_chords() {
+        let mut keymap = Keymap::default();
+        let mut pending = PendingKeys::default();
+        keymap.bind(Action::CopyHash, &[parse_sequence("c").unwrap()]);
+        keymap.bind(Action::Quit, &[parse_sequence("y").unwrap()]);
+        assert_eq!(
+            press(&keymap, &mut pending, "c"),
+            vec![(Action::CopyHash, None)]
+        );
+        assert_eq!(
+            press(&keymap, &mut pending, "y"),
+            vec![(Action::Quit, None)]
+        );
+        assert!(press(&keymap, &mut pending, "q").is_empty());
+    }
+
+    #[test]
+    fn test_parse_sequences() {
+        let chars = |s: &str| -> KeySequence {
+            s.chars()
+                .map(|c| KeyChord::plain(KeyCode::Char(c)))
+                .collect()
+        };
+        assert_eq!(parse_sequence("g g"), Ok(chars("gg")));
+        assert_eq!(parse_sequence("]c"), Ok(chars("]c")));
+        assert_eq!(parse_sequence("g]"), Ok(chars("g]")));
+        assert_eq!(
+            parse_sequence("Down"),
+            Ok(vec![KeyChord::plain(KeyCode::Down)])
+        );
+        assert_eq!(
+            parse_sequence("Ctrl-w j"),
+            Ok(vec![
+                KeyChord::ctrl('w'),
+                KeyChord::plain(KeyCode::Char('j'))
+            ])
+        );
+        assert!(parse_sequence("Hyper-x").is_err());
+        assert!(parse_sequence("  ").is_err());
+        // Misspelt key names are errors, not sequences of their letters.
+        assert_eq!(
+            parse_sequence("Pgdown"),
+            Err("unknown key 'Pgdown'".to_string())
+        );
+        assert_eq!(parse_sequence("gg"), Err("unknown key 'gg'".to_string()));
+        assert_eq!(
+            parse_sequence("Escap"),
+            Err("unknown key 'Escap'".to_string())
+        );
+        assert_eq!(format_sequence(&chars("]c")), "]c");
+        assert_eq!(format_sequence(&chars("gg")), "g g");
+        assert_eq!(
+            format_sequence(&[KeyChord::ctrl('w'), KeyChord::plain(KeyCode::Down)]),
+            "Ctrl-w Down"
+        );
+    }
+
+    #[test]
+    fn test_sequences_and_counts() {
+        let mut keymap = Keymap::default();
+        keymap.bind(Action::MoveToTop, &[parse_sequence("g g").unwrap()]);
+        keymap.bind(Action::NextTab, &[parse_sequence("]c").unwrap()]);
+        let mut pending = PendingKeys::default();
+
+        assert!(press(&keymap, &mut pending, "g").is_empty());
+        assert_eq!(pending.to_string(), "g");
+        assert_eq!(
+            press(&keymap, &mut pending, "g"),
+            vec![(Action::MoveToTop, None)]
+        );
+
+        assert_eq!(
+            press(&keymap, &mut pending, "10j"),
+            vec![(Action::MoveDown, Some(10))]
+        );
+        assert_eq!(
+            press(&keymap, &mut pending, "3]c"),
+            vec![(Action::NextTab, Some(3))]
+        );
+
+        // A broken sequence is dropped; Esc clears a pending count.
+        assert!(press(&keymap, &mut pending, "gx").is_empty());
+        assert!(press(&keymap, &mut pending, "12").is_empty());
+        assert!(keymap
+            .feed(&mut pending, key(KeyCode::Esc, KeyModifiers::NONE))
+            .is_empty());
+        assert!(pending.is_empty());
+    }
+
+    #[test]
+    fn test_shorter_binding_runs_when_sequence_breaks() {
+        let mut keymap = Keymap::default();
+        keymap.bind(Action::Reload, &[parse_sequence("g r").unwrap()]);
+        let mut pending = PendingKeys::default();
+        // `g` alone is still move_to_top; `gj` runs it, then `j`.
+        assert_eq!(
+            press(&keymap, &mut pending, "gj"),
+            vec![(Action::MoveToTop, None), (Action::MoveDown, None)]
+        );
+        assert_eq!(
+            press(&keymap, &mut pending, "gr"),
+            vec![(Action::Reload, None)]
+        );
+    }
+
+    #[test]
+    fn test_shorter_binding_runs_after_timeout() {
+        let mut keymap = Keymap::default();
+        keymap.bind(Action::Reload, &[parse_sequence("g r").unwrap()]);
+        keymap.bind(Action::NextTab, &[parse_sequence("]c").unwrap()]);
+        let mut pending = PendingKeys::default();
+
+        assert!(press(&keymap, &mut pending, "3g").is_empty());
+        assert!(keymap.expire(&mut pending, SEQUENCE_TIMEOUT).is_empty());
+        assert_eq!(
+            keymap.expire(&mut pending, Duration::ZERO),
+            vec![(Action::MoveToTop, Some(3))]
+        );
+        assert!(pending.is_empty());
+
+        // `]` is no binding of its own, so it waits for the rest.
+        assert!(press(&keymap, &mut pending, "]").is_empty());
+        assert!(keymap.expire(&mut pending, Duration::ZERO).is_empty());
+        assert_eq!(
+            press(&keymap, &mut pending, "c"),
+            vec![(Action::NextTab, None)]
+        );
+    }
+
+    #[test]
+    fn test_keys_for_lists_active_bindings() {
+        let keymap = Keymap::default();
+        assert_eq!(keymap.keys_for(Action::MoveDown), vec!["j", "Down"]);
+        assert_eq!(keymap.keys_for(Action::HalfPageDown), vec!["Ctrl-d"]);
+        assert_eq!(keymap.keys_for(Action::PreviousTab), vec!["Shift-Tab"]);
+    }
+}
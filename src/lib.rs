@@ -0,0 +1,50 @@
+//! Commit history parsing and graph layout behind the `gitviz` TUI.
+//!
+//! The [`git`] module loads commits and refs by running `git` and parsing its
+//! output into [`git::model`] types. The [`graph`] module assigns every
+//! commit a lane and turns the result into cells that
+//! [`graph::render`] draws as box-drawing or ASCII text:
+//!
+//! ```no_run
+//! use std::path::Path;
+//! use gitviz::git;
+//! use gitviz::graph::{compute_layout, graph_lines, GraphStyle, LayoutOptions};
+//!
+//! let repo = Path::new(".");
+//! let commits = git::load_commits(repo, 50, &git::default_revisions(true), None)?;
+//! let refs = git::load_refs(repo)?;
+//! let options = LayoutOptions {
+//!     branch_names: refs.branch_names(),
+//!     ..LayoutOptions::default()
+//! };
+//! for (commit, row) in commits.iter().zip(compute_layout(&commits, &options)) {
+//!     for line in graph_lines(&row, GraphStyle::default()) {
+//!         let graph: String = line.into_iter().map(|(text, _)| text).collect();
+//!         println!("{graph} {}", commit.subject);
+//!     }
+//! }
+//! # Ok::<(), anyhow::Error>(())
+//! ```
+//!
+//! The parsers in [`git::parser`] take the exact output of the `git`
+//! commands described on each function, so callers that already run `git`
+//! themselves can use them without [`git::load_commits`].
+
+#![warn(missing_docs)]
+
+/// Links into the web UI of the forge hosting a remote.
+pub mod forge;
+/// Loading history from a repository.
+pub mod git;
+/// Commit graph layout and drawing.
+pub mod graph;
+/// Issue, pull request and tracker references in commit messages.
+pub mod references;
+/// Per-author commit, line and weekly activity counts.
+pub mod stats;
+/// Colours and styles for the graph, ref labels and diffs.
+pub mod theme;
+/// Date and hash formatting.
+pub mod util;
+/// ratatui widgets for the commit graph and commit details.
+pub mod widgets;
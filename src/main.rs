@@ -1,9 +1,13 @@
 mod app;
 mod cli;
-mod git;
-mod graph;
+mod config;
+mod export;
+mod json;
+mod keymap;
+mod prefs;
 mod ui;
-mod util;
+
+use gitviz::{forge, git, graph, references, stats, theme, util, widgets};
 
 use std::io::{self, stdout, Write};
 use std::path::PathBuf;
@@ -11,15 +15,32 @@ use std::process::{Command, Stdio};
 use std::time::Duration;
 
 use anyhow::{Context, Result};
-use app::{App, DetailsTab, Mode};
+use app::{App, DetailsTab, DragTarget, LayoutMode, Mode};
 use clap::Parser;
-use cli::Cli;
+use cli::{Cli, Commands, ExportArgs};
+use config::Config;
 use crossterm::{
-    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    event::{
+        self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEvent, KeyEventKind,
+        KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+    },
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
-use ratatui::{backend::CrosstermBackend, Terminal};
+use export::{ExportFormat, ExportOptions, PageBackground};
+use forge::ForgeHosts;
+use git::model::{Commit, Refs};
+use graph::{GraphGlyphs, GraphSpacing, GraphStyle};
+use json::JsonStyle;
+use keymap::{Action, SEQUENCE_TIMEOUT};
+use prefs::Preferences;
+use ratatui::{
+    backend::CrosstermBackend,
+    layout::{Position, Rect},
+    Terminal,
+};
+use references::ReferenceFinder;
+use theme::{ColorMode, Theme, BUILTIN_THEMES};
 use util::short_hash;
 
 #[derive(Clone)]
@@ -28,7 +49,16 @@ struct RuntimeConfig {
     max: usize,
     all: bool,
     exclude_reachable_from: Option<String>,
-    colors_enabled: bool,
+    /// Revision range to start with, from `--range` or the config file.
+    initial_revisions: Option<Vec<String>>,
+    theme: Theme,
+    max_lanes: usize,
+    /// Remote whose forge commits open in; `None` picks one per repository.
+    forge_remote: Option<String>,
+    forge_hosts: ForgeHosts,
+    /// Starting layout from the config file; a saved layout only replaces
+    /// the one it was saved under.
+    config_layout: Option<LayoutMode>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -36,13 +66,21 @@ enum AppAction {
     None,
     EnsureInspect,
     Reload,
+    ApplyRevisions,
     CopyHash,
     OpenCommit,
+    /// Open the message link with this number, counted from 1.
+    OpenReference(usize),
+    /// Load line counts for the author statistics.
+    LoadLineStats,
     Quit,
 }
 
+const DEFAULT_MAX_COMMITS: usize = 200;
+const DEFAULT_MAX_LANES: usize = 16;
 const PAGE_SCROLL_LINES: i16 = 12;
 const HALF_PAGE_SCROLL_LINES: i16 = 6;
+const MOUSE_SCROLL_LINES: i16 = 3;
 
 fn main() -> Result<()> {
     let cli = Cli::parse();
@@ -60,15 +98,118 @@ fn main() -> Result<()> {
         )
     })?;
 
+    let config =
+        Config::load(git::worktree_root(&repo_path).as_deref()).context("Invalid configuration")?;
+
+    // CLI flags override the config file, which overrides built-in defaults.
+    let range = cli.range.or(config.range.clone());
+    let initial_revisions = match range {
+        Some(range) => {
+            let revisions = git::parse_revision_args(&range)
+                .and_then(|revisions| {
+                    git::validate_revisions(&repo_path, &revisions)?;
+                    Ok(revisions)
+                })
+                .with_context(|| format!("Invalid revision range '{}'", range))?;
+            Some(revisions).filter(|revisions| !revisions.is_empty())
+        }
+        None => None,
+    };
+
+    let export_args = cli.command.as_ref().map(|Commands::Export(args)| args);
+    let export_format = export_args.and_then(|args| ExportFormat::from_name(&args.format));
+
+    let no_color = cli.no_color
+        || config.color == Some(false)
+        || std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+    let theme = if no_color {
+        Theme::monochrome()
+    } else {
+        let theme = match &cli.theme {
+            Some(name) => config
+                .theme_named(name)
+                .context("Invalid configuration")?
+                .with_context(|| {
+                    format!(
+                        "Unknown theme '{}' (built-in themes: {})",
+                        name,
+                        BUILTIN_THEMES.join(", ")
+                    )
+                })?,
+            None => config.theme().context("Invalid configuration")?,
+        };
+        // HTML and SVG are viewed in browsers, which show every colour.
+        let color_mode = match export_format {
+            Some(ExportFormat::Html | ExportFormat::Svg) => ColorMode::TrueColor,
+            _ => config.color_mode.unwrap_or_else(ColorMode::detect),
+        };
+        theme.with_color_mode(color_mode)
+    };
+
     let runtime = RuntimeConfig {
         repo_path,
-        max: cli.max,
-        all: cli.all,
-        exclude_reachable_from: cli.exclude_reachable_from,
-        colors_enabled: !cli.no_color,
+        max: cli.max.or(config.max).unwrap_or(DEFAULT_MAX_COMMITS),
+        all: cli.all.or(config.all).unwrap_or(true),
+        exclude_reachable_from: cli
+            .exclude_reachable_from
+            .or(config.exclude_reachable_from.clone()),
+        initial_revisions,
+        theme,
+        max_lanes: cli
+            .max_lanes
+            .or(config.max_lanes)
+            .unwrap_or(DEFAULT_MAX_LANES),
+        forge_remote: cli.remote.or(config.forge_remote.clone()),
+        forge_hosts: config.forge_hosts.clone(),
+        config_layout: config.layout,
     };
 
-    let app = load_app(&runtime)?;
+    let graph_style = GraphStyle {
+        glyphs: cli
+            .graph_glyphs
+            .as_deref()
+            .and_then(GraphGlyphs::from_name)
+            .or(config.graph_glyphs)
+            .unwrap_or_default(),
+        spacing: cli
+            .graph_spacing
+            .as_deref()
+            .and_then(GraphSpacing::from_name)
+            .or(config.graph_spacing)
+            .unwrap_or_default(),
+    };
+
+    if let Some(style) = cli.json.as_deref().and_then(JsonStyle::from_name) {
+        let app = load_app(&runtime, false)?;
+        let result = json::write_json(
+            &mut stdout().lock(),
+            &app.commits,
+            &app.refs,
+            &app.graph,
+            style,
+        );
+        return finish_output(result);
+    }
+    if let Some(args) = export_args {
+        return export_graph(&runtime, graph_style, args);
+    }
+
+    eprintln!("Loading commits from {} …", runtime.repo_path.display());
+    let mut app = load_app(&runtime, false)?;
+    if let Some(layout) = runtime.config_layout {
+        app.layout_mode = layout;
+    }
+    if let Some(prefs) = Preferences::load() {
+        prefs.apply(&mut app, runtime.config_layout);
+    }
+    app.graph_style = graph_style;
+    app.keymap = config.keymap();
+    let forge = git::remote_forge(
+        &runtime.repo_path,
+        runtime.forge_remote.as_deref(),
+        &runtime.forge_hosts,
+    );
+    app.references = ReferenceFinder::new(forge, config.reference_patterns.clone());
 
     enable_raw_mode().context("Failed to enable raw mode")?;
     let mut stdout = stdout();
@@ -96,64 +237,166 @@ fn run_app(
     terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
     mut app: App,
     runtime: &RuntimeConfig,
+) -> Result<()> {
+    let result = event_loop(terminal, &mut app, runtime);
+    Preferences::from_app(&app, runtime.config_layout)
+        .save()
+        .ok();
+    result
+}
+
+fn event_loop(
+    terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    app: &mut App,
+    runtime: &RuntimeConfig,
 ) -> Result<()> {
     loop {
         app.clear_expired_status();
-        terminal.draw(|frame| ui::view::render(frame, &mut app))?;
+        terminal.draw(|frame| ui::view::render(frame, app))?;
 
-        if event::poll(Duration::from_millis(150))? {
+        let action = if event::poll(Duration::from_millis(150))? {
             match event::read()? {
-                Event::Key(key) if key.kind == KeyEventKind::Press => {
-                    match handle_key(&mut app, key) {
-                        AppAction::None => {}
-                        AppAction::EnsureInspect => ensure_selected_inspect(&mut app, runtime),
-                        AppAction::Reload => {
-                            if let Err(err) = reload_app(&mut app, runtime) {
-                                app.set_status(format!("Reload failed: {}", err));
-                            }
-                        }
-                        AppAction::CopyHash => copy_selected_hash(&mut app),
-                        AppAction::OpenCommit => open_selected_commit(&mut app, runtime),
-                        AppAction::Quit => break,
-                    }
+                Event::Key(key) if key.kind == KeyEventKind::Press => handle_key(app, key),
+                Event::Mouse(mouse) => handle_mouse(app, mouse),
+                _ => AppAction::None,
+            }
+        } else {
+            let actions = app.keymap.expire(&mut app.pending_keys, SEQUENCE_TIMEOUT);
+            run_key_actions(app, actions)
+        };
+
+        match action {
+            AppAction::None => {}
+            AppAction::EnsureInspect => ensure_selected_inspect(app, runtime),
+            AppAction::Reload => {
+                if let Err(err) = reload_app(app, runtime) {
+                    app.set_status(format!("Reload failed: {}", err));
                 }
-                Event::Resize(_, _) => {}
-                _ => {}
             }
+            AppAction::ApplyRevisions => apply_revision_range(app, runtime),
+            AppAction::CopyHash => copy_selected_hash(app),
+            AppAction::OpenCommit => open_selected_commit(app, runtime),
+            AppAction::OpenReference(number) => open_selected_reference(app, number),
+            AppAction::LoadLineStats => load_missing_line_stats(terminal, app, runtime)?,
+            AppAction::Quit => break,
         }
     }
     Ok(())
 }
 
-fn load_app(runtime: &RuntimeConfig) -> Result<App> {
-    eprintln!("Loading commits from {} …", runtime.repo_path.display());
+fn load_app(runtime: &RuntimeConfig, first_parent: bool) -> Result<App> {
+    let mut app = App::new(
+        Vec::new(),
+        Refs::default(),
+        Vec::new(),
+        runtime.theme.clone(),
+    );
+    app.revisions = runtime.initial_revisions.clone();
+    app.all_refs = runtime.all;
+    app.layout_options.max_lanes = Some(runtime.max_lanes);
+    app.layout_options.first_parent = first_parent;
+    let (commits, refs) = load_history(&app, runtime)?;
+    app.replace_data(commits, refs);
+    Ok(app)
+}
 
-    let commits = git::load_commits(
-        &runtime.repo_path,
-        runtime.max,
-        runtime.all,
-        runtime.exclude_reachable_from.as_deref(),
-    )
-    .context("Failed to load commits")?;
-    let refs = git::load_refs(&runtime.repo_path).context("Failed to load refs")?;
-    let graph = graph::compute_layout(&commits);
+/// `gitviz export`: render the graph once to stdout.
+fn export_graph(runtime: &RuntimeConfig, graph_style: GraphStyle, args: &ExportArgs) -> Result<()> {
+    let app = load_app(runtime, args.first_parent)?;
+    let options = ExportOptions {
+        format: ExportFormat::from_name(&args.format).unwrap_or(ExportFormat::Text),
+        theme: runtime.theme.clone(),
+        graph_style,
+        background: PageBackground::from_name(&args.background).unwrap_or_default(),
+    };
+    let output = export::export(&app.commits, &app.refs, &app.graph, &options);
+    finish_output(stdout().lock().write_all(output.as_bytes()))
+}
 
-    Ok(App::new(commits, refs, graph, runtime.colors_enabled))
+/// Treat a closed pipe (`gitviz export | head`) as success.
+fn finish_output(result: io::Result<()>) -> Result<()> {
+    match result {
+        Err(err) if err.kind() != io::ErrorKind::BrokenPipe => {
+            Err(err).context("Failed to write output")
+        }
+        _ => Ok(()),
+    }
 }
 
 fn reload_app(app: &mut App, runtime: &RuntimeConfig) -> Result<()> {
+    let (commits, refs) = load_history(app, runtime)?;
+    app.replace_data(commits, refs);
+    app.set_status("Repository reloaded");
+    Ok(())
+}
+
+/// Load refs and the commits selected by the app's current revision set.
+fn load_history(app: &App, runtime: &RuntimeConfig) -> Result<(Vec<Commit>, Refs)> {
+    let refs = git::load_refs(&runtime.repo_path).context("Failed to load refs")?;
+    // A runtime range replaces the session's revision boundary as well.
+    let exclude = match app.revisions {
+        Some(_) => None,
+        None => runtime.exclude_reachable_from.as_deref(),
+    };
     let commits = git::load_commits(
         &runtime.repo_path,
         runtime.max,
-        runtime.all,
-        runtime.exclude_reachable_from.as_deref(),
+        &log_revisions(app, &refs, runtime),
+        exclude,
     )
-    .context("Failed to reload commits")?;
-    let refs = git::load_refs(&runtime.repo_path).context("Failed to reload refs")?;
-    let graph = graph::compute_layout(&commits);
-    app.replace_data(commits, refs, graph);
-    app.set_status("Repository reloaded");
-    Ok(())
+    .context("Failed to load commits")?;
+    Ok((commits, refs))
+}
+
+/// Revisions to load: a runtime range if one is set, otherwise the session
+/// default, leaving out refs the sidebar hides when that default is `--all`.
+fn log_revisions(app: &App, refs: &Refs, runtime: &RuntimeConfig) -> Vec<String> {
+    if let Some(revisions) = &app.revisions {
+        revisions.clone()
+    } else if app.hidden_refs.is_empty() || !app.can_hide_refs() {
+        git::default_revisions(runtime.all)
+    } else {
+        refs.visible_revisions(&app.hidden_refs)
+    }
+}
+
+fn apply_revision_range(app: &mut App, runtime: &RuntimeConfig) {
+    let input = app.confirm_revision_input();
+    let revisions = match git::parse_revision_args(&input) {
+        Ok(revisions) => revisions,
+        Err(err) => {
+            app.set_status(format!("Range rejected: {}", err));
+            return;
+        }
+    };
+
+    if !revisions.is_empty() {
+        if let Err(err) = git::validate_revisions(&runtime.repo_path, &revisions) {
+            app.set_status(format!("Range rejected: {}", err));
+            return;
+        }
+    }
+
+    let previous = app.revisions.take();
+    app.revisions = if revisions.is_empty() {
+        None
+    } else {
+        Some(revisions)
+    };
+    match reload_app(app, runtime) {
+        Ok(()) => {
+            app.remember_revision_range(&input);
+            if input.is_empty() {
+                app.set_status("Revision range reset to session default");
+            } else {
+                app.set_status(format!("Showing {}", input));
+            }
+        }
+        Err(err) => {
+            app.revisions = previous;
+            app.set_status(format!("Reload failed: {}", err));
+        }
+    }
 }
 
 fn ensure_selected_inspect(app: &mut App, runtime: &RuntimeConfig) {
@@ -171,75 +414,287 @@ fn ensure_selected_inspect(app: &mut App, runtime: &RuntimeConfig) {
     }
 }
 
+/// Load the line counts the statistics panel still lacks, a batch at a time
+/// with progress in the status bar.
+fn load_missing_line_stats(
+    terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    app: &mut App,
+    runtime: &RuntimeConfig,
+) -> Result<()> {
+    let oids = app.missing_line_stats();
+    if oids.is_empty() {
+        return Ok(());
+    }
+
+    for (index, batch) in oids.chunks(git::LINE_STATS_BATCH).enumerate() {
+        app.set_status(format!(
+            "Loading line counts… {}/{} commits",
+            index * git::LINE_STATS_BATCH,
+            oids.len()
+        ));
+        terminal.draw(|frame| ui::view::render(frame, app))?;
+        match git::load_line_stats(&runtime.repo_path, batch) {
+            Ok(stats) => app.line_stats.extend(stats),
+            Err(err) => {
+                app.set_status(format!("Line counts unavailable: {}", err));
+                return Ok(());
+            }
+        }
+    }
+    app.set_status(format!("Loaded line counts for {} commits", oids.len()));
+    Ok(())
+}
+
 fn handle_key(app: &mut App, key: KeyEvent) -> AppAction {
     if app.help_open {
         return handle_help_key(app, key);
     }
 
+    if app.branches_open {
+        return handle_branches_key(app, key);
+    }
+
+    if app.stats_open {
+        return handle_stats_key(app, key);
+    }
+
+    if app.sidebar_focused && app.mode == Mode::Normal {
+        return handle_sidebar_key(app, key);
+    }
+
     match app.mode {
         Mode::Normal => handle_normal(app, key),
         Mode::Filter => handle_filter(app, key),
+        Mode::Revision => handle_revision(app, key),
     }
 }
 
 fn handle_help_key(app: &mut App, key: KeyEvent) -> AppAction {
-    match key.code {
-        KeyCode::Char('?') | KeyCode::Esc | KeyCode::Char('q') => app.close_help(),
-        _ => {}
+    if key.code == KeyCode::Esc && app.pending_keys.is_empty() {
+        app.close_help();
+        return AppAction::None;
     }
-    AppAction::None
+    feed_keymap(app, key)
+}
+
+fn handle_branches_key(app: &mut App, key: KeyEvent) -> AppAction {
+    if key.code == KeyCode::Esc && app.pending_keys.is_empty() {
+        app.close_branches();
+        return AppAction::None;
+    }
+    feed_keymap(app, key)
+}
+
+fn handle_stats_key(app: &mut App, key: KeyEvent) -> AppAction {
+    if key.code == KeyCode::Esc && app.pending_keys.is_empty() {
+        app.close_stats();
+        return AppAction::None;
+    }
+    feed_keymap(app, key)
+}
+
+/// The sidebar takes its keys from the keymap too; only Space (show or hide
+/// a ref) and Esc (back to the graph) are its own.
+fn handle_sidebar_key(app: &mut App, key: KeyEvent) -> AppAction {
+    if app.pending_keys.is_empty() {
+        match key.code {
+            KeyCode::Char(' ') if key.modifiers.is_empty() => {
+                if app.toggle_selected_ref_visibility() {
+                    return AppAction::Reload;
+                }
+                return AppAction::None;
+            }
+            KeyCode::Esc => {
+                app.unfocus_sidebar();
+                return AppAction::None;
+            }
+            _ => {}
+        }
+    }
+    feed_keymap(app, key)
 }
 
 fn handle_normal(app: &mut App, key: KeyEvent) -> AppAction {
-    match key.code {
-        KeyCode::Char('?') => {
-            app.toggle_help();
-            AppAction::None
+    feed_keymap(app, key)
+}
+
+fn feed_keymap(app: &mut App, key: KeyEvent) -> AppAction {
+    let actions = app.keymap.feed(&mut app.pending_keys, key);
+    run_key_actions(app, actions)
+}
+
+/// Run keymap actions in whichever panel has focus.
+fn run_key_actions(app: &mut App, actions: Vec<(Action, Option<usize>)>) -> AppAction {
+    let mut result = AppAction::None;
+    for (action, count) in actions {
+        let outcome = if app.help_open {
+            run_help_action(app, action, count)
+        } else if app.branches_open {
+            run_branches_action(app, action, count)
+        } else if app.stats_open {
+            run_stats_action(app, action, count)
+        } else if app.sidebar_focused {
+            run_sidebar_action(app, action, count)
+        } else {
+            run_counted_action(app, action, count)
+        };
+        if outcome != AppAction::None {
+            result = outcome;
         }
-        KeyCode::Char('j') | KeyCode::Down => selection_action(app.move_down(), app),
-        KeyCode::Char('k') | KeyCode::Up => selection_action(app.move_up(), app),
-        KeyCode::Char('g') | KeyCode::Home => selection_action(app.move_to_top(), app),
-        KeyCode::Char('G') | KeyCode::End => selection_action(app.move_to_bottom(), app),
-        KeyCode::Char('n') => selection_action(app.search_next(), app),
-        KeyCode::Char('N') => selection_action(app.search_previous(), app),
-        KeyCode::Enter => {
-            app.toggle_details();
-            AppAction::None
+    }
+    result
+}
+
+/// Lines a movement or paging action scrolls a panel by.
+fn panel_scroll(action: Action, count: Option<usize>) -> Option<i16> {
+    let count = count.unwrap_or(1).min(i16::MAX as usize) as i16;
+    match action {
+        Action::MoveDown => Some(count),
+        Action::MoveUp => Some(-count),
+        Action::HalfPageDown => Some(HALF_PAGE_SCROLL_LINES),
+        Action::HalfPageUp => Some(-HALF_PAGE_SCROLL_LINES),
+        Action::ScrollDetailsDown => Some(PAGE_SCROLL_LINES),
+        Action::ScrollDetailsUp => Some(-PAGE_SCROLL_LINES),
+        _ => None,
+    }
+}
+
+/// The keys bound to `toggle_help` or `quit` close the help overlay;
+/// movement and paging keys scroll it.
+fn run_help_action(app: &mut App, action: Action, count: Option<usize>) -> AppAction {
+    match action {
+        Action::ToggleHelp | Action::Quit => app.close_help(),
+        action => {
+            if let Some(lines) = panel_scroll(action, count) {
+                app.scroll_help_lines(lines);
+            }
         }
-        KeyCode::Tab => {
-            app.cycle_tab_forward();
-            tab_action(app)
+    }
+    AppAction::None
+}
+
+fn run_branches_action(app: &mut App, action: Action, count: Option<usize>) -> AppAction {
+    match action {
+        Action::ToggleBranches | Action::Quit => app.close_branches(),
+        action => {
+            if let Some(lines) = panel_scroll(action, count) {
+                app.scroll_branches_lines(lines);
+            }
         }
-        KeyCode::BackTab => {
-            app.cycle_tab_backward();
-            tab_action(app)
+    }
+    AppAction::None
+}
+
+fn run_stats_action(app: &mut App, action: Action, count: Option<usize>) -> AppAction {
+    match action {
+        Action::ToggleStats | Action::Quit => app.close_stats(),
+        action => {
+            if let Some(lines) = panel_scroll(action, count) {
+                app.scroll_stats_lines(lines);
+            }
         }
-        KeyCode::PageDown => {
-            app.scroll_details_lines(PAGE_SCROLL_LINES);
-            AppAction::None
+    }
+    AppAction::None
+}
+
+/// In the sidebar, `toggle_details` jumps to the selected ref's tip.
+fn run_sidebar_action(app: &mut App, action: Action, count: Option<usize>) -> AppAction {
+    match action {
+        Action::ToggleDetails => match app.jump_to_selected_ref() {
+            Some(changed) => {
+                app.unfocus_sidebar();
+                return selection_action(changed, app);
+            }
+            None => app.set_status("Ref tip is not in the loaded history"),
+        },
+        Action::ToggleSidebar => app.toggle_sidebar(),
+        Action::ToggleHelp => app.toggle_help(),
+        Action::Quit => return AppAction::Quit,
+        action => {
+            if let Some(lines) = panel_scroll(action, count) {
+                app.sidebar_move(lines as isize);
+            }
         }
-        KeyCode::PageUp => {
-            app.scroll_details_lines(-PAGE_SCROLL_LINES);
-            AppAction::None
+    }
+    AppAction::None
+}
+
+/// Run an action with an optional count prefix: `10j` repeats, `5G` jumps to row 5.
+fn run_counted_action(app: &mut App, action: Action, count: Option<usize>) -> AppAction {
+    match (action, count) {
+        (Action::MoveToTop | Action::MoveToBottom, Some(row)) => {
+            let changed = app.select_index(
+                row.saturating_sub(1)
+                    .min(app.filtered.len().saturating_sub(1)),
+            );
+            selection_action(changed, app)
         }
-        KeyCode::Char('d') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-            app.scroll_details_lines(HALF_PAGE_SCROLL_LINES);
-            AppAction::None
+        (Action::OpenReference, count) => AppAction::OpenReference(count.unwrap_or(1)),
+        (action, Some(count)) if action.repeatable() => {
+            let mut result = AppAction::None;
+            for _ in 0..count {
+                let outcome = run_action(app, action);
+                if outcome != AppAction::None {
+                    result = outcome;
+                }
+            }
+            result
         }
-        KeyCode::Char('u') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-            app.scroll_details_lines(-HALF_PAGE_SCROLL_LINES);
-            AppAction::None
+        (action, _) => run_action(app, action),
+    }
+}
+
+fn run_action(app: &mut App, action: Action) -> AppAction {
+    match action {
+        Action::MoveDown => return selection_action(app.move_down(), app),
+        Action::MoveUp => return selection_action(app.move_up(), app),
+        Action::MoveToTop => return selection_action(app.move_to_top(), app),
+        Action::MoveToBottom => return selection_action(app.move_to_bottom(), app),
+        Action::SearchNext => return selection_action(app.search_next(), app),
+        Action::SearchPrevious => return selection_action(app.search_previous(), app),
+        Action::ToggleDetails => app.toggle_details(),
+        Action::ToggleFullscreen => app.toggle_details_fullscreen(),
+        Action::CycleLayout => {
+            app.cycle_layout_mode();
+            app.set_status(format!("Layout: {}", app.layout_mode.name()));
         }
-        KeyCode::Char('/') => {
-            app.enter_filter_mode();
-            AppAction::None
+        Action::GrowDetails => app.resize_details(1),
+        Action::ShrinkDetails => app.resize_details(-1),
+        Action::NextTab => {
+            app.cycle_tab_forward();
+            return tab_action(app);
         }
-        KeyCode::Char('r') => AppAction::Reload,
-        KeyCode::Char('y') => AppAction::CopyHash,
-        KeyCode::Char('o') => AppAction::OpenCommit,
-        KeyCode::Char('q') => AppAction::Quit,
-        _ => AppAction::None,
+        Action::PreviousTab => {
+            app.cycle_tab_backward();
+            return tab_action(app);
+        }
+        Action::ScrollDetailsDown => app.scroll_details_lines(PAGE_SCROLL_LINES),
+        Action::ScrollDetailsUp => app.scroll_details_lines(-PAGE_SCROLL_LINES),
+        Action::HalfPageDown => app.scroll_details_lines(HALF_PAGE_SCROLL_LINES),
+        Action::HalfPageUp => app.scroll_details_lines(-HALF_PAGE_SCROLL_LINES),
+        Action::Filter => app.enter_filter_mode(),
+        Action::RevisionRange => app.enter_revision_mode(),
+        Action::Reload => return AppAction::Reload,
+        Action::CopyHash => return AppAction::CopyHash,
+        Action::OpenCommit => return AppAction::OpenCommit,
+        Action::OpenReference => return AppAction::OpenReference(1),
+        Action::ToggleFirstParent => app.toggle_first_parent(),
+        Action::ToggleMerge => {
+            app.toggle_selected_merge();
+        }
+        Action::ToggleRawIdentity => app.toggle_raw_identity(),
+        Action::ToggleBranches => app.toggle_branches(),
+        Action::ToggleStats => {
+            app.toggle_stats();
+            if app.stats_open {
+                return AppAction::LoadLineStats;
+            }
+        }
+        Action::ToggleSidebar => app.toggle_sidebar(),
+        Action::ToggleHelp => app.toggle_help(),
+        Action::Quit => return AppAction::Quit,
     }
+    AppAction::None
 }
 
 fn selection_action(changed: bool, app: &App) -> AppAction {
@@ -284,6 +739,129 @@ fn handle_filter(app: &mut App, key: KeyEvent) -> AppAction {
     }
 }
 
+fn handle_revision(app: &mut App, key: KeyEvent) -> AppAction {
+    match key.code {
+        KeyCode::Esc => app.exit_revision_mode(),
+        KeyCode::Enter => return AppAction::ApplyRevisions,
+        KeyCode::Backspace => app.revision_pop(),
+        KeyCode::Up => app.revision_history_previous(),
+        KeyCode::Down => app.revision_history_next(),
+        KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => app.revision_push(c),
+        _ => {}
+    }
+    AppAction::None
+}
+
+fn handle_mouse(app: &mut App, mouse: MouseEvent) -> AppAction {
+    if app.help_open {
+        return AppAction::None;
+    }
+
+    if app.branches_open {
+        match mouse.kind {
+            MouseEventKind::ScrollDown => app.scroll_branches_lines(MOUSE_SCROLL_LINES),
+            MouseEventKind::ScrollUp => app.scroll_branches_lines(-MOUSE_SCROLL_LINES),
+            _ => {}
+        }
+        return AppAction::None;
+    }
+
+    if app.stats_open {
+        match mouse.kind {
+            MouseEventKind::ScrollDown => app.scroll_stats_lines(MOUSE_SCROLL_LINES),
+            MouseEventKind::ScrollUp => app.scroll_stats_lines(-MOUSE_SCROLL_LINES),
+            _ => {}
+        }
+        return AppAction::None;
+    }
+
+    let position = Position::new(mouse.column, mouse.row);
+    let layout = app.layout;
+
+    match mouse.kind {
+        MouseEventKind::Down(MouseButton::Left) => {
+            let on_split = if layout.side_by_side {
+                mouse.column == layout.details.x
+            } else {
+                mouse.row == layout.details.y
+            };
+            if app.details_expanded
+                && !app.details_fullscreen
+                && on_split
+                && layout.details.contains(position)
+            {
+                app.drag = Some(DragTarget::DetailsSplit);
+                return AppAction::None;
+            }
+
+            if layout.sidebar.contains(position) {
+                if let Some(row) = list_row(layout.sidebar, mouse.row) {
+                    app.sidebar_focused = true;
+                    app.sidebar_select(app.sidebar_offset + row);
+                }
+            } else if layout.graph.contains(position) {
+                app.unfocus_sidebar();
+                if let Some(row) = list_row(layout.graph, mouse.row) {
+                    return selection_action(app.select_index(app.graph_index_at_line(row)), app);
+                }
+            } else if layout.details.contains(position) {
+                app.unfocus_sidebar();
+                // The tab line is the first content row until the details scroll.
+                if app.details_scroll == 0 && mouse.row == layout.details.y + 1 {
+                    let column = mouse.column.saturating_sub(layout.details.x + 1);
+                    if let Some(tab) = widgets::tab_at_column(column) {
+                        if app.select_tab(tab) {
+                            return tab_action(app);
+                        }
+                    }
+                }
+            }
+        }
+        MouseEventKind::Drag(MouseButton::Left) if app.drag == Some(DragTarget::DetailsSplit) => {
+            if layout.side_by_side {
+                let bounds = Rect {
+                    width: layout.details.x + layout.details.width - layout.graph.x,
+                    ..layout.graph
+                };
+                app.drag_details_split_horizontal(mouse.column, bounds);
+            } else {
+                let bounds = Rect {
+                    height: layout.details.y + layout.details.height - layout.graph.y,
+                    ..layout.graph
+                };
+                app.drag_details_split(mouse.row, bounds);
+            }
+        }
+        MouseEventKind::Up(MouseButton::Left) => app.drag = None,
+        MouseEventKind::ScrollDown | MouseEventKind::ScrollUp => {
+            let amount = if mouse.kind == MouseEventKind::ScrollDown {
+                MOUSE_SCROLL_LINES
+            } else {
+                -MOUSE_SCROLL_LINES
+            };
+            if layout.sidebar.contains(position) {
+                app.sidebar_move(amount as isize);
+            } else if layout.graph.contains(position) {
+                return selection_action(app.move_by(amount as isize), app);
+            } else if layout.details.contains(position) {
+                app.scroll_details_lines(amount);
+            }
+        }
+        _ => {}
+    }
+
+    AppAction::None
+}
+
+/// Row index inside a bordered list, or `None` on the border.
+fn list_row(area: Rect, row: u16) -> Option<usize> {
+    if row > area.y && row + 1 < area.y + area.height {
+        Some((row - area.y - 1) as usize)
+    } else {
+        None
+    }
+}
+
 fn copy_selected_hash(app: &mut App) {
     let Some(commit) = app.selected_commit() else {
         app.set_status("No commit selected");
@@ -302,13 +880,45 @@ fn open_selected_commit(app: &mut App, runtime: &RuntimeConfig) {
         return;
     };
 
-    let Some(url) = git::github_commit_url(&runtime.repo_path, &commit.oid) else {
-        app.set_status("Open unavailable: origin is not a supported GitHub remote");
+    let remote = runtime.forge_remote.as_deref();
+    let Some(forge) = git::remote_forge(&runtime.repo_path, remote, &runtime.forge_hosts) else {
+        app.set_status(
+            "Open unavailable: no remote on a known forge (see [forge.hosts] for self-hosted ones)",
+        );
         return;
     };
 
-    match open_url(&url) {
-        Ok(()) => app.set_status(format!("Opened {} in GitHub", short_hash(&commit.oid))),
+    match open_url(&forge.commit_url(&commit.oid)) {
+        Ok(()) => app.set_status(format!(
+            "Opened {} in {}",
+            short_hash(&commit.oid),
+            forge.kind().title()
+        )),
+        Err(_) => app.set_status("Open failed: no supported browser command found"),
+    }
+}
+
+/// Open one of the links listed under the selected commit's summary.
+fn open_selected_reference(app: &mut App, number: usize) {
+    let Some(commit) = app.selected_commit() else {
+        app.set_status("No commit selected");
+        return;
+    };
+
+    let links = app.references.commit_links(commit);
+    let Some((text, url)) = links.get(number.saturating_sub(1)) else {
+        let status = match links.len() {
+            0 => {
+                "No links in this commit message (see [[references]] for tracker keys)".to_string()
+            }
+            len => format!("No link {}: this commit message has {}", number, len),
+        };
+        app.set_status(status);
+        return;
+    };
+
+    match open_url(url) {
+        Ok(()) => app.set_status(format!("Opened {} ({} of {})", text, number, links.len())),
         Err(_) => app.set_status("Open failed: no supported browser command found"),
     }
 }
@@ -343,19 +953,16 @@ fn copy_to_clipboard(text: &str) -> Result<()> {
 
 fn open_url(url: &str) -> Result<()> {
     #[cfg(target_os = "macos")]
-    {
-        run_quiet_command("open", &[url])?;
-        return Ok(());
-    }
+    let opener = "open";
 
     #[cfg(target_os = "linux")]
-    {
-        run_quiet_command("xdg-open", &[url])?;
-        return Ok(());
-    }
+    let opener = "xdg-open";
 
     #[cfg(not(any(target_os = "macos", target_os = "linux")))]
-    anyhow::bail!("browser open unsupported on this platform")
+    anyhow::bail!("browser open unsupported on this platform");
+
+    #[cfg(any(target_os = "macos", target_os = "linux"))]
+    run_quiet_command(opener, &[url])
 }
 
 fn run_quiet_command(command: &str, args: &[&str]) -> Result<()> {
@@ -396,7 +1003,59 @@ fn pipe_to_command(command: &str, args: &[&str], input: &str) -> Result<()> {
 
 #[cfg(test)]
 mod tests {
-    use super::run_quiet_command;
+    use super::*;
+    use crossterm::event::KeyModifiers;
+    use keymap::parse_sequence;
+
+    #[test]
+    fn stats_panel_closes_with_bound_keys() {
+        let mut app = App::new(Vec::new(), Refs::default(), Vec::new(), Theme::default());
+        app.keymap
+            .bind(Action::ToggleStats, &[parse_sequence("S").unwrap()]);
+        let press = |c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE);
+
+        app.toggle_stats();
+        handle_key(&mut app, press('A'));
+        assert!(app.stats_open);
+        handle_key(&mut app, press('S'));
+        assert!(!app.stats_open);
+
+        app.toggle_stats();
+        handle_key(&mut app, press('q'));
+        assert!(!app.stats_open);
+    }
+
+    #[test]
+    fn overlays_and_sidebar_follow_remapped_keys() {
+        let mut app = App::new(Vec::new(), Refs::default(), Vec::new(), Theme::default());
+        app.keymap
+            .bind(Action::Quit, &[parse_sequence("Q").unwrap()]);
+        app.keymap
+            .bind(Action::ToggleSidebar, &[parse_sequence("S").unwrap()]);
+        app.keymap
+            .bind(Action::ToggleBranches, &[parse_sequence("B").unwrap()]);
+        let press = |c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE);
+
+        app.toggle_help();
+        handle_key(&mut app, press('q'));
+        assert!(app.help_open);
+        handle_key(&mut app, press('Q'));
+        assert!(!app.help_open);
+
+        app.toggle_branches();
+        handle_key(&mut app, press('b'));
+        assert!(app.branches_open);
+        handle_key(&mut app, press('B'));
+        assert!(!app.branches_open);
+
+        app.toggle_sidebar();
+        assert!(app.sidebar_focused);
+        assert_eq!(handle_key(&mut app, press('q')), AppAction::None);
+        handle_key(&mut app, press('S'));
+        assert!(!app.sidebar_open);
+        app.toggle_sidebar();
+        assert_eq!(handle_key(&mut app, press('Q')), AppAction::Quit);
+    }
 
     #[test]
     fn run_quiet_command_returns_ok_on_zero_exit() {
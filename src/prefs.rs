@@ -0,0 +1,227 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+use crate::app::{
+    App, LayoutMode, DEFAULT_DETAILS_HEIGHT, DEFAULT_DETAILS_WIDTH_PERCENT,
+    MAX_DETAILS_WIDTH_PERCENT, MIN_DETAILS_HEIGHT, MIN_DETAILS_WIDTH_PERCENT,
+};
+
+/// Layout preferences remembered between sessions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Preferences {
+    pub layout_mode: LayoutMode,
+    /// The config file layout in effect when `layout_mode` was saved.
+    pub config_layout: Option<LayoutMode>,
+    pub details_expanded: bool,
+    pub details_fullscreen: bool,
+    pub details_height: u16,
+    pub details_width_percent: u16,
+}
+
+/// The preferences file as written by [`Preferences::serialize`]; missing
+/// keys keep their defaults.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct PreferencesFile {
+    layout: Option<String>,
+    config_layout: Option<String>,
+    details_expanded: Option<bool>,
+    details_fullscreen: Option<bool>,
+    details_height: Option<u16>,
+    details_width_percent: Option<u16>,
+}
+
+impl Default for Preferences {
+    fn default() -> Self {
+        Preferences {
+            layout_mode: LayoutMode::Auto,
+            config_layout: None,
+            details_expanded: true,
+            details_fullscreen: false,
+            details_height: DEFAULT_DETAILS_HEIGHT,
+            details_width_percent: DEFAULT_DETAILS_WIDTH_PERCENT,
+        }
+    }
+}
+
+impl Preferences {
+    pub fn from_app(app: &App, config_layout: Option<LayoutMode>) -> Self {
+        Preferences {
+            layout_mode: app.layout_mode,
+            config_layout,
+            details_expanded: app.details_expanded,
+            details_fullscreen: app.details_fullscreen,
+            details_height: app.details_height,
+            details_width_percent: app.details_width_percent,
+        }
+    }
+
+    /// Restore the saved layout, with pane sizes held to the range the
+    /// resize keys allow. The saved layout mode is dropped once the config
+    /// file layout differs from the one it was saved under, so editing the
+    /// config takes effect.
+    pub fn apply(&self, app: &mut App, config_layout: Option<LayoutMode>) {
+        if self.config_layout == config_layout {
+            app.layout_mode = self.layout_mode;
+        }
+        app.details_expanded = self.details_expanded;
+        app.details_fullscreen = self.details_fullscreen;
+        app.details_height = self.details_height.max(MIN_DETAILS_HEIGHT);
+        app.details_width_percent = self
+            .details_width_percent
+            .clamp(MIN_DETAILS_WIDTH_PERCENT, MAX_DETAILS_WIDTH_PERCENT);
+    }
+
+    /// Parse the preferences file. The file is written by gitviz itself, so
+    /// unknown keys and an unknown layout are skipped, and a file that is not
+    /// valid TOML gives the defaults.
+    pub fn parse(contents: &str) -> Self {
+        let file: PreferencesFile = toml::from_str(contents).unwrap_or_default();
+        let defaults = Preferences::default();
+        Preferences {
+            layout_mode: file
+                .layout
+                .as_deref()
+                .and_then(LayoutMode::from_name)
+                .unwrap_or(defaults.layout_mode),
+            config_layout: file
+                .config_layout
+                .as_deref()
+                .and_then(LayoutMode::from_name),
+            details_expanded: file.details_expanded.unwrap_or(defaults.details_expanded),
+            details_fullscreen: file
+                .details_fullscreen
+                .unwrap_or(defaults.details_fullscreen),
+            details_height: file.details_height.unwrap_or(defaults.details_height),
+            details_width_percent: file
+                .details_width_percent
+                .unwrap_or(defaults.details_width_percent),
+        }
+    }
+
+    pub fn serialize(&self) -> String {
+        let config_layout = match self.config_layout {
+            Some(layout) => format!("config_layout = \"{}\"\n", layout.name()),
+            None => String::new(),
+        };
+        format!(
+            "# gitviz layout preferences (written on exit)\n\
+             layout = \"{}\"\n\
+             {}\
+             details_expanded = {}\n\
+             details_fullscreen = {}\n\
+             details_height = {}\n\
+             details_width_percent = {}\n",
+            self.layout_mode.name(),
+            config_layout,
+            self.details_expanded,
+            self.details_fullscreen,
+            self.details_height,
+            self.details_width_percent,
+        )
+    }
+
+    /// Load the saved preferences, or `None` when none were saved.
+    pub fn load() -> Option<Self> {
+        preferences_path()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .map(|contents| Preferences::parse(&contents))
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = preferences_path().context("no state directory (HOME is not set)")?;
+        write_file(&path, &self.serialize())
+    }
+}
+
+/// `$XDG_STATE_HOME/gitviz/preferences.toml`, or `~/.local/state/gitviz/...`.
+fn preferences_path() -> Option<PathBuf> {
+    let state_home = std::env::var_os("XDG_STATE_HOME")
+        .filter(|value| !value.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| {
+            std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local").join("state"))
+        })?;
+    Some(state_home.join("gitviz").join("preferences.toml"))
+}
+
+fn write_file(path: &Path, contents: &str) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("failed to create {}", parent.display()))?;
+    }
+    fs::write(path, contents).with_context(|| format!("failed to write {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_preferences_round_trip() {
+        let prefs = Preferences {
+            layout_mode: LayoutMode::SideBySide,
+            config_layout: Some(LayoutMode::Stacked),
+            details_expanded: false,
+            details_fullscreen: true,
+            details_height: 22,
+            details_width_percent: 60,
+        };
+        assert_eq!(Preferences::parse(&prefs.serialize()), prefs);
+        let prefs = Preferences {
+            config_layout: None,
+            ..prefs
+        };
+        assert_eq!(Preferences::parse(&prefs.serialize()), prefs);
+    }
+
+    #[test]
+    fn test_preferences_parse_skips_bad_values() {
+        let prefs =
+            Preferences::parse("layout = \"diagonal\"\ndetails_height = lots\nunknown = 1\n");
+        assert_eq!(prefs, Preferences::default());
+
+        let prefs = Preferences::parse("layout = \"diagonal\"\ndetails_height = 9\nunknown = 1\n");
+        assert_eq!(prefs.layout_mode, LayoutMode::Auto);
+        assert_eq!(prefs.details_height, 9);
+    }
+
+    #[test]
+    fn test_preferences_apply_clamps_pane_sizes() {
+        let prefs = Preferences::parse("details_height = 0\ndetails_width_percent = 99\n");
+        let mut app = App::new(
+            Vec::new(),
+            Default::default(),
+            Vec::new(),
+            Default::default(),
+        );
+        prefs.apply(&mut app, None);
+        assert_eq!(app.details_height, MIN_DETAILS_HEIGHT);
+        assert_eq!(app.details_width_percent, MAX_DETAILS_WIDTH_PERCENT);
+    }
+
+    #[test]
+    fn test_preferences_layout_yields_to_a_changed_config_layout() {
+        let prefs = Preferences::parse("layout = \"side-by-side\"\nconfig_layout = \"stacked\"\n");
+        let mut app = App::new(
+            Vec::new(),
+            Default::default(),
+            Vec::new(),
+            Default::default(),
+        );
+        app.layout_mode = LayoutMode::Stacked;
+        prefs.apply(&mut app, Some(LayoutMode::Stacked));
+        assert_eq!(app.layout_mode, LayoutMode::SideBySide);
+
+        // The config layout was edited since the layout was saved.
+        app.layout_mode = LayoutMode::Auto;
+        prefs.apply(&mut app, Some(LayoutMode::Auto));
+        assert_eq!(app.layout_mode, LayoutMode::Auto);
+        app.layout_mode = LayoutMode::Stacked;
+        prefs.apply(&mut app, None);
+        assert_eq!(app.layout_mode, LayoutMode::Stacked);
+    }
+}
@@ -0,0 +1,441 @@
+use std::ops::Range;
+use std::sync::OnceLock;
+
+use regex::{Captures, Regex};
+
+use crate::forge::{encode_path, Forge, ForgeKind};
+use crate::git::model::Commit;
+
+/// What a [`Reference`] points at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReferenceKind {
+    /// An issue or pull request: `#123`, `GH-123` or `owner/repo#123`.
+    Issue,
+    /// A key from an issue tracker such as `PROJ-42`, or a match of a
+    /// configured [`ReferencePattern`].
+    Ticket,
+    /// A commit named by a `Fixes:` trailer.
+    Commit,
+    /// The key of a `Fixes:`, `Closes:`, `Resolves:`, `Refs:` or
+    /// `Co-authored-by:` trailer.
+    Trailer,
+}
+
+/// A reference found in commit message text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reference {
+    /// Byte range of the reference in the searched text.
+    pub range: Range<usize>,
+    /// What the reference points at.
+    pub kind: ReferenceKind,
+    /// Web page of the reference, when the forge or a pattern can build one.
+    pub url: Option<String>,
+}
+
+/// A user-defined reference: a regular expression and the URL its matches
+/// open, where `$0` is the whole match and `$1`, `${name}` are its groups.
+#[derive(Debug, Clone)]
+pub struct ReferencePattern {
+    regex: Regex,
+    url: String,
+}
+
+/// Why a [`ReferencePattern`] was rejected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PatternError {
+    /// The regular expression does not compile; holds what is wrong with it.
+    Regex(String),
+    /// The URL does not start with `http://` or `https://`.
+    Url,
+}
+
+impl std::fmt::Display for PatternError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PatternError::Regex(reason) => write!(f, "{}", reason),
+            PatternError::Url => write!(f, "URL must start with http:// or https://"),
+        }
+    }
+}
+
+impl std::error::Error for PatternError {}
+
+impl ReferencePattern {
+    /// Compile `pattern`, opening matches at the expanded `url`, which must
+    /// be a web page.
+    pub fn new(pattern: &str, url: &str) -> Result<Self, PatternError> {
+        let regex = Regex::new(pattern).map_err(|err| {
+            // Regex errors quote the pattern over several lines; the last
+            // one says what is wrong.
+            let message = err.to_string();
+            let reason = message.lines().last().unwrap_or_default().trim();
+            PatternError::Regex(reason.strip_prefix("error: ").unwrap_or(reason).to_string())
+        })?;
+        let scheme = url
+            .split_once("://")
+            .map(|(scheme, _)| scheme.to_ascii_lowercase());
+        if !matches!(scheme.as_deref(), Some("http" | "https")) {
+            return Err(PatternError::Url);
+        }
+        Ok(ReferencePattern {
+            regex,
+            url: url.to_string(),
+        })
+    }
+
+    /// The URL for one match: `$0`, `$1`, `${name}` and so on replaced by
+    /// the percent-encoded group, `$$` by `$`. Like [`Captures::expand`],
+    /// an unbraced name runs as far as letters, digits and `_` go, and a
+    /// group that did not match expands to nothing.
+    fn expand(&self, captures: &Captures) -> String {
+        let mut url = String::with_capacity(self.url.len());
+        let mut rest = self.url.as_str();
+        while let Some(dollar) = rest.find('$') {
+            url.push_str(&rest[..dollar]);
+            rest = &rest[dollar + 1..];
+            if let Some(after) = rest.strip_prefix('$') {
+                url.push('$');
+                rest = after;
+                continue;
+            }
+            let (name, after) = match rest
+                .strip_prefix('{')
+                .and_then(|braced| braced.split_once('}'))
+            {
+                Some((name, after)) => (name, after),
+                None => {
+                    let end = rest
+                        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                        .unwrap_or(rest.len());
+                    (&rest[..end], &rest[end..])
+                }
+            };
+            if name.is_empty() {
+                url.push('$');
+                continue;
+            }
+            let group = match name.parse::<usize>() {
+                Ok(index) => captures.get(index),
+                Err(_) => captures.name(name),
+            };
+            if let Some(group) = group {
+                url.push_str(&encode_path(group.as_str()));
+            }
+            rest = after;
+        }
+        url.push_str(rest);
+        url
+    }
+}
+
+/// Finds issue, pull request and tracker references in commit messages and
+/// builds their links.
+///
+/// Configured patterns are tried first, so they can take over text the
+/// built-in rules would otherwise claim, such as a tracker whose keys look
+/// like `#123`.
+#[derive(Debug, Clone, Default)]
+pub struct ReferenceFinder {
+    forge: Option<Forge>,
+    patterns: Vec<ReferencePattern>,
+}
+
+impl ReferenceFinder {
+    /// Link issues through `forge` and tickets through `patterns`.
+    pub fn new(forge: Option<Forge>, patterns: Vec<ReferencePattern>) -> Self {
+        ReferenceFinder { forge, patterns }
+    }
+
+    /// References in `text` in order, without overlaps.
+    pub fn find(&self, text: &str) -> Vec<Reference> {
+        let mut found: Vec<Reference> = Vec::new();
+        let mut add = |range: Range<usize>, kind, url| {
+            if !found
+                .iter()
+                .any(|other| ranges_overlap(&other.range, &range))
+            {
+                found.push(Reference { range, kind, url });
+            }
+        };
+
+        for pattern in &self.patterns {
+            for captures in pattern.regex.captures_iter(text) {
+                let whole = captures.get(0).expect("group 0 always matches");
+                if whole.is_empty() {
+                    continue;
+                }
+                add(
+                    whole.range(),
+                    ReferenceKind::Ticket,
+                    Some(pattern.expand(&captures)),
+                );
+            }
+        }
+
+        for captures in trailer_regex().captures_iter(text) {
+            let key = captures.get(1).expect("trailer key");
+            add(key.start()..key.end() + 1, ReferenceKind::Trailer, None);
+            if key.as_str().eq_ignore_ascii_case("fixes") {
+                if let Some(oid) = captures.get(2) {
+                    let url = self
+                        .forge
+                        .as_ref()
+                        .map(|forge| forge.commit_url(oid.as_str()));
+                    add(oid.range(), ReferenceKind::Commit, url);
+                }
+            }
+        }
+
+        for captures in cross_repo_regex().captures_iter(text) {
+            let url = self
+                .forge
+                .as_ref()
+                .and_then(|forge| forge.sibling(&captures[2]))
+                .and_then(|forge| issue_url(&forge, &captures[3]));
+            add(
+                captures.get(1).expect("reference").range(),
+                ReferenceKind::Issue,
+                url,
+            );
+        }
+
+        for captures in github_regex().captures_iter(text) {
+            let url = self
+                .forge
+                .as_ref()
+                .filter(|forge| forge.kind() == ForgeKind::GitHub)
+                .and_then(|forge| issue_url(forge, &captures[1]));
+            add(
+                captures.get(0).expect("reference").range(),
+                ReferenceKind::Issue,
+                url,
+            );
+        }
+
+        for captures in issue_regex().captures_iter(text) {
+            let url = self
+                .forge
+                .as_ref()
+                .and_then(|forge| issue_url(forge, &captures[2]));
+            add(
+                captures.get(1).expect("reference").range(),
+                ReferenceKind::Issue,
+                url,
+            );
+        }
+
+        for captures in tracker_key_regex().captures_iter(text) {
+            if !NOT_TRACKER_KEYS.contains(&&captures[1]) {
+                add(
+                    captures.get(0).expect("key").range(),
+                    ReferenceKind::Ticket,
+                    None,
+                );
+            }
+        }
+
+        found.sort_by_key(|reference| reference.range.start);
+        found
+    }
+
+    /// References in `text` that have a web page, as `(text, url)` pairs in
+    /// order of first appearance with repeated links left out.
+    pub fn links(&self, text: &str) -> Vec<(String, String)> {
+        let mut links: Vec<(String, String)> = Vec::new();
+        for reference in self.find(text) {
+            let Some(url) = reference.url else { continue };
+            if !links.iter().any(|(_, seen)| *seen == url) {
+                links.push((text[reference.range].to_string(), url));
+            }
+        }
+        links
+    }
+
+    /// Links in a commit's subject and body, numbered from 1 in the Summary
+    /// tab.
+    pub fn commit_links(&self, commit: &Commit) -> Vec<(String, String)> {
+        self.links(&format!("{}\n\n{}", commit.subject, commit.body))
+    }
+}
+
+fn issue_url(forge: &Forge, number: &str) -> Option<String> {
+    forge.issue_url(number.parse().ok()?)
+}
+
+fn ranges_overlap(a: &Range<usize>, b: &Range<usize>) -> bool {
+    a.start < b.end && b.start < a.end
+}
+
+fn trailer_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    REGEX.get_or_init(|| {
+        Regex::new(
+            r"(?mi)^(fixes|closes|resolves|refs|co-authored-by):[ \t]*(?:([0-9a-f]{7,40})\b)?",
+        )
+        .expect("valid trailer regex")
+    })
+}
+
+/// `owner/repo#123`, not preceded by anything that would make it part of a
+/// longer path or URL.
+fn cross_repo_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    REGEX.get_or_init(|| {
+        Regex::new(r"(?:^|[^\w./#-])(([\w.-]+/[\w.-]+)#(\d+))\b").expect("valid cross-repo regex")
+    })
+}
+
+fn github_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    REGEX.get_or_init(|| Regex::new(r"\bGH-(\d+)\b").expect("valid GH- regex"))
+}
+
+/// `#123`, but not an HTML entity (`&#123;`), a URL fragment or `##123`.
+fn issue_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    REGEX.get_or_init(|| Regex::new(r"(?:^|[^\w&/#])(#(\d+))\b").expect("valid issue regex"))
+}
+
+/// Upper-case prefixes of standards and identifiers that are written like
+/// tracker keys: `SHA-256`, `UTF-8`, `ISO-8601`, `RFC-2119`, `CVE-2024-...`.
+const NOT_TRACKER_KEYS: [&str; 13] = [
+    "AES", "ANSI", "CRC", "CVE", "ECMA", "IEC", "IEEE", "ISO", "MD", "RFC", "SHA", "UCS", "UTF",
+];
+
+/// JIRA-style keys: a project key of at least two upper-case letters, a
+/// dash and an issue number that does not start with 0. Standards named in
+/// [`NOT_TRACKER_KEYS`] are skipped.
+fn tracker_key_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    REGEX.get_or_init(|| {
+        Regex::new(r"\b([A-Z]{2}[A-Z0-9_]*)-[1-9]\d*\b").expect("valid tracker key regex")
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::forge::ForgeHosts;
+    use crate::git::remote::parse_remote_url;
+
+    fn github() -> ReferenceFinder {
+        let remote = parse_remote_url("git@github.com:owner/repo.git").unwrap();
+        ReferenceFinder::new(Forge::from_remote(&remote, &ForgeHosts::new()), Vec::new())
+    }
+
+    fn found(finder: &ReferenceFinder, text: &str) -> Vec<(String, ReferenceKind, Option<String>)> {
+        finder
+            .find(text)
+            .into_iter()
+            .map(|reference| {
+                (
+                    text[reference.range].to_string(),
+                    reference.kind,
+                    reference.url,
+                )
+            })
+            .collect()
+    }
+
+    fn issue(text: &str, url: &str) -> (String, ReferenceKind, Option<String>) {
+        (
+            text.to_string(),
+            ReferenceKind::Issue,
+            Some(url.to_string()),
+        )
+    }
+
+    #[test]
+    fn test_finds_issue_references() {
+        let finder = github();
+        assert_eq!(
+            found(&finder, "Fix crash (#12), see GH-7 and other/lib#3"),
+            vec![
+                issue("#12", "https://github.com/owner/repo/issues/12"),
+                issue("GH-7", "https://github.com/owner/repo/issues/7"),
+                issue("other/lib#3", "https://github.com/other/lib/issues/3"),
+            ]
+        );
+        assert!(found(&finder, "see https://example.com/page#12 and &#38; or ##4").is_empty());
+    }
+
+    #[test]
+    fn test_finds_trailers_and_tracker_keys() {
+        let finder = github();
+        let text = "Body mentions PROJ-42, not SHA-256, UTF-8, ISO-8601, RFC-2119, CVE-2024 or AB-0.\n\nFixes: 1234abcd (\"Old change\")\nCo-authored-by: Ada <ada@example.com>";
+        assert_eq!(
+            found(&finder, text),
+            vec![
+                ("PROJ-42".to_string(), ReferenceKind::Ticket, None),
+                ("Fixes:".to_string(), ReferenceKind::Trailer, None),
+                (
+                    "1234abcd".to_string(),
+                    ReferenceKind::Commit,
+                    Some("https://github.com/owner/repo/commit/1234abcd".to_string())
+                ),
+                ("Co-authored-by:".to_string(), ReferenceKind::Trailer, None),
+            ]
+        );
+        assert_eq!(
+            found(&finder, "Closes: #5"),
+            vec![
+                ("Closes:".to_string(), ReferenceKind::Trailer, None),
+                issue("#5", "https://github.com/owner/repo/issues/5"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_configured_patterns_come_first() {
+        let patterns = vec![
+            ReferencePattern::new(r"\b[A-Z]+-\d+\b", "https://tracker.example.com/browse/$0")
+                .unwrap(),
+            ReferencePattern::new(r"#(\d{5,})", "https://tickets.example.com/${1}").unwrap(),
+        ];
+        let finder = ReferenceFinder::new(None, patterns);
+        assert_eq!(
+            finder.links("OPS-9: fix #12345, relates to #3 and OPS-9"),
+            vec![
+                (
+                    "OPS-9".to_string(),
+                    "https://tracker.example.com/browse/OPS-9".to_string()
+                ),
+                (
+                    "#12345".to_string(),
+                    "https://tickets.example.com/12345".to_string()
+                ),
+            ]
+        );
+        assert_eq!(found(&finder, "#3")[0].2, None);
+        assert_eq!(
+            ReferencePattern::new("(", "https://x").unwrap_err(),
+            PatternError::Regex("unclosed group".to_string())
+        );
+        assert_eq!(
+            ReferencePattern::new("x", "javascript:alert(1)//$0").unwrap_err(),
+            PatternError::Url
+        );
+        assert_eq!(
+            ReferencePattern::new("x", "file:///$0").unwrap_err(),
+            PatternError::Url
+        );
+    }
+
+    #[test]
+    fn test_pattern_urls_encode_matched_text() {
+        let patterns = vec![ReferencePattern::new(
+            r"ticket:(?<id>\S+)",
+            "https://t.example.com/${id}?from=$$x&all=$0",
+        )
+        .unwrap()];
+        let finder = ReferenceFinder::new(None, patterns);
+        assert_eq!(
+            finder.links("see ticket:a&b?c#d\"e"),
+            vec![(
+                "ticket:a&b?c#d\"e".to_string(),
+                "https://t.example.com/a%26b%3Fc%23d%22e?from=$x&all=ticket%3Aa%26b%3Fc%23d%22e"
+                    .to_string()
+            )]
+        );
+    }
+}
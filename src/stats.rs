@@ -0,0 +1,260 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::git::model::{Commit, LineStats};
+
+const SECONDS_PER_DAY: i64 = 86_400;
+
+/// What one author contributed to a set of commits.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuthorStats {
+    /// Author name, mapped through `.mailmap`.
+    pub name: String,
+    /// Author email, mapped through `.mailmap`.
+    pub email: String,
+    /// Commits authored.
+    pub commits: usize,
+    /// Lines added and removed by the commits whose line counts are known.
+    pub lines: LineStats,
+    /// Calendar days, in the author's timezone, with at least one commit.
+    pub active_days: usize,
+    /// Commits in each week of [`ContributionStats::first_week`] onwards,
+    /// oldest first. Every author has the same number of weeks.
+    pub weekly: Vec<usize>,
+}
+
+/// Commits, lines and activity per author over a set of commits, such as
+/// the ones a search or revision range shows.
+///
+/// Authors are told apart by their `.mailmap` identity, so one person
+/// committing from several addresses is counted once.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ContributionStats {
+    /// Authors with the most commits first; ties go to the author with more
+    /// lines changed, then by name.
+    pub authors: Vec<AuthorStats>,
+    /// Commits counted.
+    pub commits: usize,
+    /// Lines added and removed over all commits with known line counts.
+    pub lines: LineStats,
+    /// Commits whose line counts were not known.
+    pub missing_lines: usize,
+    /// First week of the weekly counts, as Mondays since the Unix epoch;
+    /// weeks run from Monday to Sunday in each author's timezone.
+    pub first_week: i64,
+}
+
+impl ContributionStats {
+    /// Count `commits` by author. `line_stats` returns the lines a commit
+    /// changed from its hash, or `None` when they are not loaded.
+    pub fn new<'a>(
+        commits: impl IntoIterator<Item = &'a Commit>,
+        line_stats: impl Fn(&str) -> Option<LineStats>,
+    ) -> Self {
+        let commits: Vec<&Commit> = commits.into_iter().collect();
+        let Some(first_week) = commits.iter().map(|commit| local_week(commit)).min() else {
+            return ContributionStats::default();
+        };
+        let last_week = commits
+            .iter()
+            .map(|commit| local_week(commit))
+            .max()
+            .unwrap_or(first_week);
+        let weeks = (last_week - first_week + 1) as usize;
+
+        let mut stats = ContributionStats {
+            first_week,
+            ..ContributionStats::default()
+        };
+        let mut authors: Vec<AuthorStats> = Vec::new();
+        let mut days: Vec<HashSet<i64>> = Vec::new();
+        let mut index_of: HashMap<(&str, &str), usize> = HashMap::new();
+        for commit in commits {
+            let index = *index_of
+                .entry((commit.author.as_str(), commit.author_email.as_str()))
+                .or_insert_with(|| {
+                    authors.push(AuthorStats {
+                        name: commit.author.clone(),
+                        email: commit.author_email.clone(),
+                        commits: 0,
+                        lines: LineStats::default(),
+                        active_days: 0,
+                        weekly: vec![0; weeks],
+                    });
+                    days.push(HashSet::new());
+                    authors.len() - 1
+                });
+            let author = &mut authors[index];
+            author.commits += 1;
+            author.weekly[(local_week(commit) - first_week) as usize] += 1;
+            days[index].insert(local_day(commit));
+            match line_stats(&commit.oid) {
+                Some(lines) => {
+                    author.lines += lines;
+                    stats.lines += lines;
+                }
+                None => stats.missing_lines += 1,
+            }
+            stats.commits += 1;
+        }
+
+        for (author, days) in authors.iter_mut().zip(days) {
+            author.active_days = days.len();
+        }
+        authors.sort_by(|a, b| {
+            b.commits
+                .cmp(&a.commits)
+                .then(b.lines.changed().cmp(&a.lines.changed()))
+                .then_with(|| a.name.cmp(&b.name))
+        });
+        stats.authors = authors;
+        stats
+    }
+
+    /// Number of weeks in each author's weekly counts.
+    pub fn weeks(&self) -> usize {
+        self.authors.first().map_or(0, |author| author.weekly.len())
+    }
+
+    /// Most commits any author made in one week.
+    pub fn busiest_week(&self) -> usize {
+        self.authors
+            .iter()
+            .flat_map(|author| author.weekly.iter().copied())
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+/// Days since the Unix epoch of the author date, in the author's timezone.
+fn local_day(commit: &Commit) -> i64 {
+    (commit.timestamp + i64::from(commit.author_tz_offset) * 60).div_euclid(SECONDS_PER_DAY)
+}
+
+/// Weeks since the Monday before the Unix epoch, which was a Thursday.
+fn local_week(commit: &Commit) -> i64 {
+    (local_day(commit) + 3).div_euclid(7)
+}
+
+/// Draw `values` as a row of bars scaled so that `max` is full height. Zero
+/// is a space and anything else at least the lowest bar; `ascii` draws the
+/// bars as `.:-=+*#@` instead of block characters.
+pub fn sparkline(values: &[usize], max: usize, ascii: bool) -> String {
+    const BLOCKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+    const ASCII: [char; 8] = ['.', ':', '-', '=', '+', '*', '#', '@'];
+    let bars = if ascii { ASCII } else { BLOCKS };
+    values
+        .iter()
+        .map(|&value| {
+            if value == 0 || max == 0 {
+                ' '
+            } else {
+                // The smallest bar that reaches `value`, rounding up.
+                bars[(value.min(max) * bars.len() - 1) / max]
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 2024-01-01, a Monday.
+    const MONDAY: i64 = 1_704_067_200;
+
+    fn commit(oid: &str, author: &str, timestamp: i64) -> Commit {
+        Commit {
+            oid: oid.to_string(),
+            author: author.to_string(),
+            author_email: format!("{}@example.com", author.to_lowercase()),
+            timestamp,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_counts_commits_lines_days_and_weeks_per_author() {
+        let mut late = commit("e", "Bob", MONDAY + 6 * SECONDS_PER_DAY + 23 * 3_600);
+        // Monday morning in UTC+2 is still Sunday night in UTC.
+        late.author_tz_offset = 120;
+        let commits = [
+            commit("a", "Ada", MONDAY + 3_600),
+            commit("b", "Ada", MONDAY + 7_200),
+            commit("c", "Ada", MONDAY + 15 * SECONDS_PER_DAY),
+            commit("d", "Bob", MONDAY + SECONDS_PER_DAY),
+            late,
+        ];
+        let lines = |oid: &str| match oid {
+            "a" => Some(LineStats {
+                added: 10,
+                removed: 2,
+            }),
+            "b" | "d" | "e" => Some(LineStats {
+                added: 1,
+                removed: 1,
+            }),
+            _ => None,
+        };
+
+        let stats = ContributionStats::new(&commits, lines);
+        assert_eq!(stats.commits, 5);
+        assert_eq!(
+            stats.lines,
+            LineStats {
+                added: 13,
+                removed: 5
+            }
+        );
+        assert_eq!(stats.missing_lines, 1);
+        assert_eq!(stats.first_week * 7 - 3, MONDAY / SECONDS_PER_DAY);
+        assert_eq!(stats.weeks(), 3);
+        assert_eq!(stats.busiest_week(), 2);
+
+        let ada = &stats.authors[0];
+        assert_eq!(
+            (ada.name.as_str(), ada.commits, ada.active_days),
+            ("Ada", 3, 2)
+        );
+        assert_eq!(
+            ada.lines,
+            LineStats {
+                added: 11,
+                removed: 3
+            }
+        );
+        assert_eq!(ada.weekly, [2, 0, 1]);
+        let bob = &stats.authors[1];
+        assert_eq!((bob.commits, bob.active_days), (2, 2));
+        assert_eq!(bob.weekly, [1, 1, 0]);
+
+        assert_eq!(
+            ContributionStats::new(&[], lines),
+            ContributionStats::default()
+        );
+    }
+
+    #[test]
+    fn test_authors_are_told_apart_by_mailmap_identity() {
+        let mut alias = commit("b", "Ada", MONDAY);
+        alias.raw_author_email = "ada@laptop.local".to_string();
+        let commits = [
+            commit("a", "Ada", MONDAY),
+            alias,
+            commit("c", "Bob", MONDAY),
+        ];
+        let stats = ContributionStats::new(&commits, |_| None);
+        let authors: Vec<(&str, usize)> = stats
+            .authors
+            .iter()
+            .map(|author| (author.name.as_str(), author.commits))
+            .collect();
+        assert_eq!(authors, [("Ada", 2), ("Bob", 1)]);
+    }
+
+    #[test]
+    fn test_sparkline() {
+        assert_eq!(sparkline(&[0, 1, 4, 8, 12], 8, false), " ▁▄██");
+        assert_eq!(sparkline(&[0, 1, 8], 8, true), " .@");
+        assert_eq!(sparkline(&[0, 0], 0, false), "  ");
+    }
+}
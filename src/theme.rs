@@ -0,0 +1,745 @@
+use ratatui::style::{Color, Modifier, Style};
+
+use crate::git::model::{RefKind, SignatureTrust};
+use crate::graph::lanes::TRUNK_COLOR;
+
+/// Theme names that are always available.
+pub const BUILTIN_THEMES: [&str; 6] = [
+    "auto",
+    "default",
+    "dark",
+    "light",
+    "high-contrast",
+    "monochrome",
+];
+
+/// Style keys a theme file may set, in the order they are documented.
+pub const STYLE_KEYS: [&str; 20] = [
+    "title",
+    "accent",
+    "strong",
+    "muted",
+    "link",
+    "head",
+    "branch",
+    "remote",
+    "tag",
+    "stash",
+    "signature_good",
+    "signature_unknown",
+    "signature_bad",
+    "selection",
+    "diff_added",
+    "diff_removed",
+    "diff_hunk",
+    "diff_meta",
+    "filter_bar",
+    "prompt_bar",
+];
+
+/// How many colours the terminal can show.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorMode {
+    /// The 16 ANSI colours.
+    Ansi16,
+    /// The xterm 256-colour palette.
+    Ansi256,
+    /// 24-bit RGB.
+    TrueColor,
+}
+
+impl ColorMode {
+    /// Parse `16`, `256`, `truecolor` or `24bit`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "16" => Some(ColorMode::Ansi16),
+            "256" => Some(ColorMode::Ansi256),
+            "truecolor" | "24bit" => Some(ColorMode::TrueColor),
+            _ => None,
+        }
+    }
+
+    /// Guess the colour depth from `COLORTERM` and `TERM`.
+    pub fn detect() -> Self {
+        let colorterm = std::env::var("COLORTERM").unwrap_or_default();
+        let term = std::env::var("TERM").unwrap_or_default();
+        ColorMode::from_env(&colorterm, &term)
+    }
+
+    fn from_env(colorterm: &str, term: &str) -> Self {
+        if matches!(colorterm, "truecolor" | "24bit") || term.ends_with("-direct") {
+            ColorMode::TrueColor
+        } else if term.contains("256color") {
+            ColorMode::Ansi256
+        } else {
+            ColorMode::Ansi16
+        }
+    }
+}
+
+/// Terminal background brightness, used by the `auto` theme.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Background {
+    /// Dark text on a light background.
+    Light,
+    /// Light text on a dark background.
+    Dark,
+}
+
+impl Background {
+    /// Read the background from `COLORFGBG` (set by rxvt, Konsole, iTerm2 and
+    /// others as `fg;bg`), if the terminal provides it.
+    pub fn detect() -> Option<Self> {
+        std::env::var("COLORFGBG")
+            .ok()
+            .and_then(|value| Background::from_colorfgbg(&value))
+    }
+
+    fn from_colorfgbg(value: &str) -> Option<Self> {
+        let bg: u8 = value.rsplit(';').next()?.trim().parse().ok()?;
+        // ANSI 7 (light gray) and 9-15 (bright colours) are light backgrounds.
+        if bg == 7 || (9..=15).contains(&bg) {
+            Some(Background::Light)
+        } else {
+            Some(Background::Dark)
+        }
+    }
+}
+
+/// Colours and styles used by every widget.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Theme {
+    /// Graph line colours: the first is the trunk's, the rest are picked by
+    /// branch colour key. Empty means graph lines are unstyled.
+    pub lanes: Vec<Color>,
+    /// Pane titles.
+    pub title: Style,
+    /// Commit hashes and field labels.
+    pub accent: Style,
+    /// Emphasised text such as the commit subject in details.
+    pub strong: Style,
+    /// Secondary text such as authors and dates.
+    pub muted: Style,
+    /// Issue, pull request and tracker references in commit messages.
+    pub link: Style,
+    /// The `HEAD` label.
+    pub head: Style,
+    /// Local branch labels.
+    pub branch: Style,
+    /// Remote-tracking branch labels.
+    pub remote: Style,
+    /// Tag labels.
+    pub tag: Style,
+    /// Stash labels.
+    pub stash: Style,
+    /// Badge of a commit with a verified signature.
+    pub signature_good: Style,
+    /// Badge of a signature that cannot be trusted or checked.
+    pub signature_unknown: Style,
+    /// Badge of a bad signature or one made by a revoked key.
+    pub signature_bad: Style,
+    /// The selected row.
+    pub selection: Style,
+    /// Added lines in a patch.
+    pub diff_added: Style,
+    /// Removed lines in a patch.
+    pub diff_removed: Style,
+    /// `@@` hunk headers in a patch.
+    pub diff_hunk: Style,
+    /// File headers in a patch.
+    pub diff_meta: Style,
+    /// The search bar.
+    pub filter_bar: Style,
+    /// The revision range prompt.
+    pub prompt_bar: Style,
+}
+
+impl Default for Theme {
+    /// The ANSI palette gitviz has always used; works on any colour terminal.
+    fn default() -> Self {
+        let bold = |color| Style::default().fg(color).add_modifier(Modifier::BOLD);
+        Theme {
+            lanes: vec![
+                Color::Blue,
+                Color::Red,
+                Color::Green,
+                Color::Magenta,
+                Color::Cyan,
+                Color::LightBlue,
+                Color::LightRed,
+            ],
+            title: Style::default().fg(Color::Cyan),
+            accent: Style::default().fg(Color::Yellow),
+            strong: bold(Color::White),
+            muted: Style::default().fg(Color::DarkGray),
+            link: Style::default()
+                .fg(Color::Blue)
+                .add_modifier(Modifier::UNDERLINED),
+            head: bold(Color::Cyan),
+            branch: bold(Color::Green),
+            remote: bold(Color::Red),
+            tag: bold(Color::Yellow),
+            stash: bold(Color::Magenta),
+            signature_good: bold(Color::Green),
+            signature_unknown: bold(Color::Yellow),
+            signature_bad: bold(Color::Red),
+            selection: Style::default()
+                .bg(Color::DarkGray)
+                .add_modifier(Modifier::BOLD),
+            diff_added: Style::default().fg(Color::Green),
+            diff_removed: Style::default().fg(Color::Red),
+            diff_hunk: Style::default().fg(Color::Cyan),
+            diff_meta: Style::default().add_modifier(Modifier::BOLD),
+            filter_bar: Style::default().fg(Color::Black).bg(Color::Yellow),
+            prompt_bar: Style::default().fg(Color::Black).bg(Color::Cyan),
+        }
+    }
+}
+
+impl Theme {
+    /// Built-in theme by name; `auto` picks light or dark from the terminal.
+    pub fn builtin(name: &str) -> Option<Self> {
+        match name {
+            "auto" => Some(match Background::detect() {
+                Some(Background::Light) => Theme::light(),
+                _ => Theme::dark(),
+            }),
+            "default" => Some(Theme::default()),
+            "dark" => Some(Theme::dark()),
+            "light" => Some(Theme::light()),
+            "high-contrast" => Some(Theme::high_contrast()),
+            "monochrome" => Some(Theme::monochrome()),
+            _ => None,
+        }
+    }
+
+    /// Attributes only, for `--no-color` and terminals without colour.
+    pub fn monochrome() -> Self {
+        let bold = Style::default().add_modifier(Modifier::BOLD);
+        let reversed = Style::default().add_modifier(Modifier::REVERSED);
+        Theme {
+            lanes: Vec::new(),
+            title: bold,
+            accent: bold,
+            strong: bold,
+            muted: Style::default(),
+            link: Style::default().add_modifier(Modifier::UNDERLINED),
+            head: bold,
+            branch: bold,
+            remote: bold,
+            tag: bold,
+            stash: bold,
+            signature_good: bold,
+            signature_unknown: Style::default(),
+            signature_bad: reversed.add_modifier(Modifier::BOLD),
+            selection: reversed.add_modifier(Modifier::BOLD),
+            diff_added: Style::default(),
+            diff_removed: Style::default(),
+            diff_hunk: Style::default().add_modifier(Modifier::DIM),
+            diff_meta: bold,
+            filter_bar: reversed,
+            prompt_bar: reversed,
+        }
+    }
+
+    /// Truecolor palette for dark backgrounds.
+    pub fn dark() -> Self {
+        let fg = |r, g, b| Style::default().fg(Color::Rgb(r, g, b));
+        let bold = |r, g, b| fg(r, g, b).add_modifier(Modifier::BOLD);
+        Theme {
+            lanes: vec![
+                Color::Rgb(0x61, 0xaf, 0xef),
+                Color::Rgb(0xe0, 0x6c, 0x75),
+                Color::Rgb(0x98, 0xc3, 0x79),
+                Color::Rgb(0xc6, 0x78, 0xdd),
+                Color::Rgb(0x56, 0xb6, 0xc2),
+                Color::Rgb(0xd1, 0x9a, 0x66),
+                Color::Rgb(0xe5, 0xc0, 0x7b),
+                Color::Rgb(0xbe, 0x50, 0x46),
+            ],
+            title: fg(0x56, 0xb6, 0xc2),
+            accent: fg(0xe5, 0xc0, 0x7b),
+            strong: bold(0xec, 0xef, 0xf4),
+            muted: fg(0x7f, 0x84, 0x8e),
+            link: fg(0x61, 0xaf, 0xef).add_modifier(Modifier::UNDERLINED),
+            head: bold(0x56, 0xb6, 0xc2),
+            branch: bold(0x98, 0xc3, 0x79),
+            remote: bold(0xe0, 0x6c, 0x75),
+            tag: bold(0xe5, 0xc0, 0x7b),
+            stash: bold(0xc6, 0x78, 0xdd),
+            signature_good: bold(0x98, 0xc3, 0x79),
+            signature_unknown: bold(0xe5, 0xc0, 0x7b),
+            signature_bad: bold(0xe0, 0x6c, 0x75),
+            selection: Style::default()
+                .bg(Color::Rgb(0x3e, 0x44, 0x51))
+                .add_modifier(Modifier::BOLD),
+            diff_added: fg(0x98, 0xc3, 0x79),
+            diff_removed: fg(0xe0, 0x6c, 0x75),
+            diff_hunk: fg(0x56, 0xb6, 0xc2),
+            diff_meta: bold(0xd1, 0x9a, 0x66),
+            filter_bar: Style::default()
+                .fg(Color::Rgb(0x28, 0x2c, 0x34))
+                .bg(Color::Rgb(0xe5, 0xc0, 0x7b)),
+            prompt_bar: Style::default()
+                .fg(Color::Rgb(0x28, 0x2c, 0x34))
+                .bg(Color::Rgb(0x56, 0xb6, 0xc2)),
+        }
+    }
+
+    /// Truecolor palette for light backgrounds.
+    pub fn light() -> Self {
+        let fg = |r, g, b| Style::default().fg(Color::Rgb(r, g, b));
+        let bold = |r, g, b| fg(r, g, b).add_modifier(Modifier::BOLD);
+        Theme {
+            lanes: vec![
+                Color::Rgb(0x0b, 0x61, 0xa4),
+                Color::Rgb(0xc0, 0x39, 0x2b),
+                Color::Rgb(0x2e, 0x7d, 0x32),
+                Color::Rgb(0x8e, 0x24, 0xaa),
+                Color::Rgb(0x00, 0x83, 0x8f),
+                Color::Rgb(0xef, 0x6c, 0x00),
+                Color::Rgb(0x6d, 0x4c, 0x41),
+                Color::Rgb(0xad, 0x14, 0x57),
+            ],
+            title: fg(0x00, 0x5f, 0x87),
+            accent: fg(0xb3, 0x59, 0x00),
+            strong: bold(0x1a, 0x1a, 0x1a),
+            muted: fg(0x80, 0x80, 0x80),
+            link: fg(0x0b, 0x61, 0xa4).add_modifier(Modifier::UNDERLINED),
+            head: bold(0x00, 0x83, 0x8f),
+            branch: bold(0x2e, 0x7d, 0x32),
+            remote: bold(0xc0, 0x39, 0x2b),
+            tag: bold(0xb3, 0x59, 0x00),
+            stash: bold(0x8e, 0x24, 0xaa),
+            signature_good: bold(0x2e, 0x7d, 0x32),
+            signature_unknown: bold(0xb3, 0x59, 0x00),
+            signature_bad: bold(0xc0, 0x39, 0x2b),
+            selection: Style::default()
+                .bg(Color::Rgb(0xdc, 0xe3, 0xea))
+                .add_modifier(Modifier::BOLD),
+            diff_added: fg(0x2e, 0x7d, 0x32),
+            diff_removed: fg(0xc0, 0x39, 0x2b),
+            diff_hunk: fg(0x00, 0x5f, 0x87),
+            diff_meta: bold(0x6d, 0x4c, 0x41),
+            filter_bar: Style::default()
+                .fg(Color::Rgb(0x1a, 0x1a, 0x1a))
+                .bg(Color::Rgb(0xff, 0xd5, 0x4f)),
+            prompt_bar: Style::default()
+                .fg(Color::Rgb(0xff, 0xff, 0xff))
+                .bg(Color::Rgb(0x00, 0x5f, 0x87)),
+        }
+    }
+
+    /// Bright, bold ANSI colours on the terminal's own background.
+    pub fn high_contrast() -> Self {
+        let bold = |color| Style::default().fg(color).add_modifier(Modifier::BOLD);
+        Theme {
+            lanes: vec![
+                Color::LightBlue,
+                Color::LightRed,
+                Color::LightGreen,
+                Color::LightMagenta,
+                Color::LightCyan,
+                Color::LightYellow,
+                Color::White,
+            ],
+            title: bold(Color::LightCyan),
+            accent: bold(Color::LightYellow),
+            strong: bold(Color::White),
+            muted: Style::default().fg(Color::Gray),
+            link: bold(Color::LightBlue).add_modifier(Modifier::UNDERLINED),
+            head: bold(Color::LightCyan).add_modifier(Modifier::UNDERLINED),
+            branch: bold(Color::LightGreen),
+            remote: bold(Color::LightRed),
+            tag: bold(Color::LightYellow),
+            stash: bold(Color::LightMagenta),
+            signature_good: bold(Color::LightGreen),
+            signature_unknown: bold(Color::LightYellow),
+            signature_bad: bold(Color::White).bg(Color::Red),
+            selection: Style::default()
+                .fg(Color::Black)
+                .bg(Color::White)
+                .add_modifier(Modifier::BOLD),
+            diff_added: bold(Color::LightGreen),
+            diff_removed: bold(Color::LightRed),
+            diff_hunk: bold(Color::LightCyan),
+            diff_meta: bold(Color::White),
+            filter_bar: Style::default()
+                .fg(Color::Black)
+                .bg(Color::LightYellow)
+                .add_modifier(Modifier::BOLD),
+            prompt_bar: Style::default()
+                .fg(Color::Black)
+                .bg(Color::LightCyan)
+                .add_modifier(Modifier::BOLD),
+        }
+    }
+
+    /// Style for a graph line by colour key. The first lane colour is kept
+    /// for the trunk; every other key is spread over the rest of the palette.
+    pub fn line_style(&self, color: u64) -> Style {
+        let index = match self.lanes.len() {
+            0 => return Style::default(),
+            1 => 0,
+            _ if color == TRUNK_COLOR => 0,
+            len => 1 + (color % (len as u64 - 1)) as usize,
+        };
+        Style::default().fg(self.lanes[index])
+    }
+
+    /// Style of a ref label of the given kind.
+    pub fn ref_style(&self, kind: RefKind) -> Style {
+        match kind {
+            RefKind::Head => self.head,
+            RefKind::Branch => self.branch,
+            RefKind::Remote => self.remote,
+            RefKind::Tag => self.tag,
+            RefKind::Stash => self.stash,
+        }
+    }
+
+    /// Style of a signature badge; unsigned commits get none.
+    pub fn signature_style(&self, trust: SignatureTrust) -> Style {
+        match trust {
+            SignatureTrust::Unsigned => Style::default(),
+            SignatureTrust::Verified => self.signature_good,
+            SignatureTrust::Unknown => self.signature_unknown,
+            SignatureTrust::Bad => self.signature_bad,
+        }
+    }
+
+    /// Style for one line of `git show` output in the Diff tab.
+    pub fn diff_line_style(&self, line: &str) -> Style {
+        if line.starts_with("+++ ") || line.starts_with("--- ") || line.starts_with("diff --git ") {
+            self.diff_meta
+        } else if line.starts_with('+') {
+            self.diff_added
+        } else if line.starts_with('-') {
+            self.diff_removed
+        } else if line.starts_with("@@") {
+            self.diff_hunk
+        } else {
+            Style::default()
+        }
+    }
+
+    /// Mutable access to a style by its theme-file key.
+    pub fn style_mut(&mut self, key: &str) -> Option<&mut Style> {
+        Some(match key {
+            "title" => &mut self.title,
+            "accent" => &mut self.accent,
+            "strong" => &mut self.strong,
+            "muted" => &mut self.muted,
+            "link" => &mut self.link,
+            "head" => &mut self.head,
+            "branch" => &mut self.branch,
+            "remote" => &mut self.remote,
+            "tag" => &mut self.tag,
+            "stash" => &mut self.stash,
+            "signature_good" => &mut self.signature_good,
+            "signature_unknown" => &mut self.signature_unknown,
+            "signature_bad" => &mut self.signature_bad,
+            "selection" => &mut self.selection,
+            "diff_added" => &mut self.diff_added,
+            "diff_removed" => &mut self.diff_removed,
+            "diff_hunk" => &mut self.diff_hunk,
+            "diff_meta" => &mut self.diff_meta,
+            "filter_bar" => &mut self.filter_bar,
+            "prompt_bar" => &mut self.prompt_bar,
+            _ => return None,
+        })
+    }
+
+    /// Approximate every colour the terminal cannot show.
+    pub fn with_color_mode(mut self, mode: ColorMode) -> Self {
+        for lane in &mut self.lanes {
+            *lane = downgrade(*lane, mode);
+        }
+        for key in STYLE_KEYS {
+            if let Some(style) = self.style_mut(key) {
+                style.fg = style.fg.map(|color| downgrade(color, mode));
+                style.bg = style.bg.map(|color| downgrade(color, mode));
+            }
+        }
+        self
+    }
+}
+
+/// Parse a style such as `bold green on #202020`: modifiers, a foreground
+/// colour and an optional background after `on`, in any order.
+pub fn parse_style(spec: &str) -> Result<Style, String> {
+    let mut style = Style::default();
+    let mut words = spec.split_whitespace();
+    while let Some(word) = words.next() {
+        let modifier = match word.to_ascii_lowercase().as_str() {
+            "bold" => Some(Modifier::BOLD),
+            "dim" => Some(Modifier::DIM),
+            "italic" => Some(Modifier::ITALIC),
+            "underline" | "underlined" => Some(Modifier::UNDERLINED),
+            "reverse" | "reversed" => Some(Modifier::REVERSED),
+            _ => None,
+        };
+        if let Some(modifier) = modifier {
+            style = style.add_modifier(modifier);
+        } else if word.eq_ignore_ascii_case("on") {
+            let color = words
+                .next()
+                .ok_or_else(|| format!("missing background colour after 'on' in '{}'", spec))?;
+            style = style.bg(parse_color(color)?);
+        } else {
+            style = style.fg(parse_color(word)?);
+        }
+    }
+    Ok(style)
+}
+
+/// Parse an ANSI colour name (`red`, `light-blue`), a 256-colour index
+/// (`208`) or a truecolor hex value (`#61afef`).
+pub fn parse_color(spec: &str) -> Result<Color, String> {
+    if let Some(hex) = spec.strip_prefix('#') {
+        let channel = |range: std::ops::Range<usize>| {
+            hex.get(range)
+                .and_then(|digits| u8::from_str_radix(digits, 16).ok())
+        };
+        return match (hex.len(), channel(0..2), channel(2..4), channel(4..6)) {
+            (6, Some(r), Some(g), Some(b)) => Ok(Color::Rgb(r, g, b)),
+            _ => Err(format!("invalid hex colour '{}' (expected #rrggbb)", spec)),
+        };
+    }
+    if let Ok(index) = spec.parse::<u8>() {
+        return Ok(Color::Indexed(index));
+    }
+
+    let name: String = spec
+        .chars()
+        .filter(|c| *c != '-' && *c != '_')
+        .collect::<String>()
+        .to_ascii_lowercase();
+    let color = match name.as_str() {
+        "default" | "reset" => Color::Reset,
+        "black" => Color::Black,
+        "red" => Color::Red,
+        "green" => Color::Green,
+        "yellow" => Color::Yellow,
+        "blue" => Color::Blue,
+        "magenta" => Color::Magenta,
+        "cyan" => Color::Cyan,
+        "gray" | "grey" => Color::Gray,
+        "darkgray" | "darkgrey" => Color::DarkGray,
+        "lightred" => Color::LightRed,
+        "lightgreen" => Color::LightGreen,
+        "lightyellow" => Color::LightYellow,
+        "lightblue" => Color::LightBlue,
+        "lightmagenta" => Color::LightMagenta,
+        "lightcyan" => Color::LightCyan,
+        "white" => Color::White,
+        _ => return Err(format!("unknown colour '{}'", spec)),
+    };
+    Ok(color)
+}
+
+/// The 16 ANSI colours with their usual xterm RGB values.
+const ANSI_COLORS: [(Color, (u8, u8, u8)); 16] = [
+    (Color::Black, (0, 0, 0)),
+    (Color::Red, (205, 0, 0)),
+    (Color::Green, (0, 205, 0)),
+    (Color::Yellow, (205, 205, 0)),
+    (Color::Blue, (0, 0, 238)),
+    (Color::Magenta, (205, 0, 205)),
+    (Color::Cyan, (0, 205, 205)),
+    (Color::Gray, (229, 229, 229)),
+    (Color::DarkGray, (127, 127, 127)),
+    (Color::LightRed, (255, 0, 0)),
+    (Color::LightGreen, (0, 255, 0)),
+    (Color::LightYellow, (255, 255, 0)),
+    (Color::LightBlue, (92, 92, 255)),
+    (Color::LightMagenta, (255, 0, 255)),
+    (Color::LightCyan, (0, 255, 255)),
+    (Color::White, (255, 255, 255)),
+];
+
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+/// RGB value a colour is usually shown as, or `None` for the terminal default.
+pub fn color_to_rgb(color: Color) -> Option<(u8, u8, u8)> {
+    match color {
+        Color::Reset => None,
+        Color::Rgb(r, g, b) => Some((r, g, b)),
+        Color::Indexed(index) => Some(indexed_to_rgb(index)),
+        named => ANSI_COLORS
+            .iter()
+            .find(|(color, _)| *color == named)
+            .map(|(_, rgb)| *rgb),
+    }
+}
+
+fn downgrade(color: Color, mode: ColorMode) -> Color {
+    match (mode, color) {
+        (ColorMode::TrueColor, _) => color,
+        (ColorMode::Ansi256, Color::Rgb(r, g, b)) => Color::Indexed(rgb_to_256(r, g, b)),
+        (ColorMode::Ansi16, Color::Rgb(r, g, b)) => nearest_ansi(r, g, b),
+        (ColorMode::Ansi16, Color::Indexed(index)) => {
+            let (r, g, b) = indexed_to_rgb(index);
+            nearest_ansi(r, g, b)
+        }
+        _ => color,
+    }
+}
+
+fn rgb_to_256(r: u8, g: u8, b: u8) -> u8 {
+    let level = |value: u8| {
+        (0..CUBE_LEVELS.len())
+            .min_by_key(|&idx| CUBE_LEVELS[idx].abs_diff(value))
+            .unwrap_or(0)
+    };
+    let (ri, gi, bi) = (level(r), level(g), level(b));
+    let cube = (CUBE_LEVELS[ri], CUBE_LEVELS[gi], CUBE_LEVELS[bi]);
+    let cube_index = 16 + 36 * ri + 6 * gi + bi;
+
+    let average = (r as u32 + g as u32 + b as u32) / 3;
+    let gray_step = (average.saturating_sub(3) / 10).min(23);
+    let gray_value = (8 + 10 * gray_step) as u8;
+    let gray_index = 232 + gray_step as usize;
+
+    if distance((r, g, b), (gray_value, gray_value, gray_value)) < distance((r, g, b), cube) {
+        gray_index as u8
+    } else {
+        cube_index as u8
+    }
+}
+
+fn indexed_to_rgb(index: u8) -> (u8, u8, u8) {
+    match index {
+        0..=15 => ANSI_COLORS[index as usize].1,
+        16..=231 => {
+            let idx = index as usize - 16;
+            (
+                CUBE_LEVELS[idx / 36],
+                CUBE_LEVELS[(idx / 6) % 6],
+                CUBE_LEVELS[idx % 6],
+            )
+        }
+        _ => {
+            let value = 8 + 10 * (index - 232);
+            (value, value, value)
+        }
+    }
+}
+
+fn nearest_ansi(r: u8, g: u8, b: u8) -> Color {
+    ANSI_COLORS
+        .iter()
+        .min_by_key(|(_, rgb)| distance((r, g, b), *rgb))
+        .map(|(color, _)| *color)
+        .unwrap_or(Color::Reset)
+}
+
+fn distance(a: (u8, u8, u8), b: (u8, u8, u8)) -> u32 {
+    let d = |x: u8, y: u8| (x as i32 - y as i32).pow(2) as u32;
+    d(a.0, b.0) + d(a.1, b.1) + d(a.2, b.2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_style() {
+        assert_eq!(
+            parse_style("bold green on #202020"),
+            Ok(Style::default()
+                .fg(Color::Green)
+                .bg(Color::Rgb(0x20, 0x20, 0x20))
+                .add_modifier(Modifier::BOLD))
+        );
+        assert_eq!(
+            parse_style("light-blue italic"),
+            Ok(Style::default()
+                .fg(Color::LightBlue)
+                .add_modifier(Modifier::ITALIC))
+        );
+        assert_eq!(parse_style(""), Ok(Style::default()));
+        assert!(parse_style("bold on").is_err());
+        assert!(parse_style("chartreuse").is_err());
+    }
+
+    #[test]
+    fn test_parse_color() {
+        assert_eq!(parse_color("208"), Ok(Color::Indexed(208)));
+        assert_eq!(parse_color("#61AFEF"), Ok(Color::Rgb(0x61, 0xaf, 0xef)));
+        assert_eq!(parse_color("Dark_Gray"), Ok(Color::DarkGray));
+        assert!(parse_color("#fff").is_err());
+        assert!(parse_color("#gg0000").is_err());
+    }
+
+    #[test]
+    fn test_color_mode_downgrades_rgb() {
+        let theme = Theme::dark().with_color_mode(ColorMode::Ansi256);
+        assert!(theme
+            .lanes
+            .iter()
+            .all(|color| matches!(color, Color::Indexed(_))));
+
+        assert_eq!(rgb_to_256(255, 0, 0), 196);
+        assert_eq!(rgb_to_256(0x80, 0x80, 0x80), 244);
+        assert_eq!(
+            downgrade(Color::Rgb(250, 10, 10), ColorMode::Ansi16),
+            Color::LightRed
+        );
+        assert_eq!(
+            downgrade(Color::Indexed(21), ColorMode::Ansi16),
+            Color::Blue
+        );
+        assert_eq!(downgrade(Color::Cyan, ColorMode::Ansi16), Color::Cyan);
+        assert_eq!(
+            downgrade(Color::Rgb(1, 2, 3), ColorMode::TrueColor),
+            Color::Rgb(1, 2, 3)
+        );
+    }
+
+    #[test]
+    fn test_color_mode_detection() {
+        assert_eq!(
+            ColorMode::from_env("truecolor", "xterm"),
+            ColorMode::TrueColor
+        );
+        assert_eq!(
+            ColorMode::from_env("", "xterm-direct"),
+            ColorMode::TrueColor
+        );
+        assert_eq!(
+            ColorMode::from_env("", "screen-256color"),
+            ColorMode::Ansi256
+        );
+        assert_eq!(ColorMode::from_env("", "xterm"), ColorMode::Ansi16);
+    }
+
+    #[test]
+    fn test_background_from_colorfgbg() {
+        assert_eq!(Background::from_colorfgbg("15;0"), Some(Background::Dark));
+        assert_eq!(Background::from_colorfgbg("0;15"), Some(Background::Light));
+        assert_eq!(
+            Background::from_colorfgbg("0;default;7"),
+            Some(Background::Light)
+        );
+        assert_eq!(Background::from_colorfgbg("garbage"), None);
+    }
+
+    #[test]
+    fn test_line_style_reserves_trunk_colour() {
+        let theme = Theme::default();
+        assert_eq!(theme.line_style(TRUNK_COLOR).fg, Some(theme.lanes[0]));
+        assert!((1..100).all(|key| theme.line_style(key).fg != Some(theme.lanes[0])));
+        assert_eq!(Theme::monochrome().line_style(5), Style::default());
+    }
+
+    #[test]
+    fn test_diff_line_style() {
+        let theme = Theme::default();
+        assert_eq!(theme.diff_line_style("+added"), theme.diff_added);
+        assert_eq!(theme.diff_line_style("+++ b/file"), theme.diff_meta);
+        assert_eq!(theme.diff_line_style("-removed"), theme.diff_removed);
+        assert_eq!(theme.diff_line_style("@@ -1 +1 @@"), theme.diff_hunk);
+        assert_eq!(theme.diff_line_style(" context"), Style::default());
+    }
+}
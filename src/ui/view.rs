@@ -3,11 +3,13 @@ use ratatui::{
     Frame,
 };
 
-use crate::app::App;
+use crate::app::{App, LayoutAreas, LayoutMode, MIN_DETAILS_HEIGHT};
 
 use super::widgets;
 
-const DETAILS_HEIGHT: u16 = 16;
+const SIDEBAR_WIDTH: u16 = 32;
+/// Terminals at least this wide put details beside the graph in auto layout.
+const AUTO_SIDE_BY_SIDE_MIN_WIDTH: u16 = 160;
 const FILTER_HEIGHT: u16 = 1;
 const HELP_HEIGHT: u16 = 1;
 
@@ -15,35 +17,103 @@ pub fn render(frame: &mut Frame, app: &mut App) {
     use crate::app::Mode;
 
     let area = frame.area();
-    let filter_h = if app.mode == Mode::Filter { FILTER_HEIGHT } else { 0 };
-    let details_h = if app.details_expanded { DETAILS_HEIGHT } else { 3 };
+    let main_area = if app.sidebar_open {
+        let columns = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Length(SIDEBAR_WIDTH), Constraint::Min(20)])
+            .split(area);
+        widgets::render_sidebar(frame, app, columns[0]);
+        app.layout.sidebar = columns[0];
+        columns[1]
+    } else {
+        app.layout.sidebar = Rect::default();
+        area
+    };
 
-    let constraints = vec![
-        Constraint::Min(3),
-        Constraint::Length(details_h),
-        Constraint::Length(filter_h),
-        Constraint::Length(HELP_HEIGHT),
-    ];
+    let filter_h = if matches!(app.mode, Mode::Filter | Mode::Revision) {
+        FILTER_HEIGHT
+    } else {
+        0
+    };
 
     let chunks = Layout::default()
         .direction(Direction::Vertical)
-        .constraints(constraints)
-        .split(area);
+        .constraints([
+            Constraint::Min(3),
+            Constraint::Length(filter_h),
+            Constraint::Length(HELP_HEIGHT),
+        ])
+        .split(main_area);
+
+    let (graph_area, details_area, side_by_side) = split_panes(app, chunks[0]);
+    app.layout = LayoutAreas {
+        graph: graph_area,
+        details: details_area,
+        side_by_side,
+        ..app.layout
+    };
+    if !app.details_fullscreen {
+        widgets::render_graph(frame, app, graph_area);
+    }
+    widgets::render_details(frame, app, details_area);
+
+    match app.mode {
+        Mode::Filter => widgets::render_filter_bar(frame, app, chunks[1]),
+        Mode::Revision => widgets::render_revision_bar(frame, app, chunks[1]),
+        Mode::Normal => {}
+    }
 
-    widgets::render_graph(frame, app, chunks[0]);
-    widgets::render_details(frame, app, chunks[1]);
+    widgets::render_help(frame, app, chunks[2]);
 
-    if app.mode == Mode::Filter {
-        widgets::render_filter_bar(frame, app, chunks[2]);
+    if app.branches_open {
+        widgets::render_branches_overlay(frame, app, centered_rect(72, 80, area));
     }
 
-    widgets::render_help(frame, app, chunks[3]);
+    if app.stats_open {
+        widgets::render_stats_overlay(frame, app, centered_rect(80, 80, area));
+    }
 
     if app.help_open {
         widgets::render_help_overlay(frame, app, centered_rect(72, 80, area));
     }
 }
 
+/// Split the content area into (graph, details, side_by_side).
+fn split_panes(app: &App, area: Rect) -> (Rect, Rect, bool) {
+    if app.details_fullscreen {
+        return (Rect::default(), area, false);
+    }
+
+    let side_by_side = app.details_expanded
+        && match app.layout_mode {
+            LayoutMode::Auto => area.width >= AUTO_SIDE_BY_SIDE_MIN_WIDTH,
+            LayoutMode::Stacked => false,
+            LayoutMode::SideBySide => true,
+        };
+
+    let panes = if side_by_side {
+        Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([
+                Constraint::Min(20),
+                Constraint::Percentage(app.details_width_percent),
+            ])
+            .split(area)
+    } else {
+        let details_h = if app.details_expanded {
+            app.details_height
+        } else {
+            MIN_DETAILS_HEIGHT
+        };
+        Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(3), Constraint::Length(details_h)])
+            .split(area)
+    };
+
+    (panes[0], panes[1], side_by_side)
+}
+
 fn centered_rect(percent_x: u16, percent_y: u16, area: Rect) -> Rect {
     let popup_layout = Layout::default()
         .direction(Direction::Vertical)
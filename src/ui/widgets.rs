@@ -1,80 +1,103 @@
 use ratatui::{
     layout::Rect,
-    style::{Color, Modifier, Style},
+    style::{Modifier, Style},
     text::{Line, Span},
     widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph},
     Frame,
 };
 
-use crate::app::{App, DetailsTab};
-use crate::git::model::{ChangeKind, Commit, CommitInspectData, Refs};
-use crate::graph::{graph_prefix, GraphRow};
-use crate::util::{format_iso, format_relative, short_hash};
+use crate::app::{App, SidebarEntry};
+use crate::git::model::RefKind;
+use crate::graph::GraphGlyphs;
+use crate::keymap::Action;
+use crate::stats::sparkline;
+use crate::util::format_iso;
+use crate::widgets::{CommitDetails, CommitDetailsState, CommitGraph, CommitGraphState};
 
-pub fn render_graph(frame: &mut Frame, app: &App, area: Rect) {
-    let items: Vec<ListItem> = app
-        .filtered
-        .iter()
-        .map(|&commit_idx| {
-            let commit = &app.commits[commit_idx];
-            let row = &app.graph[commit_idx];
-            graph_list_item(app, commit, row, &app.refs)
-        })
-        .collect();
-
-    let title = if app.filtered.len() == app.commits.len() {
+pub fn render_graph(frame: &mut Frame, app: &mut App, area: Rect) {
+    let mut title = if app.filtered.len() == app.commits.len() {
         format!(" Commits ({}) ", app.commits.len())
     } else {
         format!(" Commits ({}/{}) ", app.filtered.len(), app.commits.len())
     };
+    if let Some(revisions) = &app.revisions {
+        title.push_str(&format!("— {} ", revisions.join(" ")));
+    }
+    if app.layout_options.first_parent {
+        title.push_str("— first-parent ");
+    }
 
-    let list = List::new(items)
+    let graph = CommitGraph::new(&app.commits, &app.graph, &app.refs, &app.theme)
+        .rows(&app.filtered)
+        .style(app.graph_style)
         .block(
             Block::default()
                 .borders(Borders::ALL)
                 .title(title)
-                .title_style(title_style(app)),
-        )
-        .highlight_style(list_highlight_style(app))
-        .highlight_symbol("▶ ");
-
-    let mut state = ListState::default();
-    if !app.filtered.is_empty() {
-        state.select(Some(app.selected));
-    }
+                .title_style(app.theme.title),
+        );
 
-    frame.render_stateful_widget(list, area, &mut state);
+    let mut state = CommitGraphState::default()
+        .with_offset(app.graph_offset)
+        .with_selected(Some(app.selected));
+    frame.render_stateful_widget(graph, area, &mut state);
+    app.graph_offset = state.offset();
 }
 
-fn graph_list_item<'a>(
-    app: &'a App,
-    commit: &'a Commit,
-    row: &'a GraphRow,
-    refs: &'a Refs,
-) -> ListItem<'a> {
-    let prefix = graph_prefix(row);
-    let hash = short_hash(&commit.oid);
-    let labels = refs.labels_for(&commit.oid);
-
-    let mut spans: Vec<Span> = Vec::new();
-    let prefix_style = if app.colors_enabled {
-        Style::default().fg(lane_to_color(row.commit_lane))
+pub fn render_sidebar(frame: &mut Frame, app: &mut App, area: Rect) {
+    let items: Vec<ListItem> = app
+        .sidebar_entries()
+        .into_iter()
+        .map(|entry| match entry {
+            SidebarEntry::Section { title, refnames } => {
+                let hidden = refnames
+                    .iter()
+                    .filter(|refname| !app.is_ref_visible(refname))
+                    .count();
+                let text = if hidden == 0 {
+                    title
+                } else {
+                    format!("{} ({} hidden)", title, hidden)
+                };
+                ListItem::new(Line::from(Span::styled(text, app.theme.strong)))
+            }
+            SidebarEntry::Ref { label, refname, .. } => {
+                let (marker, style) = if app.is_ref_visible(&refname) {
+                    ("●", app.theme.ref_style(RefKind::of_refname(&refname)))
+                } else {
+                    ("○", Style::default().add_modifier(Modifier::DIM))
+                };
+                ListItem::new(Line::from(vec![
+                    Span::raw(format!("  {} ", marker)),
+                    Span::styled(label, style),
+                ]))
+            }
+        })
+        .collect();
+
+    let border_style = if app.sidebar_focused {
+        app.theme.title
     } else {
         Style::default()
     };
 
-    spans.push(Span::styled(prefix, prefix_style));
-    spans.push(Span::raw(" "));
-    spans.push(Span::styled(hash.to_string(), accent_style(app)));
-    spans.push(Span::raw(" "));
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(border_style)
+                .title(" Refs ")
+                .title_style(app.theme.title),
+        )
+        .highlight_style(app.theme.selection);
 
-    for label in &labels {
-        spans.push(Span::styled(format!("[{}]", label), ref_style(app)));
-        spans.push(Span::raw(" "));
+    let mut state = ListState::default().with_offset(app.sidebar_offset);
+    if app.sidebar_focused {
+        state.select(Some(app.sidebar_selected));
     }
 
-    spans.push(Span::raw(commit.subject.clone()));
-    ListItem::new(Line::from(spans))
+    frame.render_stateful_widget(list, area, &mut state);
+    app.sidebar_offset = state.offset();
 }
 
 pub fn render_details(frame: &mut Frame, app: &mut App, area: Rect) {
@@ -82,192 +105,23 @@ pub fn render_details(frame: &mut Frame, app: &mut App, area: Rect) {
     let block = Block::default()
         .borders(Borders::ALL)
         .title(title)
-        .title_style(title_style(app));
-
-    let inner_height = area.height.saturating_sub(2);
-    let (content, content_height) = details_lines(app);
-    let max_scroll = content_height.saturating_sub(inner_height);
-    app.clamp_details_scroll(max_scroll);
-
-    let paragraph = Paragraph::new(content)
-        .block(block)
-        .scroll((app.details_scroll, 0));
-    frame.render_widget(paragraph, area);
-}
-
-fn details_lines(app: &App) -> (Vec<Line<'static>>, u16) {
-    let mut lines = vec![tab_line(app), Line::from("")];
-
-    let Some(commit) = app.selected_commit() else {
-        lines.push(Line::from("No commits to display."));
-        let len = lines.len() as u16;
-        return (lines, len);
-    };
+        .title_style(app.theme.title);
 
-    match app.active_tab {
-        DetailsTab::Summary => {
-            lines.extend(summary_lines(app, commit));
-        }
-        DetailsTab::Files => {
-            lines.extend(files_lines(app));
-        }
-        DetailsTab::Diff => {
-            lines.extend(diff_lines(app));
-        }
-    }
-
-    let len = lines.len() as u16;
-    (lines, len)
-}
-
-fn tab_line(app: &App) -> Line<'static> {
-    let tabs = [DetailsTab::Summary, DetailsTab::Files, DetailsTab::Diff];
-    let mut spans = Vec::new();
-    for (idx, tab) in tabs.iter().enumerate() {
-        if idx > 0 {
-            spans.push(Span::raw("  "));
-        }
-        let label = format!("[{}]", tab.title());
-        let style = if *tab == app.active_tab {
-            strong_style(app)
-        } else {
-            Style::default()
-        };
-        spans.push(Span::styled(label, style));
-    }
-    Line::from(spans)
-}
-
-fn summary_lines(app: &App, commit: &Commit) -> Vec<Line<'static>> {
-    let labels = app.refs.labels_for(&commit.oid);
-    let refs_value = if labels.is_empty() {
-        "none".to_string()
-    } else {
-        labels.join(", ")
-    };
-    let parents_value = if commit.parents.is_empty() {
-        "root commit".to_string()
-    } else {
-        commit
-            .parents
-            .iter()
-            .map(|parent| short_hash(parent).to_string())
-            .collect::<Vec<_>>()
-            .join(", ")
-    };
-
-    let mut lines = vec![
-        labeled_line(app, "Commit", commit.oid.clone()),
-        labeled_line(app, "Author", format!("{} <{}>", commit.author, commit.author_email)),
-        labeled_line(
-            app,
-            "Date",
-            format!(
-                "{}  ({})",
-                format_iso(commit.timestamp),
-                format_relative(commit.timestamp)
-            ),
-        ),
-        labeled_line(app, "Parents", parents_value),
-        labeled_line(app, "Refs", refs_value),
-        Line::from(""),
-        Line::from(vec![Span::styled(
-            format!("    {}", commit.subject),
-            strong_style(app),
-        )]),
-        labeled_line(app, "Body", String::new()),
-    ];
-
-    if commit.body.trim().is_empty() {
-        lines.push(Line::from("    (no body)"));
-    } else {
-        lines.extend(
-            commit
-                .body
-                .lines()
-                .take(8)
-                .map(|line| Line::from(format!("    {}", line))),
-        );
-    }
-
-    lines
-}
-
-fn files_lines(app: &App) -> Vec<Line<'static>> {
+    let mut details = CommitDetails::new(app.selected_commit(), &app.refs, &app.theme)
+        .references(&app.references)
+        .raw_identity(app.raw_identity)
+        .block(block);
     if let Some(message) = app.selected_inspect_error() {
-        return vec![Line::from(format!(
-            "Failed to load commit details: {}",
-            message
-        ))];
+        details = details.inspect_error(message);
+    } else if let Some(data) = app.selected_inspect_data() {
+        details = details.inspect(data);
     }
 
-    let Some(data) = app.selected_inspect_data() else {
-        return vec![Line::from("Loading changed files...")];
-    };
-
-    build_files_lines(data)
-}
-
-fn build_files_lines(data: &CommitInspectData) -> Vec<Line<'static>> {
-    if data.changed_files.is_empty() {
-        return vec![Line::from("(no changed files)")];
-    }
-
-    let mut lines: Vec<Line<'static>> = data
-        .changed_files
-        .iter()
-        .map(|file| {
-            let symbol = match file.change_kind {
-                ChangeKind::Added => "A",
-                ChangeKind::Modified => "M",
-                ChangeKind::Deleted => "D",
-                ChangeKind::Renamed => "R",
-                ChangeKind::Copied => "C",
-                ChangeKind::TypeChanged => "T",
-                ChangeKind::Unmerged => "U",
-                ChangeKind::Unknown(_) => "?",
-            };
-
-            let text = match &file.old_path {
-                Some(old_path) => format!("{symbol}  {old_path} -> {}", file.path),
-                None => format!("{symbol}  {}", file.path),
-            };
-            Line::from(text)
-        })
-        .collect();
-
-    if data.file_list_truncated {
-        lines.push(Line::from("... file list truncated"));
-    }
-
-    lines
-}
-
-fn diff_lines(app: &App) -> Vec<Line<'static>> {
-    if let Some(message) = app.selected_inspect_error() {
-        return vec![Line::from(format!(
-            "Failed to load commit details: {}",
-            message
-        ))];
-    }
-
-    let Some(data) = app.selected_inspect_data() else {
-        return vec![Line::from("Loading diff preview...")];
-    };
-
-    let mut lines: Vec<Line<'static>> = data
-        .diff_text
-        .lines()
-        .map(|line| Line::from(line.to_string()))
-        .collect();
-
-    if data.diff_truncated && !data.diff_text.contains("... diff truncated;") {
-        lines.push(Line::from(
-            "... diff truncated; open in GitHub or use git show for the full patch",
-        ));
-    }
-
-    lines
+    let mut state = CommitDetailsState::default()
+        .with_tab(app.active_tab)
+        .with_scroll(app.details_scroll);
+    frame.render_stateful_widget(details, area, &mut state);
+    app.clamp_details_scroll(state.scroll());
 }
 
 pub fn render_filter_bar(frame: &mut Frame, app: &App, area: Rect) {
@@ -277,132 +131,331 @@ pub fn render_filter_bar(frame: &mut Frame, app: &App, area: Rect) {
         format!("Search: {}_", app.filter)
     };
 
-    let style = if app.colors_enabled {
-        Style::default().fg(Color::Black).bg(Color::Yellow)
+    frame.render_widget(Paragraph::new(text).style(app.theme.filter_bar), area);
+}
+
+pub fn render_revision_bar(frame: &mut Frame, app: &App, area: Rect) {
+    let text = if app.revision_input.is_empty() {
+        "Range: (e.g. main..feature, --branches=release/*, ^origin/main; empty resets)".to_string()
     } else {
-        Style::default().add_modifier(Modifier::REVERSED)
+        format!("Range: {}_", app.revision_input)
     };
 
-    frame.render_widget(Paragraph::new(text).style(style), area);
+    frame.render_widget(Paragraph::new(text).style(app.theme.prompt_bar), area);
 }
 
+/// Actions summarised in the status bar, with their short labels.
+const STATUS_HINTS: [(Action, &str); 9] = [
+    (Action::NextTab, "tabs"),
+    (Action::CopyHash, "copy"),
+    (Action::OpenCommit, "open"),
+    (Action::ToggleBranches, "branches"),
+    (Action::ToggleSidebar, "refs"),
+    (Action::Filter, "filter"),
+    (Action::Reload, "reload"),
+    (Action::ToggleHelp, "help"),
+    (Action::Quit, "quit"),
+];
+
+const HELP_GROUPS: [&str; 7] = [
+    "Navigation",
+    "Search",
+    "Details",
+    "Layout",
+    "Graph",
+    "Actions",
+    "Quit",
+];
+
 pub fn render_help(frame: &mut Frame, app: &App, area: Rect) {
-    let text = app.status_message.as_deref().unwrap_or(
-        " j/k:move  Tab:tabs  y:copy  o:open  /:filter  r:reload  ?:help  q:quit ",
-    );
+    let text = match &app.status_message {
+        _ if !app.pending_keys.is_empty() => format!(" {}", app.pending_keys),
+        Some(message) => message.clone(),
+        None => status_hints(app),
+    };
 
-    let style = if app.status_message.is_some() {
-        accent_style(app)
-    } else if app.colors_enabled {
-        Style::default().fg(Color::DarkGray)
+    let style = if app.status_message.is_some() || !app.pending_keys.is_empty() {
+        app.theme.accent
     } else {
-        Style::default()
+        app.theme.muted
     };
 
     frame.render_widget(Paragraph::new(text).style(style), area);
 }
 
+fn status_hints(app: &App) -> String {
+    let first_key = |action| app.keymap.keys_for(action).into_iter().next();
+    let mut hints = Vec::new();
+    if let (Some(down), Some(up)) = (first_key(Action::MoveDown), first_key(Action::MoveUp)) {
+        hints.push(format!("{}/{}:move", down, up));
+    }
+    for (action, label) in STATUS_HINTS {
+        if let Some(key) = first_key(action) {
+            hints.push(format!("{}:{}", key, label));
+        }
+    }
+    format!(" {} ", hints.join("  "))
+}
+
+/// `a`, `a or b`, `a, b or c`.
+fn join_alternatives(items: &[String]) -> String {
+    match items.split_last() {
+        Some((last, rest)) if !rest.is_empty() => format!("{} or {}", rest.join(", "), last),
+        Some((last, _)) => last.clone(),
+        None => String::new(),
+    }
+}
+
 pub fn render_help_overlay(frame: &mut Frame, app: &App, area: Rect) {
     let block = Block::default()
         .borders(Borders::ALL)
         .title(" Help ")
-        .title_style(title_style(app));
+        .title_style(app.theme.title);
+
+    let bindings: Vec<(Action, String)> = Action::ALL
+        .into_iter()
+        .map(|action| {
+            let keys = app.keymap.keys_for(action);
+            let keys = if keys.is_empty() {
+                "(unbound)".to_string()
+            } else {
+                keys.join(", ")
+            };
+            (action, keys)
+        })
+        .collect();
+    let key_width = bindings
+        .iter()
+        .map(|(_, keys)| keys.chars().count())
+        .max()
+        .unwrap_or(0);
+
+    let mut lines = Vec::new();
+    for group in HELP_GROUPS {
+        lines.push(Line::from(vec![Span::styled(group, app.theme.strong)]));
+        for (action, keys) in bindings
+            .iter()
+            .filter(|(action, _)| action.group() == group)
+        {
+            lines.push(Line::from(vec![
+                Span::styled(format!("  {keys:<key_width$}  "), app.theme.accent),
+                Span::raw(action.description()),
+            ]));
+        }
+        lines.push(Line::from(""));
+    }
 
-    let lines = vec![
-        Line::from(vec![Span::styled("Navigation", strong_style(app))]),
-        Line::from("  j/k, arrows: move selection"),
-        Line::from("  g / G: jump to top / bottom"),
-        Line::from("  Enter: collapse or expand details"),
-        Line::from(""),
-        Line::from(vec![Span::styled("Search", strong_style(app))]),
-        Line::from("  /: enter search"),
-        Line::from("  Esc: clear search or close help"),
-        Line::from("  n / N: next / previous search result"),
+    let first_key = |action| app.keymap.keys_for(action).into_iter().next();
+    let jump_keys = app.keymap.keys_for(Action::ToggleDetails);
+    let jump_keys = if jump_keys.is_empty() {
+        "(unbound)".to_string()
+    } else {
+        jump_keys.join(", ")
+    };
+    lines.extend([
+        Line::from(vec![Span::styled("Counts", app.theme.strong)]),
+        Line::from("  Prefix a count to repeat a motion (10j) or jump to a row (25G)"),
+        Line::from("  Esc: cancel a pending count or key sequence"),
         Line::from(""),
-        Line::from(vec![Span::styled("Tabs", strong_style(app))]),
-        Line::from("  Tab / Shift-Tab: cycle detail tabs"),
-        Line::from("  PageUp / PageDown: scroll details"),
-        Line::from("  Ctrl-u / Ctrl-d: half-page scroll"),
+        Line::from(vec![Span::styled("Ref sidebar", app.theme.strong)]),
+        Line::from(format!("  {}: jump to the selected ref's tip", jump_keys)),
+        Line::from("  Space: show or hide a ref (or whole group) in the graph"),
+        Line::from("  Esc: return focus to the graph"),
         Line::from(""),
-        Line::from(vec![Span::styled("Actions", strong_style(app))]),
-        Line::from("  y: copy commit hash"),
-        Line::from("  o: open commit in GitHub"),
-        Line::from("  r: reload repository state"),
+        Line::from(vec![Span::styled("Mouse", app.theme.strong)]),
+        Line::from("  Click to select, wheel to scroll, drag the details border"),
         Line::from(""),
-        Line::from(vec![Span::styled("Quit", strong_style(app))]),
-        Line::from("  ?: toggle help"),
-        Line::from("  q: close help or quit"),
-    ];
+    ]);
+    let mut footer = String::new();
+    if let (Some(down), Some(up)) = (first_key(Action::MoveDown), first_key(Action::MoveUp)) {
+        footer = format!("{}/{} scroll this help; ", down, up);
+    }
+    let close_keys: Vec<String> = [
+        first_key(Action::ToggleHelp),
+        Some("Esc".to_string()),
+        first_key(Action::Quit),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+    footer.push_str(&format!("{} close it", join_alternatives(&close_keys)));
+    lines.push(Line::from(footer));
 
     frame.render_widget(Clear, area);
-    frame.render_widget(Paragraph::new(lines).block(block), area);
+    frame.render_widget(
+        Paragraph::new(lines)
+            .block(block)
+            .scroll((app.help_scroll, 0)),
+        area,
+    );
 }
 
-fn labeled_line(app: &App, label: &str, value: String) -> Line<'static> {
-    let mut spans = vec![Span::styled(format!("{label:<7} "), accent_style(app))];
-    if !value.is_empty() {
-        spans.push(Span::raw(value));
-    }
-    Line::from(spans)
-}
+pub fn render_branches_overlay(frame: &mut Frame, app: &App, area: Rect) {
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(" Branches (by divergence) ")
+        .title_style(app.theme.title);
 
-fn lane_to_color(lane: usize) -> Color {
-    const COLORS: [Color; 7] = [
-        Color::Blue,
-        Color::Red,
-        Color::Green,
-        Color::Magenta,
-        Color::Cyan,
-        Color::LightBlue,
-        Color::LightRed,
-    ];
-    COLORS[lane % COLORS.len()]
-}
+    let branches = app.refs.branches_by_divergence();
+    let name_width = branches
+        .iter()
+        .map(|(name, _)| name.chars().count())
+        .max()
+        .unwrap_or(0);
 
-fn title_style(app: &App) -> Style {
-    if app.colors_enabled {
-        Style::default().fg(Color::Cyan)
+    let lines: Vec<Line> = if branches.is_empty() {
+        vec![Line::from("No local branches.")]
     } else {
-        Style::default().add_modifier(Modifier::BOLD)
-    }
+        branches
+            .iter()
+            .map(|(name, tracking)| {
+                let mut spans = vec![Span::styled(
+                    format!("{name:<name_width$}  "),
+                    app.theme.branch,
+                )];
+                match tracking {
+                    Some(tracking) => {
+                        let upstream = tracking
+                            .upstream
+                            .strip_prefix("refs/remotes/")
+                            .unwrap_or(tracking.upstream.as_str());
+                        let marker = tracking.marker();
+                        let marker = if marker.is_empty() {
+                            "up to date".to_string()
+                        } else {
+                            marker
+                        };
+                        spans.push(Span::styled(format!("{marker:<10} "), app.theme.accent));
+                        spans.push(Span::raw(upstream.to_string()));
+                    }
+                    None => spans.push(Span::raw("(no upstream)")),
+                }
+                Line::from(spans)
+            })
+            .collect()
+    };
+
+    frame.render_widget(Clear, area);
+    frame.render_widget(
+        Paragraph::new(lines)
+            .block(block)
+            .scroll((app.branches_scroll, 0)),
+        area,
+    );
 }
 
-fn accent_style(app: &App) -> Style {
-    if app.colors_enabled {
-        Style::default().fg(Color::Yellow)
-    } else {
-        Style::default().add_modifier(Modifier::BOLD)
+/// Columns taken by the commit, line and day counts of a statistics row.
+const STATS_COUNT_COLUMNS: usize = 39;
+const STATS_MAX_NAME_WIDTH: usize = 24;
+
+pub fn render_stats_overlay(frame: &mut Frame, app: &App, area: Rect) {
+    let mut title = " Authors ".to_string();
+    if !app.filter.trim().is_empty() {
+        title.push_str(&format!("— matching '{}' ", app.filter.trim()));
     }
-}
+    if let Some(revisions) = &app.revisions {
+        title.push_str(&format!("— {} ", revisions.join(" ")));
+    }
+    if app.layout_options.first_parent {
+        title.push_str("— first-parent ");
+    }
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(title)
+        .title_style(app.theme.title);
 
-fn ref_style(app: &App) -> Style {
-    if app.colors_enabled {
-        Style::default()
-            .fg(Color::Green)
-            .add_modifier(Modifier::BOLD)
-    } else {
-        Style::default().add_modifier(Modifier::BOLD)
+    let stats = app.contribution_stats();
+    if stats.authors.is_empty() {
+        frame.render_widget(Clear, area);
+        frame.render_widget(Paragraph::new("No commits shown.").block(block), area);
+        return;
     }
-}
 
-fn strong_style(app: &App) -> Style {
-    if app.colors_enabled {
-        Style::default()
-            .fg(Color::White)
-            .add_modifier(Modifier::BOLD)
+    // Weeks start on Monday; `first_week` counts from the Monday before the epoch.
+    let first_monday = (stats.first_week * 7 - 3) * 86_400;
+    let mut lines = vec![Line::from(Span::styled(
+        format!(
+            "{} by {} over {} from {}, +{} -{} lines",
+            counted(stats.commits, "commit"),
+            counted(stats.authors.len(), "author"),
+            counted(stats.weeks(), "week"),
+            &format_iso(first_monday)[..10],
+            stats.lines.added,
+            stats.lines.removed
+        ),
+        app.theme.muted,
+    ))];
+    if stats.missing_lines > 0 {
+        lines.push(Line::from(Span::styled(
+            format!("Line counts missing for {} commits", stats.missing_lines),
+            app.theme.muted,
+        )));
+    }
+    lines.push(Line::from(""));
+
+    let name_width = stats
+        .authors
+        .iter()
+        .map(|author| author.name.chars().count())
+        .max()
+        .unwrap_or(0)
+        .clamp("Author".len(), STATS_MAX_NAME_WIDTH);
+    let inner_width = area.width.saturating_sub(2) as usize;
+    let spark_width = inner_width.saturating_sub(name_width + STATS_COUNT_COLUMNS);
+    let shown_weeks = stats.weeks().min(spark_width);
+    let activity = if shown_weeks < stats.weeks() {
+        format!("Last {} weeks", shown_weeks)
     } else {
-        Style::default().add_modifier(Modifier::BOLD)
+        "Weekly".to_string()
+    };
+    lines.push(Line::from(Span::styled(
+        format!(
+            "{:<name_width$}  {:>7}  {:>9}  {:>9}  {:>4}  {}",
+            "Author", "Commits", "Added", "Removed", "Days", activity
+        ),
+        app.theme.strong,
+    )));
+
+    let busiest = stats.busiest_week();
+    let ascii = app.graph_style.glyphs == GraphGlyphs::Ascii;
+    for author in &stats.authors {
+        let name = if author.name.chars().count() > name_width {
+            let mut name: String = author.name.chars().take(name_width - 1).collect();
+            name.push('…');
+            name
+        } else {
+            author.name.clone()
+        };
+        let weekly = &author.weekly[author.weekly.len() - shown_weeks..];
+        lines.push(Line::from(vec![
+            Span::styled(format!("{name:<name_width$}  "), app.theme.branch),
+            Span::raw(format!("{:>7}  ", author.commits)),
+            Span::styled(
+                format!("{:>9}  ", format!("+{}", author.lines.added)),
+                app.theme.diff_added,
+            ),
+            Span::styled(
+                format!("{:>9}  ", format!("-{}", author.lines.removed)),
+                app.theme.diff_removed,
+            ),
+            Span::raw(format!("{:>4}  ", author.active_days)),
+            Span::styled(sparkline(weekly, busiest, ascii), app.theme.accent),
+        ]));
     }
+
+    frame.render_widget(Clear, area);
+    frame.render_widget(
+        Paragraph::new(lines)
+            .block(block)
+            .scroll((app.stats_scroll, 0)),
+        area,
+    );
 }
 
-fn list_highlight_style(app: &App) -> Style {
-    if app.colors_enabled {
-        Style::default()
-            .bg(Color::DarkGray)
-            .add_modifier(Modifier::BOLD)
+fn counted(count: usize, noun: &str) -> String {
+    if count == 1 {
+        format!("1 {}", noun)
     } else {
-        Style::default()
-            .add_modifier(Modifier::REVERSED)
-            .add_modifier(Modifier::BOLD)
+        format!("{} {}s", count, noun)
     }
 }
@@ -13,27 +13,33 @@ pub fn format_relative(ts: i64) -> String {
         return "in the future".to_string();
     }
 
-    match diff {
-        0..=59 => format!("{} second{} ago", diff, plural(diff)),
+    format!("{} ago", format_duration(diff))
+}
+
+/// Format a non-negative number of seconds in its largest whole unit,
+/// e.g. `3 days`.
+pub fn format_duration(secs: i64) -> String {
+    match secs {
+        i64::MIN..=59 => format!("{} second{}", secs, plural(secs)),
         60..=3599 => {
-            let m = diff / 60;
-            format!("{} minute{} ago", m, plural(m))
+            let m = secs / 60;
+            format!("{} minute{}", m, plural(m))
         }
         3600..=86399 => {
-            let h = diff / 3600;
-            format!("{} hour{} ago", h, plural(h))
+            let h = secs / 3600;
+            format!("{} hour{}", h, plural(h))
         }
         86400..=2591999 => {
-            let d = diff / 86400;
-            format!("{} day{} ago", d, plural(d))
+            let d = secs / 86400;
+            format!("{} day{}", d, plural(d))
         }
         2592000..=31535999 => {
-            let mo = diff / 2592000;
-            format!("{} month{} ago", mo, plural(mo))
+            let mo = secs / 2592000;
+            format!("{} month{}", mo, plural(mo))
         }
         _ => {
-            let y = diff / 31536000;
-            format!("{} year{} ago", y, plural(y))
+            let y = secs / 31536000;
+            format!("{} year{}", y, plural(y))
         }
     }
 }
@@ -54,6 +60,21 @@ pub fn format_iso(ts: i64) -> String {
     )
 }
 
+/// Format a Unix timestamp as an ISO-8601 date string in the timezone
+/// `offset_minutes` east of UTC, like `git log --date=iso`.
+pub fn format_iso_offset(ts: i64, offset_minutes: i32) -> String {
+    let local = format_iso(ts.saturating_add(offset_minutes as i64 * 60));
+    let sign = if offset_minutes < 0 { '-' } else { '+' };
+    let offset = offset_minutes.unsigned_abs();
+    format!(
+        "{} {}{:02}{:02}",
+        local.trim_end_matches(" UTC"),
+        sign,
+        offset / 60,
+        offset % 60
+    )
+}
+
 /// Return the short hash (first 7 characters).
 pub fn short_hash(oid: &str) -> &str {
     let end = oid.len().min(7);
@@ -108,6 +129,20 @@ mod tests {
         assert_eq!(format_iso(-1), "1969-12-31 23:59:59 UTC");
     }
 
+    #[test]
+    fn test_format_iso_offset() {
+        assert_eq!(format_iso_offset(0, 330), "1970-01-01 05:30:00 +0530");
+        assert_eq!(format_iso_offset(0, -480), "1969-12-31 16:00:00 -0800");
+        assert_eq!(format_iso_offset(0, 0), "1970-01-01 00:00:00 +0000");
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(1), "1 second");
+        assert_eq!(format_duration(7_200), "2 hours");
+        assert_eq!(format_duration(3 * 86_400 + 5), "3 days");
+    }
+
     #[test]
     fn test_format_iso_extreme_timestamps() {
         let min = format_iso(i64::MIN);
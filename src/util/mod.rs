@@ -1,3 +1,4 @@
+/// Formatting of timestamps and object ids.
 pub mod fmt;
 
-pub use fmt::{format_iso, format_relative, short_hash};
+pub use fmt::{format_duration, format_iso, format_iso_offset, format_relative, short_hash};
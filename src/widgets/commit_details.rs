@@ -0,0 +1,654 @@
+use std::borrow::Cow;
+
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::Style,
+    text::{Line, Span},
+    widgets::{Block, Paragraph, StatefulWidget, Widget},
+};
+
+use crate::git::model::{ChangeKind, Commit, CommitInspectData, Refs, Signature, SignatureTrust};
+use crate::references::ReferenceFinder;
+use crate::theme::Theme;
+use crate::util::{format_duration, format_iso_offset, format_relative, short_hash};
+
+/// Page of a [`CommitDetails`] panel.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum DetailsTab {
+    /// Hash, author, committer, signature, parents, refs, message, trailers
+    /// and links.
+    #[default]
+    Summary,
+    /// Changed files.
+    Files,
+    /// The patch.
+    Diff,
+}
+
+impl DetailsTab {
+    /// Label shown in the tab line.
+    pub fn title(self) -> &'static str {
+        match self {
+            DetailsTab::Summary => "Summary",
+            DetailsTab::Files => "Files",
+            DetailsTab::Diff => "Diff",
+        }
+    }
+
+    /// The tab to the right, wrapping around.
+    pub fn next(self) -> Self {
+        match self {
+            DetailsTab::Summary => DetailsTab::Files,
+            DetailsTab::Files => DetailsTab::Diff,
+            DetailsTab::Diff => DetailsTab::Summary,
+        }
+    }
+
+    /// The tab to the left, wrapping around.
+    pub fn previous(self) -> Self {
+        match self {
+            DetailsTab::Summary => DetailsTab::Diff,
+            DetailsTab::Files => DetailsTab::Summary,
+            DetailsTab::Diff => DetailsTab::Files,
+        }
+    }
+}
+
+const DETAILS_TABS: [DetailsTab; 3] = [DetailsTab::Summary, DetailsTab::Files, DetailsTab::Diff];
+const TAB_GAP: &str = "  ";
+
+/// Tab whose `[Title]` label covers `column`, counted from the start of the tab line.
+pub fn tab_at_column(column: u16) -> Option<DetailsTab> {
+    let mut start = 0u16;
+    for tab in DETAILS_TABS {
+        let end = start + tab.title().len() as u16 + 2;
+        if (start..end).contains(&column) {
+            return Some(tab);
+        }
+        start = end + TAB_GAP.len() as u16;
+    }
+    None
+}
+
+/// Active tab and scroll position of a [`CommitDetails`] panel.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct CommitDetailsState {
+    tab: DetailsTab,
+    scroll: u16,
+}
+
+impl CommitDetailsState {
+    /// Start on `tab`.
+    pub fn with_tab(mut self, tab: DetailsTab) -> Self {
+        self.tab = tab;
+        self
+    }
+
+    /// Start scrolled down by `scroll` lines.
+    pub fn with_scroll(mut self, scroll: u16) -> Self {
+        self.scroll = scroll;
+        self
+    }
+
+    /// The tab shown.
+    pub fn tab(&self) -> DetailsTab {
+        self.tab
+    }
+
+    /// Show `tab` from its top.
+    pub fn select_tab(&mut self, tab: DetailsTab) {
+        self.tab = tab;
+        self.scroll = 0;
+    }
+
+    /// Lines scrolled past. Rendering stops it at the end of the content.
+    pub fn scroll(&self) -> u16 {
+        self.scroll
+    }
+
+    /// Scroll by `amount` lines, down when positive.
+    pub fn scroll_by(&mut self, amount: i32) {
+        if amount >= 0 {
+            self.scroll = self.scroll.saturating_add(amount as u16);
+        } else {
+            self.scroll = self.scroll.saturating_sub((-amount) as u16);
+        }
+    }
+}
+
+/// Tabbed panel describing one commit: a summary, its changed files and
+/// its patch.
+///
+/// Changed files and the patch come from
+/// [`load_commit_inspect_data`](crate::git::load_commit_inspect_data), which
+/// callers usually run in the background; until [`CommitDetails::inspect`] or
+/// [`CommitDetails::inspect_error`] is given, those tabs say they are loading.
+#[derive(Debug, Clone)]
+pub struct CommitDetails<'a> {
+    commit: Option<&'a Commit>,
+    refs: &'a Refs,
+    theme: &'a Theme,
+    references: Option<&'a ReferenceFinder>,
+    raw_identity: bool,
+    inspect: Option<Result<&'a CommitInspectData, &'a str>>,
+    block: Option<Block<'a>>,
+}
+
+impl<'a> CommitDetails<'a> {
+    /// Details of `commit`; `None` shows a placeholder.
+    pub fn new(commit: Option<&'a Commit>, refs: &'a Refs, theme: &'a Theme) -> Self {
+        CommitDetails {
+            commit,
+            refs,
+            theme,
+            references: None,
+            raw_identity: false,
+            inspect: None,
+            block: None,
+        }
+    }
+
+    /// Highlight and link references in the message with `finder`. Without
+    /// one, references are still highlighted but only configured patterns
+    /// and a forge can give them links.
+    pub fn references(mut self, finder: &'a ReferenceFinder) -> Self {
+        self.references = Some(finder);
+        self
+    }
+
+    /// Show author and committer as recorded in the commit instead of
+    /// mapped through `.mailmap`.
+    pub fn raw_identity(mut self, raw: bool) -> Self {
+        self.raw_identity = raw;
+        self
+    }
+
+    /// Changed files and patch of the commit.
+    pub fn inspect(mut self, data: &'a CommitInspectData) -> Self {
+        self.inspect = Some(Ok(data));
+        self
+    }
+
+    /// Why the changed files and patch could not be loaded.
+    pub fn inspect_error(mut self, message: &'a str) -> Self {
+        self.inspect = Some(Err(message));
+        self
+    }
+
+    /// Surround the panel with a block.
+    pub fn block(mut self, block: Block<'a>) -> Self {
+        self.block = Some(block);
+        self
+    }
+
+    fn lines(&self, tab: DetailsTab) -> Vec<Line<'static>> {
+        let mut lines = vec![self.tab_line(tab), Line::from("")];
+
+        let Some(commit) = self.commit else {
+            lines.push(Line::from("No commits to display."));
+            return lines;
+        };
+
+        match tab {
+            DetailsTab::Summary => lines.extend(self.summary_lines(commit)),
+            DetailsTab::Files => lines.extend(self.files_lines()),
+            DetailsTab::Diff => lines.extend(self.diff_lines()),
+        }
+        lines
+    }
+
+    fn tab_line(&self, active: DetailsTab) -> Line<'static> {
+        let mut spans = Vec::new();
+        for (idx, tab) in DETAILS_TABS.iter().enumerate() {
+            if idx > 0 {
+                spans.push(Span::raw(TAB_GAP));
+            }
+            let label = format!("[{}]", tab.title());
+            let style = if *tab == active {
+                self.theme.strong
+            } else {
+                Style::default()
+            };
+            spans.push(Span::styled(label, style));
+        }
+        Line::from(spans)
+    }
+
+    fn summary_lines(&self, commit: &Commit) -> Vec<Line<'static>> {
+        let labels = self.refs.labels_for(&commit.oid);
+        let refs_value = if labels.is_empty() {
+            "none".to_string()
+        } else {
+            labels.join(", ")
+        };
+        let parents_value = if commit.parents.is_empty() {
+            "root commit".to_string()
+        } else {
+            commit
+                .parents
+                .iter()
+                .map(|parent| short_hash(parent).to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+
+        let (author, author_email) = commit.author_identity(self.raw_identity);
+        let (committer, committer_email) = commit.committer_identity(self.raw_identity);
+        let mut lines = vec![
+            self.labeled_line("Commit", commit.oid.clone()),
+            self.labeled_line("Author", format!("{} <{}>", author, author_email)),
+            self.labeled_line(
+                "Date",
+                format!(
+                    "{}  ({})",
+                    format_iso_offset(commit.timestamp, commit.author_tz_offset),
+                    format_relative(commit.timestamp)
+                ),
+            ),
+            self.labeled_line("Committer", format!("{} <{}>", committer, committer_email)),
+        ];
+        // Rebases, cherry-picks and applied patches keep the author date.
+        let gap = commit.commit_timestamp - commit.timestamp;
+        if gap != 0 {
+            let direction = if gap > 0 { "later" } else { "earlier" };
+            lines.push(self.labeled_line(
+                "Committed",
+                format!(
+                    "{}  (committed {} {})",
+                    format_iso_offset(commit.commit_timestamp, commit.committer_tz_offset),
+                    format_duration(gap.abs()),
+                    direction
+                ),
+            ));
+        }
+        lines.push(self.signature_line(&commit.signature));
+        lines.extend([
+            self.labeled_line("Parents", parents_value),
+            self.labeled_line("Refs", refs_value),
+            Line::from(""),
+            self.message_line(&commit.subject, self.theme.strong),
+            self.labeled_line("Body", String::new()),
+        ]);
+
+        let body = commit.body_without_trailers();
+        if body.trim().is_empty() {
+            lines.push(Line::from("    (no body)"));
+        } else {
+            lines.extend(
+                body.lines()
+                    .take(8)
+                    .map(|line| self.message_line(line, Style::default())),
+            );
+        }
+
+        if !commit.trailers.is_empty() {
+            lines.push(Line::from(""));
+            lines.push(self.labeled_line("Trailers", String::new()));
+            let width = commit
+                .trailers
+                .iter()
+                .map(|trailer| trailer.key.chars().count())
+                .max()
+                .unwrap_or(0);
+            for trailer in &commit.trailers {
+                let mut spans = vec![Span::styled(
+                    format!("    {:<width$}  ", trailer.key),
+                    self.theme.muted,
+                )];
+                spans.extend(self.message_spans(&trailer.value, Style::default()));
+                lines.push(Line::from(spans));
+            }
+        }
+
+        let links = self.finder().commit_links(commit);
+        if !links.is_empty() {
+            lines.push(Line::from(""));
+            lines.push(self.labeled_line("Links", String::new()));
+            let width = links
+                .iter()
+                .map(|(text, _)| text.chars().count())
+                .max()
+                .unwrap_or(0);
+            for (number, (text, url)) in links.into_iter().enumerate() {
+                lines.push(Line::from(vec![
+                    Span::styled(format!("    {:>2}  ", number + 1), self.theme.muted),
+                    Span::styled(format!("{text:<width$}"), self.theme.link),
+                    Span::raw(format!("  {}", url)),
+                ]));
+            }
+        }
+
+        lines
+    }
+
+    fn signature_line(&self, signature: &Signature) -> Line<'static> {
+        let trust = signature.status.trust();
+        let style = match trust {
+            SignatureTrust::Unsigned => self.theme.muted,
+            trust => self.theme.signature_style(trust),
+        };
+        let mut line = self.labeled_line("Signature", String::new());
+        line.spans
+            .push(Span::styled(signature.status.description(), style));
+        if !signature.signer.is_empty() {
+            line.spans
+                .push(Span::raw(format!(" from {}", signature.signer)));
+        }
+        if !signature.key.is_empty() {
+            line.spans
+                .push(Span::raw(format!("  (key {})", signature.key)));
+        }
+        line
+    }
+
+    fn finder(&self) -> Cow<'a, ReferenceFinder> {
+        match self.references {
+            Some(finder) => Cow::Borrowed(finder),
+            None => Cow::Owned(ReferenceFinder::default()),
+        }
+    }
+
+    /// One indented line of the commit message with its references highlighted.
+    fn message_line(&self, text: &str, style: Style) -> Line<'static> {
+        let mut spans = vec![Span::raw("    ")];
+        spans.extend(self.message_spans(text, style));
+        Line::from(spans)
+    }
+
+    fn message_spans(&self, text: &str, style: Style) -> Vec<Span<'static>> {
+        let mut spans = Vec::new();
+        let mut end = 0;
+        for reference in self.finder().find(text) {
+            if reference.range.start > end {
+                spans.push(Span::styled(
+                    text[end..reference.range.start].to_string(),
+                    style,
+                ));
+            }
+            spans.push(Span::styled(
+                text[reference.range.clone()].to_string(),
+                style.patch(self.theme.link),
+            ));
+            end = reference.range.end;
+        }
+        if end < text.len() || spans.is_empty() {
+            spans.push(Span::styled(text[end..].to_string(), style));
+        }
+        spans
+    }
+
+    fn files_lines(&self) -> Vec<Line<'static>> {
+        match self.inspect {
+            None => vec![Line::from("Loading changed files...")],
+            Some(Err(message)) => vec![load_error_line(message)],
+            Some(Ok(data)) => self.build_files_lines(data),
+        }
+    }
+
+    fn build_files_lines(&self, data: &CommitInspectData) -> Vec<Line<'static>> {
+        if data.changed_files.is_empty() {
+            return vec![Line::from("(no changed files)")];
+        }
+
+        let theme = self.theme;
+        let mut lines: Vec<Line<'static>> = data
+            .changed_files
+            .iter()
+            .map(|file| {
+                let (symbol, style) = match file.change_kind {
+                    ChangeKind::Added => ("A", theme.diff_added),
+                    ChangeKind::Modified => ("M", theme.accent),
+                    ChangeKind::Deleted => ("D", theme.diff_removed),
+                    ChangeKind::Renamed => ("R", theme.accent),
+                    ChangeKind::Copied => ("C", theme.accent),
+                    ChangeKind::TypeChanged => ("T", theme.accent),
+                    ChangeKind::Unmerged => ("U", theme.diff_removed),
+                    ChangeKind::Unknown(_) => ("?", Style::default()),
+                };
+
+                let text = match &file.old_path {
+                    Some(old_path) => format!("  {old_path} -> {}", file.path),
+                    None => format!("  {}", file.path),
+                };
+                Line::from(vec![Span::styled(symbol, style), Span::raw(text)])
+            })
+            .collect();
+
+        if data.file_list_truncated {
+            lines.push(Line::from("... file list truncated"));
+        }
+
+        lines
+    }
+
+    fn diff_lines(&self) -> Vec<Line<'static>> {
+        let data = match self.inspect {
+            None => return vec![Line::from("Loading diff preview...")],
+            Some(Err(message)) => return vec![load_error_line(message)],
+            Some(Ok(data)) => data,
+        };
+
+        let mut lines: Vec<Line<'static>> = data
+            .diff_text
+            .lines()
+            .map(|line| Line::styled(line.to_string(), self.theme.diff_line_style(line)))
+            .collect();
+
+        if data.diff_truncated && !data.diff_text.contains("... diff truncated;") {
+            lines.push(Line::from(
+                "... diff truncated; open the commit in the browser or use git show for the full patch",
+            ));
+        }
+
+        lines
+    }
+
+    fn labeled_line(&self, label: &str, value: String) -> Line<'static> {
+        let mut spans = vec![Span::styled(format!("{label:<9} "), self.theme.accent)];
+        if !value.is_empty() {
+            spans.push(Span::raw(value));
+        }
+        Line::from(spans)
+    }
+}
+
+fn load_error_line(message: &str) -> Line<'static> {
+    Line::from(format!("Failed to load commit details: {}", message))
+}
+
+impl StatefulWidget for CommitDetails<'_> {
+    type State = CommitDetailsState;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        let content = self.lines(state.tab);
+        let inner_height = match &self.block {
+            Some(block) => block.inner(area).height,
+            None => area.height,
+        };
+        let max_scroll = (content.len() as u16).saturating_sub(inner_height);
+        state.scroll = state.scroll.min(max_scroll);
+
+        let mut paragraph = Paragraph::new(content).scroll((state.scroll, 0));
+        if let Some(block) = self.block {
+            paragraph = paragraph.block(block);
+        }
+        Widget::render(paragraph, area, buf);
+    }
+}
+
+impl Widget for CommitDetails<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        StatefulWidget::render(self, area, buf, &mut CommitDetailsState::default());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::model::SignatureStatus;
+    use crate::references::ReferencePattern;
+    use ratatui::style::Modifier;
+
+    fn commit() -> Commit {
+        Commit {
+            oid: "abc1234def".to_string(),
+            parents: vec!["0123456789".to_string()],
+            author: "Ada".to_string(),
+            author_email: "ada@example.com".to_string(),
+            timestamp: 0,
+            subject: "Add widgets".to_string(),
+            body: (1..=8)
+                .map(|n| format!("line {}", n))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            ..Default::default()
+        }
+    }
+
+    fn line(buf: &Buffer, y: u16) -> String {
+        (0..buf.area.width)
+            .map(|x| buf[(x, y)].symbol())
+            .collect::<String>()
+            .trim_end()
+            .to_string()
+    }
+
+    #[test]
+    fn test_summary_scroll_stops_at_end_of_content() {
+        let commit = commit();
+        let refs = Refs::default();
+        let theme = Theme::monochrome();
+        let mut buf = Buffer::empty(Rect::new(0, 0, 40, 4));
+        let mut state = CommitDetailsState::default().with_scroll(100);
+
+        StatefulWidget::render(
+            CommitDetails::new(Some(&commit), &refs, &theme),
+            buf.area,
+            &mut buf,
+            &mut state,
+        );
+        // Tab line, blank, seven fields, blank, subject, body label, eight body lines.
+        assert_eq!(state.scroll(), 20 - 4);
+        assert_eq!(line(&buf, 3), "    line 8");
+
+        state.scroll_by(-20);
+        assert_eq!(state.scroll(), 0);
+    }
+
+    #[test]
+    fn test_summary_highlights_and_lists_links() {
+        let mut commit = commit();
+        commit.subject = "Fix crash (#12)".to_string();
+        commit.body = "Tracked in OPS-7.".to_string();
+        let refs = Refs::default();
+        let theme = Theme::monochrome();
+        let finder = ReferenceFinder::new(
+            None,
+            vec![ReferencePattern::new(r"OPS-\d+", "https://t.example.com/$0").unwrap()],
+        );
+        let details = CommitDetails::new(Some(&commit), &refs, &theme).references(&finder);
+        let lines = details.summary_lines(&commit);
+
+        let subject = &lines[8];
+        assert_eq!(subject.spans[2].content, "#12");
+        assert!(subject.spans[2]
+            .style
+            .add_modifier
+            .contains(Modifier::UNDERLINED));
+        assert!(subject.spans[2].style.add_modifier.contains(Modifier::BOLD));
+        let text: Vec<String> = lines[10..].iter().map(|line| line.to_string()).collect();
+        assert_eq!(
+            text,
+            [
+                "    Tracked in OPS-7.",
+                "",
+                "Links     ",
+                "     1  OPS-7  https://t.example.com/OPS-7",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_summary_shows_committer_and_commit_date() {
+        let mut commit = commit();
+        commit.author_tz_offset = 120;
+        commit.committer = "Bob".to_string();
+        commit.committer_email = "bob@example.com".to_string();
+        commit.raw_committer = "bob".to_string();
+        commit.raw_committer_email = "bob@laptop.local".to_string();
+        commit.commit_timestamp = 3 * 86_400 + 3_600;
+        let refs = Refs::default();
+        let theme = Theme::monochrome();
+        let lines = CommitDetails::new(Some(&commit), &refs, &theme).summary_lines(&commit);
+        let text: Vec<String> = lines[1..5].iter().map(|line| line.to_string()).collect();
+        assert_eq!(text[0], "Author    Ada <ada@example.com>");
+        assert!(text[1].starts_with("Date      1970-01-01 02:00:00 +0200  ("));
+        assert_eq!(text[2], "Committer Bob <bob@example.com>");
+        assert_eq!(
+            text[3],
+            "Committed 1970-01-04 01:00:00 +0000  (committed 3 days later)"
+        );
+
+        let lines = CommitDetails::new(Some(&commit), &refs, &theme)
+            .raw_identity(true)
+            .summary_lines(&commit);
+        assert_eq!(lines[3].to_string(), "Committer bob <bob@laptop.local>");
+
+        commit.commit_timestamp = commit.timestamp;
+        commit.signature = Signature {
+            status: SignatureStatus::GoodUnknownValidity,
+            signer: String::new(),
+            key: "SHA256:abc".to_string(),
+        };
+        let lines = CommitDetails::new(Some(&commit), &refs, &theme).summary_lines(&commit);
+        assert_eq!(
+            lines[4].to_string(),
+            "Signature good signature from an untrusted key  (key SHA256:abc)"
+        );
+        assert!(lines[5].to_string().starts_with("Parents"));
+    }
+
+    #[test]
+    fn test_summary_shows_trailers_as_a_table() {
+        let mut commit = commit();
+        commit.body = "Explain.\n\nSigned-off-by: Ada <ada@example.com>\nFixes: #4".to_string();
+        commit.trailers = crate::git::parser::split_trailers(&commit.body).1;
+        let refs = Refs::default();
+        let theme = Theme::monochrome();
+        let lines = CommitDetails::new(Some(&commit), &refs, &theme).summary_lines(&commit);
+        let text: Vec<String> = lines[10..].iter().map(|line| line.to_string()).collect();
+        assert_eq!(
+            text,
+            [
+                "    Explain.",
+                "",
+                "Trailers  ",
+                "    Signed-off-by  Ada <ada@example.com>",
+                "    Fixes          #4",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_files_tab_shows_loading_and_errors() {
+        let commit = commit();
+        let refs = Refs::default();
+        let theme = Theme::monochrome();
+        let mut state = CommitDetailsState::default().with_tab(DetailsTab::Files);
+
+        let mut buf = Buffer::empty(Rect::new(0, 0, 60, 3));
+        let details = CommitDetails::new(Some(&commit), &refs, &theme);
+        StatefulWidget::render(details, buf.area, &mut buf, &mut state);
+        assert_eq!(line(&buf, 0), "[Summary]  [Files]  [Diff]");
+        assert_eq!(line(&buf, 2), "Loading changed files...");
+
+        let mut buf = Buffer::empty(Rect::new(0, 0, 60, 3));
+        let details = CommitDetails::new(Some(&commit), &refs, &theme).inspect_error("bad object");
+        StatefulWidget::render(details, buf.area, &mut buf, &mut state);
+        assert_eq!(line(&buf, 2), "Failed to load commit details: bad object");
+
+        assert_eq!(tab_at_column(11), Some(DetailsTab::Files));
+        assert_eq!(tab_at_column(9), None);
+    }
+}
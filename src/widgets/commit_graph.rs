@@ -0,0 +1,353 @@
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    text::{Line, Span},
+    widgets::{Block, List, ListItem, ListState, StatefulWidget, Widget},
+};
+
+use crate::git::model::{BranchTracking, Commit, Refs, SignatureTrust};
+use crate::graph::{graph_lines, GraphGlyphs, GraphRow, GraphStyle};
+use crate::theme::Theme;
+use crate::util::short_hash;
+
+/// Scroll position and selection of a [`CommitGraph`]. Both count shown
+/// commits, not terminal lines.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct CommitGraphState {
+    selected: Option<usize>,
+    offset: usize,
+}
+
+impl CommitGraphState {
+    /// Start scrolled so that the shown commit at `offset` is at the top.
+    pub fn with_offset(mut self, offset: usize) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// Start with the shown commit at `selected` highlighted.
+    pub fn with_selected(mut self, selected: Option<usize>) -> Self {
+        self.selected = selected;
+        self
+    }
+
+    /// Index of the highlighted commit among the shown ones.
+    pub fn selected(&self) -> Option<usize> {
+        self.selected
+    }
+
+    /// Highlight the shown commit at `index`, or nothing.
+    pub fn select(&mut self, index: Option<usize>) {
+        self.selected = index;
+    }
+
+    /// Index of the first shown commit in view. Rendering scrolls it to keep
+    /// the selection visible.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+}
+
+/// Scrollable commit list with the graph drawn beside each commit: short
+/// hash, ref labels and subject, in the colours of a [`Theme`].
+///
+/// ```no_run
+/// # use gitviz::git::model::{Commit, Refs};
+/// # use gitviz::graph::{compute_layout, LayoutOptions};
+/// # use gitviz::theme::Theme;
+/// # use gitviz::widgets::{CommitGraph, CommitGraphState};
+/// # use ratatui::widgets::{Block, Borders};
+/// # fn draw(frame: &mut ratatui::Frame, commits: &[Commit], refs: &Refs, state: &mut CommitGraphState) {
+/// let graph = compute_layout(commits, &LayoutOptions::default());
+/// let theme = Theme::dark();
+/// let widget = CommitGraph::new(commits, &graph, refs, &theme)
+///     .block(Block::default().borders(Borders::ALL).title(" Commits "));
+/// frame.render_stateful_widget(widget, frame.area(), state);
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct CommitGraph<'a> {
+    commits: &'a [Commit],
+    graph: &'a [GraphRow],
+    refs: &'a Refs,
+    theme: &'a Theme,
+    rows: Option<&'a [usize]>,
+    style: GraphStyle,
+    block: Option<Block<'a>>,
+}
+
+impl<'a> CommitGraph<'a> {
+    /// `graph` is the layout of `commits` from
+    /// [`compute_layout`](crate::graph::compute_layout).
+    pub fn new(
+        commits: &'a [Commit],
+        graph: &'a [GraphRow],
+        refs: &'a Refs,
+        theme: &'a Theme,
+    ) -> Self {
+        CommitGraph {
+            commits,
+            graph,
+            refs,
+            theme,
+            rows: None,
+            style: GraphStyle::default(),
+            block: None,
+        }
+    }
+
+    /// Show only these commits, given as indices into `commits`, such as the
+    /// matches of a search. By default every commit is shown except those a
+    /// first-parent layout hides.
+    pub fn rows(mut self, rows: &'a [usize]) -> Self {
+        self.rows = Some(rows);
+        self
+    }
+
+    /// Glyphs and spacing of the graph.
+    pub fn style(mut self, style: GraphStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Surround the list with a block.
+    pub fn block(mut self, block: Block<'a>) -> Self {
+        self.block = Some(block);
+        self
+    }
+
+    fn item(&self, commit: &'a Commit, row: &GraphRow) -> ListItem<'a> {
+        let theme = self.theme;
+        let mut lines = graph_lines(row, self.style).into_iter().map(|segments| {
+            segments
+                .into_iter()
+                .map(|(text, color)| Span::styled(text, theme.line_style(color)))
+                .collect::<Vec<Span>>()
+        });
+
+        let mut spans = lines.next().unwrap_or_default();
+        spans.extend([
+            Span::raw(" "),
+            Span::styled(short_hash(&commit.oid).to_string(), theme.accent),
+            Span::raw(" "),
+        ]);
+
+        let trust = commit.signature.status.trust();
+        if let Some(badge) = signature_badge(trust, self.style.glyphs) {
+            spans.push(Span::styled(badge, theme.signature_style(trust)));
+            spans.push(Span::raw(" "));
+        }
+
+        for (kind, label, tracking) in self.refs.tracked_labels(&commit.oid) {
+            let marker = tracking.map(BranchTracking::marker).unwrap_or_default();
+            let label = if marker.is_empty() {
+                label
+            } else {
+                format!("{} {}", label, marker)
+            };
+            spans.push(Span::styled(format!("[{}]", label), theme.ref_style(kind)));
+            spans.push(Span::raw(" "));
+        }
+
+        spans.push(Span::raw(commit.subject.as_str()));
+        let mut text = vec![Line::from(spans)];
+        text.extend(lines.map(Line::from));
+        ListItem::new(text)
+    }
+}
+
+/// Mark drawn after the hash of a signed commit.
+fn signature_badge(trust: SignatureTrust, glyphs: GraphGlyphs) -> Option<&'static str> {
+    let ascii = glyphs == GraphGlyphs::Ascii;
+    match trust {
+        SignatureTrust::Unsigned => None,
+        SignatureTrust::Verified => Some(if ascii { "+" } else { "✓" }),
+        SignatureTrust::Unknown => Some("?"),
+        SignatureTrust::Bad => Some(if ascii { "!" } else { "✗" }),
+    }
+}
+
+impl StatefulWidget for CommitGraph<'_> {
+    type State = CommitGraphState;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        let all_rows: Vec<usize>;
+        let rows = match self.rows {
+            Some(rows) => rows,
+            None => {
+                all_rows = (0..self.commits.len())
+                    .filter(|&index| !self.graph[index].hidden)
+                    .collect();
+                &all_rows
+            }
+        };
+
+        let items: Vec<ListItem> = rows
+            .iter()
+            .map(|&index| self.item(&self.commits[index], &self.graph[index]))
+            .collect();
+
+        let mut list = List::new(items)
+            .highlight_style(self.theme.selection)
+            .highlight_symbol("▶ ");
+        if let Some(block) = self.block {
+            list = list.block(block);
+        }
+
+        let mut list_state = ListState::default().with_offset(state.offset);
+        if !rows.is_empty() {
+            list_state.select(state.selected);
+        }
+        StatefulWidget::render(list, area, buf, &mut list_state);
+        state.offset = list_state.offset();
+    }
+}
+
+impl Widget for CommitGraph<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        StatefulWidget::render(self, area, buf, &mut CommitGraphState::default());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::model::{Signature, SignatureStatus};
+    use crate::graph::{compute_layout, LayoutOptions};
+
+    fn commit(oid: &str, parents: &[&str]) -> Commit {
+        Commit {
+            oid: oid.to_string(),
+            parents: parents.iter().map(|parent| parent.to_string()).collect(),
+            author: "Ada".to_string(),
+            author_email: "ada@example.com".to_string(),
+            timestamp: 0,
+            subject: format!("subject {}", oid),
+            body: String::new(),
+            ..Default::default()
+        }
+    }
+
+    fn rendered(buf: &Buffer) -> Vec<String> {
+        (0..buf.area.height)
+            .map(|y| {
+                (0..buf.area.width)
+                    .map(|x| buf[(x, y)].symbol())
+                    .collect::<String>()
+                    .trim_end()
+                    .to_string()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_renders_rows_and_keeps_selection_in_view() {
+        let commits = vec![
+            commit("c3", &["c2"]),
+            commit("c2", &["c1"]),
+            commit("c1", &[]),
+        ];
+        let graph = compute_layout(&commits, &LayoutOptions::default());
+        let refs = Refs::default();
+        let theme = Theme::monochrome();
+
+        let mut buf = Buffer::empty(Rect::new(0, 0, 20, 2));
+        let mut state = CommitGraphState::default().with_selected(Some(2));
+        StatefulWidget::render(
+            CommitGraph::new(&commits, &graph, &refs, &theme),
+            buf.area,
+            &mut buf,
+            &mut state,
+        );
+        assert_eq!(state.offset(), 1);
+        assert_eq!(rendered(&buf), ["  ● c2 subject c2", "▶ ● c1 subject c1"]);
+
+        let mut buf = Buffer::empty(Rect::new(0, 0, 20, 2));
+        Widget::render(
+            CommitGraph::new(&commits, &graph, &refs, &theme).rows(&[0, 2]),
+            buf.area,
+            &mut buf,
+        );
+        assert_eq!(rendered(&buf), ["● c3 subject c3", "● c1 subject c1"]);
+    }
+
+    #[test]
+    fn test_branch_labels_show_upstream_divergence() {
+        let commits = vec![commit("c1", &[])];
+        let graph = compute_layout(&commits, &LayoutOptions::default());
+        let mut refs = Refs {
+            head_oid: "c1".to_string(),
+            head_name: Some("refs/heads/main".to_string()),
+            branches: vec![("refs/heads/main".to_string(), "c1".to_string())],
+            ..Refs::default()
+        };
+        refs.tracking.insert(
+            "refs/heads/main".to_string(),
+            BranchTracking {
+                upstream: "refs/remotes/origin/main".to_string(),
+                ahead: 2,
+                behind: 1,
+                gone: false,
+            },
+        );
+        let theme = Theme::monochrome();
+
+        let mut buf = Buffer::empty(Rect::new(0, 0, 40, 1));
+        Widget::render(
+            CommitGraph::new(&commits, &graph, &refs, &theme),
+            buf.area,
+            &mut buf,
+        );
+        assert_eq!(rendered(&buf), ["● c1 [HEAD -> main ↑2 ↓1] subject c1"]);
+        assert_eq!(refs.labels_for("c1"), ["HEAD -> main"]);
+    }
+
+    #[test]
+    fn test_signature_badges() {
+        let signed = |oid: &str, parents: &[&str], status| Commit {
+            signature: Signature {
+                status,
+                ..Signature::default()
+            },
+            ..commit(oid, parents)
+        };
+        let commits = vec![
+            signed("c3", &["c2"], SignatureStatus::Bad),
+            signed("c2", &["c1"], SignatureStatus::GoodUnknownValidity),
+            signed("c1", &["c0"], SignatureStatus::Good),
+            commit("c0", &[]),
+        ];
+        let graph = compute_layout(&commits, &LayoutOptions::default());
+        let refs = Refs::default();
+        let theme = Theme::default();
+
+        let mut buf = Buffer::empty(Rect::new(0, 0, 20, 4));
+        Widget::render(
+            CommitGraph::new(&commits, &graph, &refs, &theme),
+            buf.area,
+            &mut buf,
+        );
+        assert_eq!(
+            rendered(&buf),
+            [
+                "● c3 ✗ subject c3",
+                "● c2 ? subject c2",
+                "● c1 ✓ subject c1",
+                "● c0 subject c0"
+            ]
+        );
+        assert_eq!(buf[(5, 2)].style().fg, theme.signature_good.fg);
+
+        let ascii = GraphStyle {
+            glyphs: GraphGlyphs::Ascii,
+            ..GraphStyle::default()
+        };
+        let mut buf = Buffer::empty(Rect::new(0, 0, 20, 1));
+        Widget::render(
+            CommitGraph::new(&commits, &graph, &refs, &theme).style(ascii),
+            buf.area,
+            &mut buf,
+        );
+        assert_eq!(rendered(&buf), ["* c3 ! subject c3"]);
+    }
+}
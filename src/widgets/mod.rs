@@ -0,0 +1,5 @@
+mod commit_details;
+mod commit_graph;
+
+pub use commit_details::{tab_at_column, CommitDetails, CommitDetailsState, DetailsTab};
+pub use commit_graph::{CommitGraph, CommitGraphState};